@@ -4,15 +4,20 @@ use tokio::sync::mpsc;
 use tokio::task;
 use tokio::time;
 
-use dxcllistener::Listener;
+use dxcllistener::{Listener, ListenerPool};
 
 #[tokio::main]
 async fn main() {
-    // Create two listeners
-    let mut listeners: Vec<dxcllistener::Listener> = vec![
-        Listener::new("example.com".into(), 1234, "INVALID-1".into()),
-        Listener::new("example.net".into(), 5678, "INVALID-2".into()),
-    ];
+    // Create pool of two listeners, labeled to tell their lines apart
+    let mut pool = ListenerPool::new();
+    for (label, host, port, callsign) in [
+        ("com", "example.com", 1234, "INVALID-1"),
+        ("net", "example.net", 5678, "INVALID-2"),
+    ] {
+        let mut listener = Listener::new(host.into(), port, callsign.into());
+        listener.label = Some(label.into());
+        pool.add(listener);
+    }
 
     // Create channel to implement graceful shutdown
     let (shtdwn_tx, mut shtdwn_rx) = mpsc::unbounded_channel::<()>();
@@ -24,52 +29,46 @@ async fn main() {
     });
 
     // Communication channel between listeners and receiver
-    let (spot_tx, mut spot_rx) = mpsc::unbounded_channel::<String>();
+    let (spot_tx, mut spot_rx) = mpsc::unbounded_channel::<dxcllistener::ReceivedLine>();
 
     // Handle incoming spots
     let receiver = task::spawn(async move {
         while let Some(spot) = spot_rx.recv().await {
-            println!("{}", spot);
+            println!(
+                "{} [{}] {}",
+                spot.received.format("%H:%M:%S"),
+                spot.source,
+                spot.raw
+            );
         }
     });
 
     // Start listening for spots
-    for lis in listeners.iter_mut() {
-        lis.listen(spot_tx.clone(), Duration::from_millis(1000))
-            .await
-            .unwrap();
-    }
+    pool.listen(spot_tx, Duration::from_millis(1000))
+        .await
+        .unwrap();
 
     // Main loop
-    while !listeners.is_empty() {
+    loop {
         // Check for unexpectedly stopped listeners
-        if let Some(pos) = listeners.iter_mut().position(|x| !x.is_running()) {
-            let res = listeners[pos].join().await.unwrap_err();
-            println!(
-                "Listener {}@{}:{} stopped unexpectedly ({})",
-                listeners[pos].callsign, listeners[pos].host, listeners[pos].port, res
-            );
-            listeners.remove(pos);
+        for (idx, err) in pool.reap().await {
+            let listener = pool.iter().nth(idx).unwrap();
+            println!("Listener {} stopped unexpectedly ({})", listener, err);
+        }
+        if pool.status().running() == 0 {
+            break;
         }
 
         // Either wait a few milliseconds or receive signal to stop listeners
         tokio::select! {
             _ = time::sleep(time::Duration::from_millis(250)) => (),
             _ = shtdwn_rx.recv() => {
-                for l in listeners.iter_mut() {
-                    l.request_stop().unwrap();
-                }
-                for l in listeners.iter_mut() {
-                    l.join().await.unwrap();
-                }
+                pool.stop().await;
                 break;
             }
         }
     }
 
-    // Drop last spot sender to quit receiving task
-    drop(spot_tx);
-
     // Receiver will stop its execution after the last listener stopped
     receiver.await.unwrap();
 }
@@ -28,7 +28,7 @@ async fn main() {
 
         // Process spots
         while let Some(spot) = rx.recv().await {
-            println!("{}", spot);
+            println!("{}", spot.raw);
         }
 
         // Evaluate error reason why the listener stopped unexpectedly
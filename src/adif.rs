@@ -0,0 +1,129 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::band::Mode;
+use crate::line::ReceivedLine;
+use crate::message::{ClusterMessage, DigitalMode};
+use crate::record::SpotRecord;
+
+// Version of the ADIF specification the records are written in
+const ADIF_VERSION: &str = "3.1.4";
+
+// Program identifier written to the header and used for application-defined fields
+const PROGRAM_ID: &str = "DXCLLISTENER";
+
+/// Create the header of an ADIF file.
+pub fn adif_header() -> String {
+    let mut header = String::from("ADIF export of received spots\n");
+    push_field(&mut header, "ADIF_VER", ADIF_VERSION);
+    push_field(&mut header, "PROGRAMID", PROGRAM_ID);
+    push_field(&mut header, "PROGRAMVERSION", env!("CARGO_PKG_VERSION"));
+    header.push_str("<EOH>\n");
+    header
+}
+
+impl ReceivedLine {
+    /// Convert a spot into a single ADIF record without trailing line break.
+    ///
+    /// The record holds the callsign, date and time, frequency, band and mode of the spotted station,
+    /// its locator and DXCC entity if known, and the comment. Records are marked as SWL reports,
+    /// the spotter is written to the application-defined field `APP_DXCLLISTENER_SPOTTER`.
+    /// Characters other than printable ASCII are removed from all fields, fields left empty are omitted.
+    ///
+    /// # Result
+    ///
+    /// Returns the record or `None` if the line is not a parsed spot.
+    pub fn to_adif(&self) -> Option<String> {
+        let record = SpotRecord::from_line(self)?;
+        let mut adif = String::new();
+
+        push_field(&mut adif, "CALL", &record.dx);
+        push_field(
+            &mut adif,
+            "QSO_DATE",
+            &record.time.format("%Y%m%d").to_string(),
+        );
+        push_field(
+            &mut adif,
+            "TIME_ON",
+            &record.time.format("%H%M%S").to_string(),
+        );
+        push_field(
+            &mut adif,
+            "FREQ",
+            &format!("{:.6}", record.freq as f64 / 1_000_000.0),
+        );
+        if let Some(band) = record.band {
+            push_field(&mut adif, "BAND", &band.to_string());
+        }
+        if let Some((mode, submode)) = adif_mode(self, record.mode) {
+            push_field(&mut adif, "MODE", mode);
+            if let Some(submode) = submode {
+                push_field(&mut adif, "SUBMODE", &submode);
+            }
+        }
+        if let Some(grid) = record.dx_grid.as_deref() {
+            push_field(&mut adif, "GRIDSQUARE", grid);
+        }
+        if let Some(dxcc) = record.dx_dxcc {
+            push_field(&mut adif, "DXCC", &dxcc.to_string());
+        }
+        if let Some(comment) = record.comment.as_deref() {
+            push_field(&mut adif, "COMMENT", comment);
+        }
+        push_field(&mut adif, "SWL", "Y");
+        push_field(
+            &mut adif,
+            &format!("APP_{}_SPOTTER", PROGRAM_ID),
+            &record.spotter,
+        );
+        adif.push_str("<EOR>");
+
+        Some(adif)
+    }
+}
+
+/// Get the ADIF mode and submode of a spot, if known.
+fn adif_mode(line: &ReceivedLine, mode: Option<Mode>) -> Option<(&'static str, Option<String>)> {
+    let parsed = line.parsed.as_ref()?;
+
+    if let Some(digital) = parsed.digital() {
+        return Some(match digital.mode {
+            DigitalMode::Ft8 => ("FT8", None),
+            DigitalMode::Ft4 => ("MFSK", Some("FT4".into())),
+            DigitalMode::Jt65 => ("JT65", None),
+            DigitalMode::Jt9 => ("JT9", None),
+            DigitalMode::Js8 => ("MFSK", Some("JS8".into())),
+            DigitalMode::Msk144 => ("MSK144", None),
+            DigitalMode::Q65 => ("MFSK", Some("Q65".into())),
+        });
+    }
+
+    if let ClusterMessage::SkimmerSpot(spot) = parsed {
+        let name = spot.mode.to_ascii_uppercase();
+        if name == "RTTY" {
+            return Some(("RTTY", None));
+        } else if name.starts_with("PSK") {
+            return Some(("PSK", Some(name)));
+        }
+    }
+
+    match mode? {
+        Mode::Cw => Some(("CW", None)),
+        Mode::Ssb => Some(("SSB", None)),
+        Mode::Am => Some(("AM", None)),
+        Mode::Fm => Some(("FM", None)),
+        Mode::Digi => None,
+    }
+}
+
+/// Append a field in the form `<NAME:LENGTH>VALUE` followed by a space.
+/// Characters other than printable ASCII are removed, as the length is given in bytes.
+/// The field is omitted if the value is empty afterwards.
+fn push_field(adif: &mut String, name: &str, value: &str) {
+    let value: String = value.chars().filter(|c| matches!(c, ' '..='~')).collect();
+    if !value.is_empty() {
+        adif.push_str(&format!("<{}:{}>{} ", name, value.len(), value));
+    }
+}
@@ -0,0 +1,118 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use crate::expr::{FilterExpr, FilterParseError};
+use crate::filter::Filter;
+use crate::line::ReceivedLine;
+
+/// Spot matching an alert rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Alert {
+    /// Label of the matching rule
+    pub label: String,
+
+    /// Matching spot
+    pub line: ReceivedLine,
+}
+
+/// Rule based alerting, e.g. to get notified about a new DXCC entity on 17 m.
+///
+/// Every delivered spot is checked against the registered rules, an `Alert` is sent to the alert channel for every matching rule.
+/// Lines which are not spots never trigger an alert.
+/// Add the alerts to `Listener::alerts` or call `Alerts::check` for every received line.
+#[derive(Clone)]
+pub struct Alerts {
+    /// Registered rules with their label
+    rules: Vec<(String, Arc<dyn Filter>)>,
+
+    /// Channel to send alerts to
+    channel: mpsc::UnboundedSender<Alert>,
+}
+
+impl Alerts {
+    /// Create a new set of alert rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel`: Channel to send alerts to
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of `Alerts` without any rules.
+    pub fn new(channel: mpsc::UnboundedSender<Alert>) -> Self {
+        Self {
+            rules: Vec::new(),
+            channel,
+        }
+    }
+
+    /// Register a rule given as filter expression, see `FilterExpr`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label`: Label attached to the alerts of the rule
+    /// * `expr`: Filter expression matching the spots to alert on, e.g. `band = 17m and dx_dxcc = 61`
+    ///
+    /// # Result
+    ///
+    /// Returns the alerts with the rule registered or an error if the expression is invalid.
+    pub fn with_rule(self, label: &str, expr: &str) -> Result<Self, FilterParseError> {
+        Ok(self.with_filter(label, FilterExpr::parse(expr)?))
+    }
+
+    /// Register a rule given as filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `label`: Label attached to the alerts of the rule
+    /// * `filter`: Filter accepting the spots to alert on
+    pub fn with_filter<F: Filter + 'static>(mut self, label: &str, filter: F) -> Self {
+        self.rules.push((label.into(), Arc::new(filter)));
+        self
+    }
+
+    /// Check a received line against all rules and send an alert for every matching rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `line`: Received line
+    ///
+    /// # Result
+    ///
+    /// Returns the number of alerts sent.
+    pub fn check(&self, line: &ReceivedLine) -> usize {
+        let spot = line
+            .parsed
+            .as_ref()
+            .is_some_and(|msg| msg.calls().is_some() && msg.freq().is_some());
+        if !spot {
+            return 0;
+        }
+
+        self.rules
+            .iter()
+            .filter(|(_, filter)| filter.accept(line))
+            .filter(|(label, _)| {
+                self.channel
+                    .send(Alert {
+                        label: label.clone(),
+                        line: line.clone(),
+                    })
+                    .is_ok()
+            })
+            .count()
+    }
+}
+
+impl fmt::Debug for Alerts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels: Vec<&str> = self.rules.iter().map(|(label, _)| label.as_str()).collect();
+        f.debug_struct("Alerts").field("rules", &labels).finish()
+    }
+}
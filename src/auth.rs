@@ -0,0 +1,590 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use regex::{Regex, RegexBuilder};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::time;
+
+use crate::connection::Connection;
+use crate::listener::ListenError;
+use crate::message::ClusterMessage;
+
+// Authentication tokens sent by cluster servers.
+const AUTH_TOKEN: [&str; 2] = ["login:", "Please enter your call:"];
+
+// Prompts asking new users for their name or QTH after the callsign was sent.
+const NAME_PROMPT: &str = r"(?i)(enter|what is) your (first )?name[^\r\n]*[:>?]\s*$";
+const QTH_PROMPT: &str = r"(?i)(enter|what is) your (qth|location)[^\r\n]*[:>?]\s*$";
+
+// Prompt asking for the password after the callsign was sent.
+const PASSWORD_PROMPT: &str = r"(?i)password\s*:\s*$";
+
+// Messages of servers refusing the login, only checked in the line answering the login.
+const REJECT_PATTERN: &str = r"(?i)(invalid call|not a valid call|access denied|not allowed|(login|connection) (refused|rejected|denied)|you are barred|not authori[sz]ed)";
+
+// Messages of servers refusing the login because the callsign is already connected.
+const IN_USE_PATTERN: &str = r"(?i)(already (connected|logged in|in use)|is (currently )?connected (to|at|on)|callsign in use)";
+
+/// Pattern to detect an authentication prompt sent by a cluster server
+#[derive(Debug, Clone)]
+pub enum AuthPrompt {
+    /// Received data ends with the given string
+    Suffix(String),
+
+    /// Received data matches the given regular expression
+    Regex(Regex),
+}
+
+impl AuthPrompt {
+    /// Create a prompt pattern from a regular expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern`: Regular expression
+    /// * `case_insensitive`: True to ignore the case while matching
+    ///
+    /// # Result
+    ///
+    /// Returns the prompt pattern or an error if the regular expression is invalid.
+    pub fn regex(pattern: &str, case_insensitive: bool) -> Result<Self, regex::Error> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map(AuthPrompt::Regex)
+    }
+
+    /// Check if the received data matches the prompt pattern.
+    pub fn matches(&self, data: &str) -> bool {
+        match self {
+            AuthPrompt::Suffix(suffix) => data.ends_with(suffix.as_str()),
+            AuthPrompt::Regex(regex) => regex.is_match(data),
+        }
+    }
+
+    /// Check if the received data matches the prompt pattern while ignoring the case.
+    /// Regular expressions are matched as they are, use `AuthPrompt::regex` to ignore their case.
+    pub fn matches_ignore_case(&self, data: &str) -> bool {
+        match self {
+            AuthPrompt::Suffix(suffix) => ends_with_ignore_case(data, suffix),
+            AuthPrompt::Regex(regex) => regex.is_match(data),
+        }
+    }
+}
+
+/// Check if a given string ends with the given suffix while ignoring the case.
+fn ends_with_ignore_case(data: &str, suffix: &str) -> bool {
+    data.to_lowercase().ends_with(&suffix.to_lowercase())
+}
+
+/// Login procedure executed right after the connection to the server is established.
+///
+/// Implement this trait to support servers requiring a custom login dialog,
+/// e.g. callsign followed by a password and a terminal type.
+#[async_trait]
+pub trait Authenticator: Send + Sync {
+    /// Authenticate at the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn`: Connection to the server
+    /// * `callsign`: Callsign to use for authentication
+    ///
+    /// # Result
+    ///
+    /// Returns `Ok(())` if the login succeeded and the server is ready to send spots.
+    async fn authenticate(&self, conn: &mut Connection, callsign: &str) -> Result<(), ListenError>;
+}
+
+/// Authenticator for feeds without login, e.g. spots broadcast as AX.25 UI frames.
+/// Nothing is sent to the server, the received lines are delivered right away.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoAuthenticator;
+
+#[async_trait]
+impl Authenticator for NoAuthenticator {
+    async fn authenticate(
+        &self,
+        _conn: &mut Connection,
+        _callsign: &str,
+    ) -> Result<(), ListenError> {
+        Ok(())
+    }
+}
+
+/// Default timeout for a single read while waiting for the authentication prompt
+pub const DEFAULT_AUTH_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Default number of reads while waiting for the authentication prompt
+pub const DEFAULT_AUTH_RETRIES: u32 = 5;
+
+/// Default authenticator.
+/// Waits for one of the known authentication prompts and answers it with the callsign.
+#[derive(Debug, Clone)]
+pub struct PromptAuthenticator {
+    /// Additional authentication prompts to look for besides the built-in ones
+    pub prompts: Vec<AuthPrompt>,
+
+    /// Timeout for a single read while waiting for the authentication prompt
+    pub timeout: Duration,
+
+    /// Number of reads until the authentication is aborted
+    pub retries: u32,
+
+    /// Ignore the case while matching the authentication prompts
+    pub case_insensitive: bool,
+
+    /// Delay between receiving the authentication prompt and sending the callsign
+    pub callsign_delay: Duration,
+
+    /// Delay between two characters while sending the callsign.
+    /// The callsign is sent at once if not set.
+    pub typing_delay: Option<Duration>,
+
+    /// Name to answer with if the server asks for it after the login
+    pub name: Option<String>,
+
+    /// QTH to answer with if the server asks for it after the login
+    pub qth: Option<String>,
+
+    /// Password to answer with if the server asks for it after the callsign
+    pub password: Option<String>,
+}
+
+impl Default for PromptAuthenticator {
+    fn default() -> Self {
+        Self {
+            prompts: Vec::new(),
+            timeout: DEFAULT_AUTH_TIMEOUT,
+            retries: DEFAULT_AUTH_RETRIES,
+            case_insensitive: false,
+            callsign_delay: Duration::ZERO,
+            typing_delay: None,
+            name: None,
+            qth: None,
+            password: None,
+        }
+    }
+}
+
+impl PromptAuthenticator {
+    /// Check if a given string ends with one of the built-in authentication tokens
+    /// or matches one of the additionally configured prompts.
+    fn is_auth_token(&self, token: &str) -> bool {
+        if self.case_insensitive {
+            AUTH_TOKEN
+                .iter()
+                .any(|key| ends_with_ignore_case(token, key))
+                || self
+                    .prompts
+                    .iter()
+                    .any(|prompt| prompt.matches_ignore_case(token))
+        } else {
+            AUTH_TOKEN.iter().any(|key| token.ends_with(key))
+                || self.prompts.iter().any(|prompt| prompt.matches(token))
+        }
+    }
+
+    /// Wait for the password prompt and answer it with the configured password.
+    /// Nothing is done if no password is configured or the server does not ask for it.
+    async fn answer_password_prompt(&self, conn: &mut Connection) -> Result<(), ListenError> {
+        static PASSWORD: OnceLock<Regex> = OnceLock::new();
+        let password_prompt = PASSWORD.get_or_init(|| Regex::new(PASSWORD_PROMPT).unwrap());
+
+        let password = match self.password.as_deref() {
+            Some(password) => password,
+            None => return Ok(()),
+        };
+        let mut buf = vec![];
+
+        for _ in 0..self.retries.max(1) {
+            let res = time::timeout(self.timeout, conn.read_until(b':', &mut buf)).await;
+            let timed_out = match res {
+                Ok(inner) => inner.map(|_| false)?,
+                Err(_) => true,
+            };
+
+            // Only the last, not yet terminated line may contain the prompt
+            let text = String::from_utf8_lossy(&buf);
+            let tail = text.rsplit('\n').next().unwrap_or_default();
+
+            if password_prompt.is_match(tail) {
+                return conn.send_line(password).await;
+            } else if timed_out {
+                break;
+            }
+        }
+
+        // Server did not ask for a password, hand back the received data
+        conn.unread(&buf);
+        Ok(())
+    }
+
+    /// Answer the name and QTH prompts some servers show to new users after the login.
+    /// Stops as soon as all configured answers were sent or the server stays silent.
+    /// Data received in the meantime, except for the answered prompts, is handed back afterwards.
+    async fn answer_user_prompts(&self, conn: &mut Connection) -> Result<(), ListenError> {
+        static NAME: OnceLock<Regex> = OnceLock::new();
+        static QTH: OnceLock<Regex> = OnceLock::new();
+        let name_prompt = NAME.get_or_init(|| Regex::new(NAME_PROMPT).unwrap());
+        let qth_prompt = QTH.get_or_init(|| Regex::new(QTH_PROMPT).unwrap());
+
+        let mut name = self.name.as_deref();
+        let mut qth = self.qth.as_deref();
+        let mut retries = self.retries.max(1);
+        let mut buf = vec![];
+        let mut received = vec![];
+
+        while (name.is_some() || qth.is_some()) && retries > 0 {
+            let res = time::timeout(self.timeout, conn.read_until(b':', &mut buf)).await;
+            let timed_out = match res {
+                Ok(inner) => inner.map(|_| false)?,
+                Err(_) => true,
+            };
+
+            // Only the last, not yet terminated line may contain a prompt
+            let text = String::from_utf8_lossy(&buf);
+            let tail = text.rsplit('\n').next().unwrap_or_default();
+            let answered = if name.is_some() && name_prompt.is_match(tail) {
+                name.take()
+            } else if qth.is_some() && qth_prompt.is_match(tail) {
+                qth.take()
+            } else {
+                None
+            };
+
+            if let Some(answer) = answered {
+                conn.send_line(answer).await?;
+
+                // Keep the lines preceding the answered prompt
+                let end = buf
+                    .iter()
+                    .rposition(|b| *b == b'\n')
+                    .map_or(0, |pos| pos + 1);
+                received.extend(buf.drain(..end));
+                buf.clear();
+            } else if timed_out {
+                break;
+            }
+
+            retries -= 1;
+        }
+
+        received.append(&mut buf);
+        conn.unread(&received);
+        Ok(())
+    }
+
+    /// Check whether the server refuses the login.
+    /// Reads the lines received right after the login up to the first line answering it and hands them back afterwards.
+    /// Returns `ListenError::CallsignInUse` if the answer tells that the callsign is already connected,
+    /// `ListenError::LoginRejected` if the answer is a rejection message or if the server closes the connection.
+    /// Later lines, e.g. the message of the day, are not checked, so the login is delayed by the answer only,
+    /// or by one `timeout` if the server stays silent after the login.
+    async fn verify_login(&self, conn: &mut Connection, callsign: &str) -> Result<(), ListenError> {
+        static REJECT: OnceLock<Regex> = OnceLock::new();
+        static IN_USE: OnceLock<Regex> = OnceLock::new();
+        let reject = REJECT.get_or_init(|| Regex::new(REJECT_PATTERN).unwrap());
+        let in_use = IN_USE.get_or_init(|| Regex::new(IN_USE_PATTERN).unwrap());
+
+        let mut buf = vec![];
+        let mut result = Ok(());
+
+        for _ in 0..self.retries.max(1) {
+            let start = buf.len();
+            match time::timeout(self.timeout, conn.read_until(b'\n', &mut buf)).await {
+                Ok(Ok(_)) => (),
+                Ok(Err(ListenError::ConnectionLost)) => {
+                    result = Err(ListenError::LoginRejected(
+                        "connection closed after login".into(),
+                    ));
+                    break;
+                }
+                Ok(Err(err)) => {
+                    result = Err(err);
+                    break;
+                }
+                // Server is silent, assume the login succeeded
+                Err(_) => break,
+            }
+
+            // Skip empty lines and the echoed callsign
+            let line = String::from_utf8_lossy(&buf[start..]);
+            let line = line.trim();
+            if line.is_empty() || line.eq_ignore_ascii_case(callsign) {
+                continue;
+            }
+
+            // The session started with the first prompt or spot
+            let msg = ClusterMessage::parse(line);
+            if msg.is_spot() || matches!(msg, ClusterMessage::Prompt(_)) {
+                break;
+            }
+
+            if in_use.is_match(line) {
+                result = Err(ListenError::CallsignInUse);
+            } else if reject.is_match(line) {
+                result = Err(ListenError::LoginRejected(line.into()));
+            }
+            break;
+        }
+
+        conn.unread(&buf);
+        result
+    }
+}
+
+#[async_trait]
+impl Authenticator for PromptAuthenticator {
+    async fn authenticate(&self, conn: &mut Connection, callsign: &str) -> Result<(), ListenError> {
+        // Configuration
+        let mut retries = self.retries.max(1);
+
+        // Buffer
+        let mut buf = vec![];
+
+        loop {
+            // Read data with timeout
+            let res = time::timeout(self.timeout, conn.read_until(b':', &mut buf)).await;
+
+            // Check for errors of read function
+            let timed_out = match res {
+                Ok(inner) => inner.map(|_| false)?,
+                Err(_) => true,
+            };
+
+            // Process read data, invalid characters like Latin-1 in a banner must not hide the prompt
+            let line = String::from_utf8_lossy(&buf);
+
+            // Check if the read string ends with the auth token
+            if self.is_auth_token(&line) {
+                // Send callsign to server for authentication
+                time::sleep(self.callsign_delay).await;
+                match self.typing_delay {
+                    Some(delay) => conn.send_line_paced(callsign, delay).await?,
+                    None => conn.send_line(callsign).await?,
+                }
+
+                // Complete the login dialog if requested by the server
+                self.answer_password_prompt(conn).await?;
+                self.answer_user_prompts(conn)
+                    .await
+                    .map_err(|err| match err {
+                        ListenError::ConnectionLost => {
+                            ListenError::LoginRejected("connection closed after login".into())
+                        }
+                        other => other,
+                    })?;
+
+                // Check for refused login
+                self.verify_login(conn, callsign).await?;
+                break;
+            }
+
+            // Take care of endless loop
+            retries -= 1;
+            if retries == 0 {
+                if timed_out {
+                    Err(ListenError::AuthenticationTimeout)?;
+                }
+                Err(ListenError::AuthenticationError)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the callsign with the next SSID.
+/// Increments the SSID of the callsign or appends `-1` if the callsign has no SSID.
+///
+/// # Arguments
+///
+/// * `callsign`: Callsign with or without SSID
+/// * `max`: Highest allowed SSID
+///
+/// # Result
+///
+/// Returns the callsign with the next SSID or `None` if the highest SSID is exceeded.
+pub fn next_ssid(callsign: &str, max: u8) -> Option<String> {
+    let (base, ssid) = match callsign.rsplit_once('-') {
+        Some((base, ssid)) => match ssid.parse::<u8>() {
+            Ok(ssid) => (base, ssid),
+            Err(_) => (callsign, 0),
+        },
+        None => (callsign, 0),
+    };
+
+    let next = ssid.checked_add(1)?;
+    (next <= max).then(|| format!("{}-{}", base, next))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream};
+
+    /// Open a connection to a scripted server which already sent the given data.
+    async fn connect(script: &str) -> (Connection, BufReader<DuplexStream>) {
+        let (client, mut server) = tokio::io::duplex(4096);
+        server.write_all(script.as_bytes()).await.unwrap();
+        (
+            Connection::from_transport(Box::new(client)),
+            BufReader::new(server),
+        )
+    }
+
+    /// Authenticator with a short timeout.
+    fn authenticator() -> PromptAuthenticator {
+        PromptAuthenticator {
+            timeout: Duration::from_millis(50),
+            ..Default::default()
+        }
+    }
+
+    /// Read the next line sent to the scripted server.
+    async fn read_line(server: &mut BufReader<DuplexStream>) -> String {
+        let mut line = String::new();
+        server.read_line(&mut line).await.unwrap();
+        line.trim_end().into()
+    }
+
+    #[tokio::test]
+    async fn builtin_prompt() {
+        let (mut conn, mut server) = connect("Welcome\r\nlogin: ").await;
+
+        assert_eq!(
+            authenticator().authenticate(&mut conn, "N0CALL").await,
+            Ok(())
+        );
+        assert_eq!(read_line(&mut server).await, "N0CALL");
+    }
+
+    #[tokio::test]
+    async fn custom_prompt() {
+        let (mut conn, _server) = connect("Welcome\r\nYour call please> ").await;
+        assert_eq!(
+            authenticator().authenticate(&mut conn, "N0CALL").await,
+            Err(ListenError::AuthenticationTimeout)
+        );
+
+        let (mut conn, mut server) = connect("Welcome\r\nYour call please> ").await;
+        let auth = PromptAuthenticator {
+            prompts: vec![AuthPrompt::Suffix("please> ".into())],
+            ..authenticator()
+        };
+        assert_eq!(auth.authenticate(&mut conn, "N0CALL").await, Ok(()));
+        assert_eq!(read_line(&mut server).await, "N0CALL");
+    }
+
+    #[tokio::test]
+    async fn case_insensitive_prompt() {
+        let (mut conn, _server) = connect("Welcome\r\nLOGIN: ").await;
+        assert_eq!(
+            authenticator().authenticate(&mut conn, "N0CALL").await,
+            Err(ListenError::AuthenticationTimeout)
+        );
+
+        let (mut conn, mut server) = connect("Welcome\r\nLOGIN: ").await;
+        let auth = PromptAuthenticator {
+            case_insensitive: true,
+            ..authenticator()
+        };
+        assert_eq!(auth.authenticate(&mut conn, "N0CALL").await, Ok(()));
+        assert_eq!(read_line(&mut server).await, "N0CALL");
+    }
+
+    #[tokio::test]
+    async fn regex_prompt() {
+        let (mut conn, mut server) = connect("Welcome\r\nEnter CALL > ").await;
+        let auth = PromptAuthenticator {
+            prompts: vec![AuthPrompt::regex(r"call\s*>\s*$", true).unwrap()],
+            ..authenticator()
+        };
+        assert_eq!(auth.authenticate(&mut conn, "N0CALL").await, Ok(()));
+        assert_eq!(read_line(&mut server).await, "N0CALL");
+    }
+
+    #[tokio::test]
+    async fn rejected() {
+        let (mut conn, mut server) =
+            connect("login: N0CALL\r\nSorry, N0CALL is not a valid callsign\r\n").await;
+
+        assert_eq!(
+            authenticator().authenticate(&mut conn, "N0CALL").await,
+            Err(ListenError::LoginRejected(
+                "Sorry, N0CALL is not a valid callsign".into()
+            ))
+        );
+        assert_eq!(read_line(&mut server).await, "N0CALL");
+    }
+
+    #[tokio::test]
+    async fn motd_not_rejected() {
+        let (mut conn, _server) =
+            connect("login: \r\nHello N0CALL\r\nSpots of unknown users are not allowed\r\n").await;
+        assert_eq!(
+            authenticator().authenticate(&mut conn, "N0CALL").await,
+            Ok(())
+        );
+
+        // The lines read while checking the login are handed back
+        let mut line = String::new();
+        conn.read_line(&mut line).await.unwrap();
+        assert_eq!(line.trim(), "");
+        line.clear();
+        conn.read_line(&mut line).await.unwrap();
+        assert_eq!(line.trim(), "Hello N0CALL");
+    }
+
+    #[tokio::test]
+    async fn answer_only() {
+        let (mut conn, _server) =
+            connect("login: N0CALL\r\nHello N0CALL\r\nWelcome to the node\r\n").await;
+        let auth = PromptAuthenticator {
+            timeout: Duration::from_secs(2),
+            ..authenticator()
+        };
+
+        // The lines after the answer are not waited for
+        let start = time::Instant::now();
+        assert_eq!(auth.authenticate(&mut conn, "N0CALL").await, Ok(()));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn closed_after_login() {
+        let (mut conn, mut server) = connect("login: ").await;
+        let script = tokio::spawn(async move { read_line(&mut server).await });
+
+        assert_eq!(
+            authenticator().authenticate(&mut conn, "N0CALL").await,
+            Err(ListenError::LoginRejected(
+                "connection closed after login".into()
+            ))
+        );
+        assert_eq!(script.await.unwrap(), "N0CALL");
+    }
+
+    #[tokio::test]
+    async fn callsign_in_use() {
+        let (mut conn, _server) =
+            connect("login: N0CALL\r\nSorry, N0CALL is already connected\r\n").await;
+
+        assert_eq!(
+            authenticator().authenticate(&mut conn, "N0CALL").await,
+            Err(ListenError::CallsignInUse)
+        );
+    }
+
+    #[test]
+    fn ssid() {
+        assert_eq!(next_ssid("N0CALL", 3).as_deref(), Some("N0CALL-1"));
+        assert_eq!(next_ssid("N0CALL-2", 3).as_deref(), Some("N0CALL-3"));
+        assert_eq!(next_ssid("N0CALL-3", 3), None);
+        assert_eq!(next_ssid("N0CALL-X", 3).as_deref(), Some("N0CALL-X-1"));
+        assert_eq!(next_ssid("N0CALL-255", 255), None);
+    }
+}
@@ -0,0 +1,252 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use regex::Regex;
+use std::fmt;
+use std::sync::OnceLock;
+
+// Modes named in spot comments, e.g. `CW 599` or `USB up 5`.
+const MODE_PATTERN: &str = r"(?i)\b(CW|SSB|USB|LSB|AM|FM|RTTY|PSK\d*|FT8|FT4|JT65|JT9|JS8|MSK144|Q65|SSTV|OLIVIA|MFSK\d*|DIGI)\b";
+
+// Frequency ranges of the amateur radio bands in kHz.
+const BANDS: [(Band, u64, u64); 15] = [
+    (Band::M160, 1_800, 2_000),
+    (Band::M80, 3_500, 4_000),
+    (Band::M60, 5_060, 5_450),
+    (Band::M40, 7_000, 7_300),
+    (Band::M30, 10_100, 10_150),
+    (Band::M20, 14_000, 14_350),
+    (Band::M17, 18_068, 18_168),
+    (Band::M15, 21_000, 21_450),
+    (Band::M12, 24_890, 24_990),
+    (Band::M10, 28_000, 29_700),
+    (Band::M6, 50_000, 54_000),
+    (Band::M4, 70_000, 70_500),
+    (Band::M2, 144_000, 148_000),
+    (Band::Cm70, 420_000, 450_000),
+    (Band::Cm23, 1_240_000, 1_300_000),
+];
+
+// Segments of the band plan in kHz used to infer the mode if the comment does not name it.
+// Frequencies not covered by a segment are assumed to be used for SSB within the HF bands.
+const SEGMENTS: [(u64, u64, Mode); 28] = [
+    (1_800, 1_838, Mode::Cw),
+    (1_838, 1_843, Mode::Digi),
+    (3_500, 3_570, Mode::Cw),
+    (3_570, 3_600, Mode::Digi),
+    (7_000, 7_040, Mode::Cw),
+    (7_040, 7_060, Mode::Digi),
+    (7_070, 7_080, Mode::Digi),
+    (10_100, 10_130, Mode::Cw),
+    (10_130, 10_150, Mode::Digi),
+    (14_000, 14_070, Mode::Cw),
+    (14_070, 14_100, Mode::Digi),
+    (18_068, 18_095, Mode::Cw),
+    (18_095, 18_110, Mode::Digi),
+    (21_000, 21_070, Mode::Cw),
+    (21_070, 21_150, Mode::Digi),
+    (24_890, 24_915, Mode::Cw),
+    (24_915, 24_930, Mode::Digi),
+    (28_000, 28_070, Mode::Cw),
+    (28_070, 28_190, Mode::Digi),
+    (29_500, 29_700, Mode::Fm),
+    (50_000, 50_100, Mode::Cw),
+    (50_300, 50_330, Mode::Digi),
+    (51_000, 54_000, Mode::Fm),
+    (144_000, 144_150, Mode::Cw),
+    (144_150, 144_400, Mode::Ssb),
+    (145_000, 148_000, Mode::Fm),
+    (432_000, 432_100, Mode::Cw),
+    (432_100, 432_400, Mode::Ssb),
+];
+
+/// Amateur radio band
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Band {
+    /// 160 m
+    M160,
+
+    /// 80 m
+    M80,
+
+    /// 60 m
+    M60,
+
+    /// 40 m
+    M40,
+
+    /// 30 m
+    M30,
+
+    /// 20 m
+    M20,
+
+    /// 17 m
+    M17,
+
+    /// 15 m
+    M15,
+
+    /// 12 m
+    M12,
+
+    /// 10 m
+    M10,
+
+    /// 6 m
+    M6,
+
+    /// 4 m
+    M4,
+
+    /// 2 m
+    M2,
+
+    /// 70 cm
+    Cm70,
+
+    /// 23 cm
+    Cm23,
+}
+
+impl Band {
+    /// Get the band of a frequency.
+    ///
+    /// # Arguments
+    ///
+    /// * `freq`: Frequency in Hz
+    ///
+    /// # Result
+    ///
+    /// Returns the band or `None` if the frequency is outside of the known amateur radio bands.
+    pub fn from_freq(freq: u64) -> Option<Self> {
+        BANDS
+            .iter()
+            .find(|(_, lower, upper)| (lower * 1000..=upper * 1000).contains(&freq))
+            .map(|(band, _, _)| *band)
+    }
+
+    /// Get the band by its name, e.g. `20m` or `70cm`, ignoring the case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        BANDS
+            .iter()
+            .map(|(band, _, _)| *band)
+            .find(|band| band.to_string() == name)
+    }
+
+    /// Get the frequency range of the band in Hz.
+    pub fn range(&self) -> (u64, u64) {
+        BANDS
+            .iter()
+            .find(|(band, _, _)| band == self)
+            .map(|(_, lower, upper)| (lower * 1000, upper * 1000))
+            .unwrap()
+    }
+}
+
+impl fmt::Display for Band {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Band::M160 => "160m",
+            Band::M80 => "80m",
+            Band::M60 => "60m",
+            Band::M40 => "40m",
+            Band::M30 => "30m",
+            Band::M20 => "20m",
+            Band::M17 => "17m",
+            Band::M15 => "15m",
+            Band::M12 => "12m",
+            Band::M10 => "10m",
+            Band::M6 => "6m",
+            Band::M4 => "4m",
+            Band::M2 => "2m",
+            Band::Cm70 => "70cm",
+            Band::Cm23 => "23cm",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Mode of a spotted transmission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mode {
+    /// Morse code
+    Cw,
+
+    /// Single sideband
+    Ssb,
+
+    /// Amplitude modulation
+    Am,
+
+    /// Frequency modulation
+    Fm,
+
+    /// Digital modes like RTTY, PSK or FT8
+    Digi,
+}
+
+impl Mode {
+    /// Get the mode by its name as used in spot comments, e.g. `CW`, `USB` or `FT8`, ignoring the case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_uppercase().as_str() {
+            "CW" => Some(Mode::Cw),
+            "SSB" | "USB" | "LSB" | "PHONE" => Some(Mode::Ssb),
+            "AM" => Some(Mode::Am),
+            "FM" => Some(Mode::Fm),
+            "DIGI" | "DATA" | "RTTY" | "FT8" | "FT4" | "JT65" | "JT9" | "JS8" | "MSK144"
+            | "Q65" | "SSTV" | "OLIVIA" => Some(Mode::Digi),
+            name if name.starts_with("PSK") || name.starts_with("MFSK") => Some(Mode::Digi),
+            _ => None,
+        }
+    }
+
+    /// Detect the mode named in the comment of a spot.
+    pub fn from_comment(comment: &str) -> Option<Self> {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN
+            .get_or_init(|| Regex::new(MODE_PATTERN).unwrap())
+            .captures(comment)
+            .and_then(|caps| Mode::from_name(&caps[1]))
+    }
+
+    /// Infer the mode from the band plan.
+    ///
+    /// # Arguments
+    ///
+    /// * `freq`: Frequency in Hz
+    ///
+    /// # Result
+    ///
+    /// Returns the mode usually used on the frequency or `None` if unknown.
+    pub fn from_freq(freq: u64) -> Option<Self> {
+        if let Some((_, _, mode)) = SEGMENTS
+            .iter()
+            .find(|(lower, upper, _)| (lower * 1000..upper * 1000).contains(&freq))
+        {
+            return Some(*mode);
+        }
+
+        match Band::from_freq(freq) {
+            Some(band) if band <= Band::M10 && band != Band::M60 => Some(Mode::Ssb),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Mode::Cw => "CW",
+            Mode::Ssb => "SSB",
+            Mode::Am => "AM",
+            Mode::Fm => "FM",
+            Mode::Digi => "DIGI",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,218 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::enrich::Enricher;
+use crate::filter::Filter;
+use crate::line::ReceivedLine;
+
+// Structure of a callsign: prefix with up to three characters, a digit, and a suffix ending with a letter.
+const CALLSIGN_PATTERN: &str = r"^[A-Z0-9]{1,3}[0-9][A-Z0-9]{0,3}[A-Z]$";
+
+// Default maximum difference in Hz between the frequencies of spots of the same station
+const DEFAULT_TOLERANCE: u64 = 1000;
+
+// Default number of distinct spotters required to corroborate a similar callsign
+const DEFAULT_CORROBORATION: usize = 2;
+
+// Longest time window, used for windows exceeding the range of `chrono::Duration`
+const MAX_WINDOW: chrono::Duration = chrono::Duration::milliseconds(i64::MAX);
+
+/// Detection of spots with likely busted callsigns of the spotted station.
+///
+/// A callsign is considered busted if
+/// - it fails the structural validation, e.g. because it contains no digit or ends with a digit, or
+/// - it is spotted by a single spotter only, while a similar callsign differing by one character
+///   is spotted near the same frequency by multiple spotters within the time window.
+///
+/// Add the detector to the enrichers of a listener to flag busted spots in `ReceivedLine::busted`,
+/// or to the filters to drop them. Lines which are not spots are never considered busted.
+///
+/// Clones of a detector share the recently seen spots.
+#[derive(Debug, Clone)]
+pub struct BustedCallDetector {
+    /// Time window of spots to compare with
+    window: chrono::Duration,
+
+    /// Maximum difference in Hz between the frequencies of spots of the same station
+    tolerance: u64,
+
+    /// Number of distinct spotters required to corroborate a similar callsign
+    corroboration: usize,
+
+    /// Spots seen within the time window
+    recent: Arc<Mutex<VecDeque<Recent>>>,
+}
+
+/// Spot seen within the time window
+#[derive(Debug)]
+struct Recent {
+    /// Point in time the spot was received
+    received: DateTime<Utc>,
+
+    /// Callsign of the spotted station
+    dx: String,
+
+    /// Frequency in Hz
+    freq: u64,
+
+    /// Callsign of the spotter
+    spotter: String,
+}
+
+impl BustedCallDetector {
+    /// Create a new detector.
+    ///
+    /// # Arguments
+    ///
+    /// * `window`: Time window of spots to compare with
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `BustedCallDetector`.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window: chrono::Duration::from_std(window).unwrap_or(MAX_WINDOW),
+            tolerance: DEFAULT_TOLERANCE,
+            corroboration: DEFAULT_CORROBORATION,
+            recent: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Set the maximum difference between the frequencies of spots of the same station.
+    ///
+    /// # Arguments
+    ///
+    /// * `khz`: Tolerance in kHz
+    pub fn with_tolerance(mut self, khz: f64) -> Self {
+        self.tolerance = (khz.max(0.0) * 1000.0).round() as u64;
+        self
+    }
+
+    /// Set the number of distinct spotters required to corroborate a similar callsign.
+    pub fn with_corroboration(mut self, spotters: usize) -> Self {
+        self.corroboration = spotters.max(1);
+        self
+    }
+
+    /// Check if the spotted callsign of a line is likely busted and remember the spot for later checks.
+    ///
+    /// # Arguments
+    ///
+    /// * `line`: Received line
+    ///
+    /// # Result
+    ///
+    /// Returns `true` if the line is a spot of a likely busted callsign.
+    pub fn check(&self, line: &ReceivedLine) -> bool {
+        let (spotter, dx, freq) = match line.parsed.as_ref() {
+            Some(msg) => match (msg.calls(), msg.freq()) {
+                (Some((spotter, dx)), Some(freq)) => (spotter, dx, freq),
+                _ => return false,
+            },
+            None => return false,
+        };
+        let dx = dx.to_ascii_uppercase();
+        let spotter = spotter.to_ascii_uppercase();
+
+        if !is_valid_callsign(&dx) {
+            return true;
+        }
+
+        let now = line.received;
+        let mut recent = self.recent.lock().unwrap();
+        while recent
+            .front()
+            .is_some_and(|spot| now - spot.received >= self.window)
+        {
+            recent.pop_front();
+        }
+
+        let nearby: Vec<&Recent> = recent
+            .iter()
+            .filter(|spot| spot.freq.abs_diff(freq) <= self.tolerance)
+            .collect();
+        let spotters = |call: &str| {
+            nearby
+                .iter()
+                .filter(|spot| spot.dx == call)
+                .map(|spot| spot.spotter.as_str())
+                .collect::<HashSet<&str>>()
+        };
+
+        let own = spotters(&dx);
+        let single = own.is_empty() || (own.len() == 1 && own.contains(spotter.as_str()));
+        let busted = single
+            && nearby
+                .iter()
+                .map(|spot| spot.dx.as_str())
+                .filter(|call| *call != dx && is_similar(call, &dx))
+                .collect::<HashSet<&str>>()
+                .into_iter()
+                .any(|call| spotters(call).len() >= self.corroboration);
+
+        recent.push_back(Recent {
+            received: now,
+            dx,
+            freq,
+            spotter,
+        });
+
+        busted
+    }
+}
+
+impl Enricher for BustedCallDetector {
+    fn enrich(&self, line: &mut ReceivedLine) {
+        line.busted = self.check(line);
+    }
+}
+
+impl Filter for BustedCallDetector {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        !self.check(line)
+    }
+
+    fn is_stateful(&self) -> bool {
+        true
+    }
+}
+
+/// Check if a callsign is structurally valid.
+/// Prefix designators and portable suffixes are ignored, e.g. `EA8/DL1ABC/P`.
+pub fn is_valid_callsign(callsign: &str) -> bool {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN
+        .get_or_init(|| Regex::new(CALLSIGN_PATTERN).unwrap())
+        .is_match(&home_call(callsign).to_ascii_uppercase())
+}
+
+/// Get the home callsign without SSID, prefix designator and portable suffix.
+fn home_call(callsign: &str) -> &str {
+    let callsign = callsign.split('-').next().unwrap_or(callsign);
+    callsign
+        .split('/')
+        .max_by_key(|part| part.len())
+        .unwrap_or(callsign)
+}
+
+/// Check if two callsigns differ by a single substituted, inserted or removed character.
+fn is_similar(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+
+    match long.len() - short.len() {
+        0 => short.iter().zip(long).filter(|(x, y)| x != y).count() == 1,
+        1 => {
+            let common = short.iter().zip(long).take_while(|(x, y)| x == y).count();
+            short[common..] == long[common + 1..]
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,131 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::listener::ListenError;
+
+/// Lowest plausible spot frequency in kHz (2200 m band)
+pub const MIN_SPOT_FREQUENCY: f64 = 135.0;
+
+/// Highest plausible spot frequency in kHz (microwave bands)
+pub const MAX_SPOT_FREQUENCY: f64 = 250_000_000.0;
+
+/// Maximum length of a spot comment accepted by common cluster software
+pub const MAX_SPOT_COMMENT: usize = 30;
+
+/// Create the command to submit a DX spot.
+///
+/// # Arguments
+///
+/// * `dx_call`: Callsign of the spotted station
+/// * `freq_khz`: Frequency in kHz
+/// * `comment`: Comment of the spot, may be empty
+///
+/// # Result
+///
+/// Returns the command or `ListenError::InvalidSpot` if one of the arguments is not plausible.
+pub fn dx_command(dx_call: &str, freq_khz: f64, comment: &str) -> Result<String, ListenError> {
+    if !is_callsign(dx_call) {
+        return Err(ListenError::InvalidSpot(format!(
+            "invalid callsign '{}'",
+            dx_call
+        )));
+    }
+
+    if !(MIN_SPOT_FREQUENCY..=MAX_SPOT_FREQUENCY).contains(&freq_khz) {
+        return Err(ListenError::InvalidSpot(format!(
+            "implausible frequency {} kHz",
+            freq_khz
+        )));
+    }
+
+    let comment = comment.trim();
+    if comment.chars().count() > MAX_SPOT_COMMENT {
+        return Err(ListenError::InvalidSpot(format!(
+            "comment longer than {} characters",
+            MAX_SPOT_COMMENT
+        )));
+    }
+
+    if comment.chars().any(char::is_control) {
+        return Err(ListenError::InvalidSpot(
+            "comment contains control characters".into(),
+        ));
+    }
+
+    Ok(
+        format!("DX {:.1} {} {}", freq_khz, dx_call.to_uppercase(), comment)
+            .trim_end()
+            .into(),
+    )
+}
+
+/// Create the command to send an announcement.
+///
+/// # Arguments
+///
+/// * `text`: Text of the announcement
+/// * `full`: True to distribute the announcement to the whole cluster network, false for the local node only
+///
+/// # Result
+///
+/// Returns the command or `ListenError::InvalidMessage` if the text is not valid.
+pub fn announce_command(text: &str, full: bool) -> Result<String, ListenError> {
+    let text = check_text(text)?;
+
+    if full {
+        Ok(format!("announce full {}", text))
+    } else {
+        Ok(format!("announce {}", text))
+    }
+}
+
+/// Create the command to send a talk message to another station.
+///
+/// # Arguments
+///
+/// * `to_call`: Callsign of the receiving station
+/// * `text`: Text of the message
+///
+/// # Result
+///
+/// Returns the command or `ListenError::InvalidMessage` if one of the arguments is not valid.
+pub fn talk_command(to_call: &str, text: &str) -> Result<String, ListenError> {
+    if !is_callsign(to_call) {
+        return Err(ListenError::InvalidMessage(format!(
+            "invalid callsign '{}'",
+            to_call
+        )));
+    }
+
+    let text = check_text(text)?;
+
+    Ok(format!("talk {} {}", to_call.to_uppercase(), text))
+}
+
+/// Check the text of a message.
+/// Returns the trimmed text if it is neither empty nor contains control characters.
+fn check_text(text: &str) -> Result<&str, ListenError> {
+    let text = text.trim();
+
+    if text.is_empty() {
+        return Err(ListenError::InvalidMessage("empty text".into()));
+    }
+
+    if text.chars().any(char::is_control) {
+        return Err(ListenError::InvalidMessage(
+            "text contains control characters".into(),
+        ));
+    }
+
+    Ok(text)
+}
+
+/// Check if a given string is a plausible callsign.
+/// A callsign consists of letters, digits and slashes and contains at least one digit and one letter.
+fn is_callsign(call: &str) -> bool {
+    !call.is_empty()
+        && call.chars().all(|c| c.is_ascii_alphanumeric() || c == '/')
+        && call.chars().any(|c| c.is_ascii_digit())
+        && call.chars().any(|c| c.is_ascii_alphabetic())
+}
@@ -0,0 +1,661 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+#[cfg(feature = "amqp")]
+use crate::amqp::AmqpSink;
+use crate::endpoint::EndpointError;
+use crate::expr::{FilterExpr, FilterParseError};
+use crate::file::{FileFormat, FileSink, Rotation};
+use crate::filter::FilterChain;
+use crate::flavor::ClusterFlavor;
+#[cfg(feature = "influx")]
+use crate::influx::InfluxSink;
+#[cfg(feature = "journald")]
+use crate::journald::JournalSink;
+#[cfg(feature = "kafka")]
+use crate::kafka::KafkaSink;
+use crate::listener::{ListenError, Listener, ReconnectPolicy};
+#[cfg(feature = "mqtt")]
+use crate::mqtt::MqttSink;
+use crate::pool::PoolMode;
+#[cfg(feature = "postgres")]
+use crate::postgres::PostgresSink;
+#[cfg(feature = "redis")]
+use crate::redis::{RedisMode, RedisSink};
+use crate::sink::{SinkError, SinkRegistry};
+use crate::socks::Socks5Proxy;
+#[cfg(feature = "sqlite")]
+use crate::sqlite::SqliteSink;
+#[cfg(any(feature = "tls", feature = "native-tls"))]
+use crate::tls::TlsConfig;
+use crate::udp::{UdpFormat, UdpSink};
+#[cfg(feature = "webhook")]
+use crate::webhook::WebhookSink;
+
+// Default connection timeout to each server in seconds
+const DEFAULT_CONNECTION_TIMEOUT: u64 = 10;
+
+// Default port of MQTT brokers
+#[cfg(feature = "mqtt")]
+const DEFAULT_MQTT_PORT: u16 = 1883;
+
+/// Possible errors while loading a configuration
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("failed to read configuration: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid configuration: {0}")]
+    Syntax(String),
+
+    #[error("invalid value '{1}' for '{0}'")]
+    InvalidValue(String, String),
+
+    #[error("invalid endpoint: {0}")]
+    Endpoint(#[from] EndpointError),
+
+    #[error("invalid filter: {0}")]
+    Filter(#[from] FilterParseError),
+
+    #[error("invalid listener settings: {0}")]
+    Listener(#[from] ListenError),
+
+    #[error("failed to set up sink '{0}': {1}")]
+    Sink(String, SinkError),
+}
+
+/// Configuration of a `ListenerPool` and its sinks, e.g. loaded from a TOML or JSON file:
+///
+/// ```toml
+/// filter = "not spotter_cont = AS"
+///
+/// [reconnect]
+/// delay = 30
+///
+/// [[listeners]]
+/// endpoint = "telnet://dxc.example:7300"
+/// callsign = "N0CALL"
+/// flavor = "dxspider"
+/// label = "local"
+///
+/// [[sinks]]
+/// name = "archive"
+/// type = "file"
+/// path = "spots.log"
+/// daily = true
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Connection timeout to each server in seconds
+    pub connection_timeout: u64,
+
+    /// Reconnect policy of all listeners without their own policy, listeners stop after a lost connection if not set
+    pub reconnect: Option<ReconnectConfig>,
+
+    /// Filter expression applied by all listeners, see `FilterExpr`
+    pub filter: Option<String>,
+
+    /// How the listeners deliver their lines, e.g. `standby` to use the first listener as primary.
+    /// Not changed by `ListenerPool::reload`.
+    pub mode: PoolMode,
+
+    /// Listeners of the pool
+    pub listeners: Vec<ListenerConfig>,
+
+    /// Sinks the received lines are delivered to
+    pub sinks: Vec<SinkConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            connection_timeout: DEFAULT_CONNECTION_TIMEOUT,
+            reconnect: None,
+            filter: None,
+            mode: PoolMode::All,
+            listeners: Vec::new(),
+            sinks: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Parse a configuration in TOML format.
+    pub fn from_toml(data: &str) -> Result<Self, ConfigError> {
+        toml::from_str(data).map_err(|err| ConfigError::Syntax(err.to_string()))
+    }
+
+    /// Parse a configuration in JSON format.
+    pub fn from_json(data: &str) -> Result<Self, ConfigError> {
+        serde_json::from_str(data).map_err(|err| ConfigError::Syntax(err.to_string()))
+    }
+
+    /// Load a configuration from a file, in JSON format if the file name ends with `.json`, otherwise in TOML format.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: Path of the file
+    ///
+    /// # Result
+    ///
+    /// Returns the configuration or an error if the file could not be read or parsed.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path)?;
+
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::from_json(&data),
+            _ => Self::from_toml(&data),
+        }
+    }
+
+    /// Connection timeout to each server, to pass to `ListenerPool::listen`
+    pub fn connection_timeout(&self) -> Duration {
+        Duration::from_secs(self.connection_timeout)
+    }
+}
+
+/// Policy to reconnect after a lost connection, see `ReconnectPolicy`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconnectConfig {
+    /// Delay before reconnecting in seconds
+    pub delay: u64,
+
+    /// Maximum number of consecutive failed attempts, unlimited if not set
+    #[serde(default)]
+    pub max_attempts: Option<u32>,
+}
+
+impl From<ReconnectConfig> for ReconnectPolicy {
+    fn from(config: ReconnectConfig) -> Self {
+        ReconnectPolicy {
+            delay: Duration::from_secs(config.delay),
+            max_attempts: config.max_attempts,
+        }
+    }
+}
+
+/// Configuration of a listener
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListenerConfig {
+    /// Endpoint of the server, e.g. `telnet://dxc.example:7300`, see `Endpoint`
+    pub endpoint: String,
+
+    /// Callsign to use for authentication
+    pub callsign: String,
+
+    /// Label to identify the listener in delivered lines
+    #[serde(default)]
+    pub label: Option<String>,
+
+    /// Software of the server, e.g. `dxspider`
+    #[serde(default)]
+    pub flavor: ClusterFlavor,
+
+    /// Password to send if the server asks for one
+    #[serde(default)]
+    pub password: Option<String>,
+
+    /// Name of the operator to send if the server asks for it
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// QTH of the operator to send if the server asks for it
+    #[serde(default)]
+    pub qth: Option<String>,
+
+    /// Filter expression applied by the listener in addition to the filter of all listeners
+    #[serde(default)]
+    pub filter: Option<String>,
+
+    /// Reconnect policy of the listener instead of the policy of all listeners
+    #[serde(default)]
+    pub reconnect: Option<ReconnectConfig>,
+
+    /// Address of a SOCKS5 proxy to connect through, e.g. `127.0.0.1:9050`
+    #[serde(default)]
+    pub proxy: Option<String>,
+
+    /// SHA-256 fingerprints of accepted server certificates for TLS endpoints, see `TlsConfig::pin`
+    #[serde(default)]
+    pub pinned_certificates: Vec<String>,
+
+    /// Number of historical spots to request after the login
+    #[serde(default)]
+    pub backlog: usize,
+
+    /// Commands to send after the login instead of the recommended commands of the flavor
+    #[serde(default)]
+    pub login_commands: Option<Vec<String>>,
+}
+
+impl ListenerConfig {
+    /// Create the listener.
+    ///
+    /// # Arguments
+    ///
+    /// * `config`: Configuration of the pool, providing the settings shared by all listeners
+    ///
+    /// # Result
+    ///
+    /// Returns the listener or an error if a setting is invalid.
+    pub fn build(&self, config: &Config) -> Result<Listener, ConfigError> {
+        let mut listener = Listener::from_uri(&self.endpoint, self.callsign.clone())?;
+        listener.apply_flavor(self.flavor);
+        listener.label = self.label.clone();
+        listener.login_password = self.password.clone();
+        listener.login_name = self.name.clone();
+        listener.login_qth = self.qth.clone();
+        listener.backlog = self.backlog;
+        listener.reconnect = self.reconnect.or(config.reconnect).map(Into::into);
+
+        if let Some(cmds) = self.login_commands.as_ref() {
+            listener.login_commands = cmds.clone();
+        }
+
+        if let Some(proxy) = self.proxy.as_ref() {
+            listener.proxy = Some(Socks5Proxy::new(proxy));
+        }
+
+        if !self.pinned_certificates.is_empty() {
+            #[cfg(any(feature = "tls", feature = "native-tls"))]
+            {
+                let tls = listener.tls.get_or_insert_with(TlsConfig::new);
+                for fingerprint in self.pinned_certificates.iter() {
+                    tls.pin(fingerprint)?;
+                }
+            }
+            #[cfg(not(any(feature = "tls", feature = "native-tls")))]
+            return Err(ConfigError::InvalidValue(
+                "pinned_certificates".into(),
+                "requires the feature 'tls'".into(),
+            ));
+        }
+
+        let mut filters = FilterChain::new();
+        for expr in [config.filter.as_ref(), self.filter.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            filters.push(FilterExpr::parse(expr)?);
+        }
+        listener.filters = filters;
+
+        Ok(listener)
+    }
+}
+
+/// Configuration of a named sink
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SinkConfig {
+    /// Name of the sink passed to the error handler of the `SinkRegistry`
+    pub name: String,
+
+    /// Type and settings of the sink
+    #[serde(flatten)]
+    pub kind: SinkKind,
+}
+
+/// Type and settings of a sink, given by the field `type`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkKind {
+    /// `FileSink`
+    File {
+        /// Path of the file
+        path: PathBuf,
+
+        /// Format of the lines, `raw`, `json` or `adif`
+        #[serde(default)]
+        format: Option<String>,
+
+        /// Write to a file per UTC day
+        #[serde(default)]
+        daily: bool,
+
+        /// Start a new file if the file would exceed the given size in bytes
+        #[serde(default)]
+        max_size: Option<u64>,
+    },
+
+    /// `UdpSink`
+    Udp {
+        /// Address the datagrams are sent to
+        target: SocketAddr,
+
+        /// Template of the datagrams, raw lines are sent if not set
+        #[serde(default)]
+        template: Option<String>,
+    },
+
+    /// `MqttSink`
+    #[cfg(feature = "mqtt")]
+    Mqtt {
+        /// Host of the broker
+        host: String,
+
+        /// Port of the broker
+        #[serde(default)]
+        port: Option<u16>,
+
+        /// Template of the topic
+        topic: String,
+    },
+
+    /// `RedisSink`
+    #[cfg(feature = "redis")]
+    Redis {
+        /// URL of the server
+        url: String,
+
+        /// Channel or stream to deliver the spots to
+        key: String,
+
+        /// Append to a stream instead of publishing to a channel
+        #[serde(default)]
+        stream: bool,
+    },
+
+    /// `KafkaSink`
+    #[cfg(feature = "kafka")]
+    Kafka {
+        /// Comma separated list of brokers
+        brokers: String,
+
+        /// Topic to publish to
+        topic: String,
+    },
+
+    /// `AmqpSink`
+    #[cfg(feature = "amqp")]
+    Amqp {
+        /// URI of the broker
+        uri: String,
+
+        /// Topic exchange to publish to
+        exchange: String,
+
+        /// Template of the routing key
+        routing_key: String,
+    },
+
+    /// `WebhookSink`
+    #[cfg(feature = "webhook")]
+    Webhook {
+        /// URL of the endpoint
+        url: String,
+    },
+
+    /// `InfluxSink`
+    #[cfg(feature = "influx")]
+    Influx {
+        /// URL of the write endpoint
+        url: String,
+    },
+
+    /// `SqliteSink`
+    #[cfg(feature = "sqlite")]
+    Sqlite {
+        /// Path of the database
+        path: PathBuf,
+    },
+
+    /// `PostgresSink`
+    #[cfg(feature = "postgres")]
+    Postgres {
+        /// Connection string of the database
+        config: String,
+    },
+
+    /// `JournalSink`
+    #[cfg(feature = "journald")]
+    Journald,
+}
+
+impl SinkConfig {
+    /// Add the sink to a registry.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry`: Registry to add the sink to
+    ///
+    /// # Result
+    ///
+    /// Returns `Ok(())` or an error if a setting is invalid or the sink could not be created.
+    pub async fn register(&self, registry: &mut SinkRegistry) -> Result<(), ConfigError> {
+        let sink_error = |err| ConfigError::Sink(self.name.clone(), err);
+
+        match &self.kind {
+            SinkKind::File {
+                path,
+                format,
+                daily,
+                max_size,
+            } => {
+                let mut sink = FileSink::new(path);
+                sink.format = match format.as_deref().unwrap_or("raw") {
+                    "raw" => FileFormat::Raw,
+                    "json" => FileFormat::Json,
+                    "adif" => FileFormat::Adif,
+                    other => Err(ConfigError::InvalidValue("format".into(), other.into()))?,
+                };
+                sink.rotation = match (daily, max_size) {
+                    (true, _) => Rotation::Daily,
+                    (false, Some(size)) => Rotation::Size(*size),
+                    (false, None) => Rotation::Never,
+                };
+                registry.register(&self.name, sink);
+            }
+            SinkKind::Udp { target, template } => {
+                let mut sink = UdpSink::bind(*target).await.map_err(sink_error)?;
+                if let Some(template) = template {
+                    sink.format = UdpFormat::Template(template.clone());
+                }
+                registry.register(&self.name, sink);
+            }
+            #[cfg(feature = "mqtt")]
+            SinkKind::Mqtt { host, port, topic } => {
+                let port = port.unwrap_or(DEFAULT_MQTT_PORT);
+                registry.register(&self.name, MqttSink::new(host, port, topic));
+            }
+            #[cfg(feature = "redis")]
+            SinkKind::Redis { url, key, stream } => {
+                let mode = if *stream {
+                    RedisMode::Stream(None)
+                } else {
+                    RedisMode::Publish
+                };
+                let sink = RedisSink::connect(url, key, mode)
+                    .await
+                    .map_err(sink_error)?;
+                registry.register(&self.name, sink);
+            }
+            #[cfg(feature = "kafka")]
+            SinkKind::Kafka { brokers, topic } => {
+                let sink = KafkaSink::new(brokers, topic).map_err(sink_error)?;
+                registry.register(&self.name, sink);
+            }
+            #[cfg(feature = "amqp")]
+            SinkKind::Amqp {
+                uri,
+                exchange,
+                routing_key,
+            } => {
+                let sink = AmqpSink::new(uri, exchange, routing_key);
+                registry.register(&self.name, sink);
+            }
+            #[cfg(feature = "webhook")]
+            SinkKind::Webhook { url } => {
+                let sink = WebhookSink::new(url).map_err(sink_error)?;
+                registry.register(&self.name, sink);
+            }
+            #[cfg(feature = "influx")]
+            SinkKind::Influx { url } => {
+                let sink = InfluxSink::new(url).map_err(sink_error)?;
+                registry.register(&self.name, sink);
+            }
+            #[cfg(feature = "sqlite")]
+            SinkKind::Sqlite { path } => {
+                let sink = SqliteSink::open(path).await.map_err(sink_error)?;
+                registry.register(&self.name, sink);
+            }
+            #[cfg(feature = "postgres")]
+            SinkKind::Postgres { config } => {
+                registry.register(&self.name, PostgresSink::new(config));
+            }
+            #[cfg(feature = "journald")]
+            SinkKind::Journald => {
+                let sink = JournalSink::new().map_err(sink_error)?;
+                registry.register(&self.name, sink);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SinkRegistry {
+    /// Create a registry with the sinks of a configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config`: Configuration of the sinks
+    ///
+    /// # Result
+    ///
+    /// Returns the registry, not spawned yet, or an error if a sink could not be created.
+    pub async fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let mut registry = Self::new();
+        for sink in config.sinks.iter() {
+            sink.register(&mut registry).await?;
+        }
+        Ok(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        connection_timeout = 5
+        filter = "not spotter_cont = AS"
+        mode = "standby"
+
+        [reconnect]
+        delay = 30
+
+        [[listeners]]
+        endpoint = "telnet://dxc.example:7300"
+        callsign = "N0CALL"
+        flavor = "dxspider"
+        label = "local"
+
+        [[listeners]]
+        endpoint = "dxc.example:8000"
+        callsign = "N0CALL"
+        filter = "band = 20m"
+        reconnect = { delay = 5, max_attempts = 3 }
+
+        [[sinks]]
+        name = "archive"
+        type = "file"
+        path = "spots.log"
+        daily = true
+    "#;
+
+    #[test]
+    fn toml() {
+        let config = Config::from_toml(CONFIG).unwrap();
+
+        assert_eq!(config.connection_timeout(), Duration::from_secs(5));
+        assert_eq!(config.mode, PoolMode::Standby);
+        assert_eq!(config.listeners.len(), 2);
+        assert_eq!(config.listeners[0].flavor, ClusterFlavor::DxSpider);
+        assert_eq!(
+            config.sinks[0].kind,
+            SinkKind::File {
+                path: "spots.log".into(),
+                format: None,
+                daily: true,
+                max_size: None,
+            }
+        );
+    }
+
+    #[test]
+    fn json() {
+        let config = Config::from_json(
+            r#"{"listeners": [{"endpoint": "dxc.example:7300", "callsign": "N0CALL"}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.connection_timeout(), Duration::from_secs(10));
+        assert_eq!(config.mode, PoolMode::All);
+        assert_eq!(config.listeners[0].label, None);
+        assert!(config.sinks.is_empty());
+
+        assert!(matches!(
+            Config::from_json(r#"{"listeners": [{"endpoint": "dxc.example:7300"}]}"#),
+            Err(ConfigError::Syntax(_))
+        ));
+    }
+
+    #[test]
+    fn build() {
+        let config = Config::from_toml(CONFIG).unwrap();
+
+        // The settings of the pool apply to listeners without their own
+        let listener = config.listeners[0].build(&config).unwrap();
+        assert_eq!(listener.label.as_deref(), Some("local"));
+        assert_eq!(
+            listener.reconnect,
+            Some(ReconnectPolicy {
+                delay: Duration::from_secs(30),
+                max_attempts: None,
+            })
+        );
+
+        let listener = config.listeners[1].build(&config).unwrap();
+        assert_eq!(
+            listener.reconnect,
+            Some(ReconnectPolicy {
+                delay: Duration::from_secs(5),
+                max_attempts: Some(3),
+            })
+        );
+
+        let mut invalid = config.listeners[1].clone();
+        invalid.filter = Some("band = ".into());
+        assert!(matches!(
+            invalid.build(&config),
+            Err(ConfigError::Filter(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn sinks() {
+        let mut config = Config::from_toml(CONFIG).unwrap();
+        let registry = SinkRegistry::from_config(&config).await.unwrap();
+        assert_eq!(registry.names(), ["archive"]);
+
+        config.sinks[0].kind = SinkKind::File {
+            path: "spots.log".into(),
+            format: Some("xml".into()),
+            daily: false,
+            max_size: None,
+        };
+        assert!(matches!(
+            SinkRegistry::from_config(&config).await,
+            Err(ConfigError::InvalidValue(field, value)) if field == "format" && value == "xml"
+        ));
+    }
+}
@@ -0,0 +1,301 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::time::Duration;
+use tokio::io::{self, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::time;
+
+use crate::listener::ListenError;
+
+/// Boxed reading half of a connection to a cluster server
+pub type BoxedReader = Box<dyn AsyncRead + Unpin + Send>;
+
+/// Boxed writing half of a connection to a cluster server
+pub type BoxedWriter = Box<dyn AsyncWrite + Unpin + Send>;
+
+/// Bidirectional stream to a cluster server, e.g. a TCP or TLS stream
+pub trait Transport: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Transport for T {}
+
+/// Boxed bidirectional stream to a cluster server
+pub type BoxedTransport = Box<dyn Transport>;
+
+/// Terminator appended to lines sent to the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineTerminator {
+    /// Carriage return followed by line feed (`\r\n`)
+    #[default]
+    CrLf,
+
+    /// Line feed only (`\n`)
+    Lf,
+
+    /// Carriage return only (`\r`)
+    Cr,
+}
+
+impl LineTerminator {
+    /// Get the terminator as string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineTerminator::CrLf => "\r\n",
+            LineTerminator::Lf => "\n",
+            LineTerminator::Cr => "\r",
+        }
+    }
+}
+
+/// Decoding of received lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Decoding {
+    /// Lines have to be valid UTF-8, other lines are discarded and reported as `InvalidData`
+    #[default]
+    Strict,
+
+    /// Invalid UTF-8 sequences are replaced by the replacement character `U+FFFD`
+    Lossy,
+
+    /// Lines are valid UTF-8 or otherwise decoded as Latin-1 (ISO 8859-1)
+    Latin1,
+}
+
+impl Decoding {
+    /// Decode a received line.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`: Received bytes
+    ///
+    /// # Result
+    ///
+    /// Returns the decoded line or `ListenError::InvalidData` if the line can not be decoded.
+    pub fn decode(&self, data: Vec<u8>) -> Result<String, ListenError> {
+        match String::from_utf8(data) {
+            Ok(line) => Ok(line),
+            Err(err) => match self {
+                Decoding::Strict => Err(ListenError::InvalidData),
+                Decoding::Lossy => Ok(String::from_utf8_lossy(err.as_bytes()).into_owned()),
+                Decoding::Latin1 => Ok(err.as_bytes().iter().map(|b| *b as char).collect()),
+            },
+        }
+    }
+}
+
+/// Established connection to a cluster server.
+///
+/// Provides buffered line based access to the reading half and
+/// line based writing to the writing half of the underlying stream.
+pub struct Connection {
+    /// Buffered reading half
+    reader: BufReader<BoxedReader>,
+
+    /// Writing half
+    writer: BoxedWriter,
+
+    /// Partially received line
+    pending: Vec<u8>,
+
+    /// Data handed back by `unread`, returned by the next reads before any new data
+    unread: Vec<u8>,
+
+    /// Terminator appended to sent lines
+    terminator: LineTerminator,
+
+    /// Decoding of received lines
+    decoding: Decoding,
+}
+
+impl Connection {
+    /// Create new instance of `Connection`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader`: Reading half of the stream
+    /// * `writer`: Writing half of the stream
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `Connection`.
+    pub fn new(reader: BoxedReader, writer: BoxedWriter) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            writer,
+            pending: Vec::new(),
+            unread: Vec::new(),
+            terminator: LineTerminator::default(),
+            decoding: Decoding::default(),
+        }
+    }
+
+    /// Create new instance of `Connection` from a bidirectional stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport`: Stream to the server, split into reading and writing half
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `Connection`.
+    pub fn from_transport(transport: BoxedTransport) -> Self {
+        let (rx, tx) = io::split(transport);
+        Self::new(Box::new(rx), Box::new(tx))
+    }
+
+    /// Set the terminator appended to sent lines.
+    pub fn set_line_terminator(&mut self, terminator: LineTerminator) {
+        self.terminator = terminator;
+    }
+
+    /// Set the decoding of received lines.
+    pub fn set_decoding(&mut self, decoding: Decoding) {
+        self.decoding = decoding;
+    }
+
+    /// Read data until the given delimiter is found and append it to `buf`.
+    /// Data read so far stays in `buf` even if the future gets dropped, e.g. due to a timeout.
+    ///
+    /// See `check_read_result` for the possible errors.
+    pub async fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> Result<usize, ListenError> {
+        read_until(&mut self.reader, &mut self.unread, byte, buf).await
+    }
+
+    /// Read a single line and append it to `buf`.
+    /// The function is cancel safe, a partially received line is kept until the next call.
+    ///
+    /// See `check_read_result` for the possible errors.
+    /// A line which can not be decoded with the configured decoding is discarded and reported as `InvalidData`.
+    pub async fn read_line(&mut self, buf: &mut String) -> Result<usize, ListenError> {
+        let line = self.read_frame().await?;
+        let len = line.len();
+        buf.push_str(&self.decode(line)?);
+
+        Ok(len)
+    }
+
+    /// Read the bytes of a single line including the line break as they are received.
+    /// The function is cancel safe, a partially received line is kept until the next call.
+    ///
+    /// See `check_read_result` for the possible errors.
+    pub async fn read_frame(&mut self) -> Result<Vec<u8>, ListenError> {
+        read_until(&mut self.reader, &mut self.unread, b'\n', &mut self.pending).await?;
+        Ok(std::mem::take(&mut self.pending))
+    }
+
+    /// Decode the bytes of a line with the configured decoding.
+    pub fn decode(&self, frame: Vec<u8>) -> Result<String, ListenError> {
+        self.decoding.decode(frame)
+    }
+
+    /// Get the partially received line, e.g. a prompt not terminated by a line break.
+    pub fn partial_line(&self) -> &[u8] {
+        &self.pending
+    }
+
+    /// Take the partially received line.
+    pub fn take_partial_line(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Hand back already read data.
+    /// The data is returned by the next reads before any newly received data.
+    /// Allows to inspect received data during the login without withholding it from the listener.
+    pub fn unread(&mut self, data: &[u8]) {
+        self.unread.splice(0..0, data.iter().copied());
+    }
+
+    /// Send a string to the server.
+    /// Appends the configured line terminator to the given string before sending it.
+    pub async fn send_line(&mut self, data: &str) -> Result<(), ListenError> {
+        self.writer
+            .write_all(format!("{}{}", data, self.terminator.as_str()).as_bytes())
+            .await
+            .map_err(|_| ListenError::UnknownError)?;
+
+        // Buffering transports, e.g. WebSocket, only send the data when flushed
+        self.writer
+            .flush()
+            .await
+            .map_err(|_| ListenError::UnknownError)
+    }
+
+    /// Send a string to the server character by character, simulating a typing person.
+    /// Appends the configured line terminator to the given string before sending it.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`: String to send
+    /// * `delay`: Delay between two characters
+    pub async fn send_line_paced(
+        &mut self,
+        data: &str,
+        delay: Duration,
+    ) -> Result<(), ListenError> {
+        let line = format!("{}{}", data, self.terminator.as_str());
+        let mut buf = [0; 4];
+
+        for (i, c) in line.chars().enumerate() {
+            if i > 0 {
+                time::sleep(delay).await;
+            }
+
+            self.writer
+                .write_all(c.encode_utf8(&mut buf).as_bytes())
+                .await
+                .map_err(|_| ListenError::UnknownError)?;
+            self.writer
+                .flush()
+                .await
+                .map_err(|_| ListenError::UnknownError)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Read data until the given delimiter is found and append it to `buf`.
+/// Data handed back by `Connection::unread` is consumed first.
+async fn read_until(
+    reader: &mut BufReader<BoxedReader>,
+    unread: &mut Vec<u8>,
+    byte: u8,
+    buf: &mut Vec<u8>,
+) -> Result<usize, ListenError> {
+    if let Some(pos) = unread.iter().position(|b| *b == byte) {
+        buf.extend(unread.drain(..=pos));
+        return Ok(pos + 1);
+    }
+
+    let len = unread.len();
+    buf.append(unread);
+
+    match check_read_result(&reader.read_until(byte, buf).await) {
+        Err(ListenError::ConnectionLost) if len > 0 => Ok(len),
+        res => res.map(|num| num + len),
+    }
+}
+
+/// Check result from read function against possible errors.
+///
+/// Possible `ListenError`:
+/// - `ConnectionLost`: Received EOF or peer closed connection
+/// - `InvalidData`: Received data with incompatible encoding (utf-8 required)
+/// - `ConnectionTimeout`: TCP keepalive check failed
+/// - `InternalError`: Unknown/unhandled error
+fn check_read_result(res: &io::Result<usize>) -> Result<usize, ListenError> {
+    match res {
+        Ok(0) => Err(ListenError::ConnectionLost),
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => Err(ListenError::InvalidData),
+        Err(err) if err.kind() == io::ErrorKind::TimedOut => Err(ListenError::ConnectionTimeout),
+        // TLS streams report a connection closed without close notification as unexpected EOF
+        Err(err)
+            if err.kind() == io::ErrorKind::ConnectionReset
+                || err.kind() == io::ErrorKind::UnexpectedEof =>
+        {
+            Err(ListenError::ConnectionLost)
+        }
+        Ok(num) => Ok(*num),
+        Err(_) => Err(ListenError::InternalError),
+    }
+}
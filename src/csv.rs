@@ -0,0 +1,195 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+
+use crate::line::ReceivedLine;
+use crate::record::SpotRecord;
+
+/// Columns written by default, one row per spot
+pub const DEFAULT_CSV_COLUMNS: [CsvColumn; 8] = [
+    CsvColumn::Time,
+    CsvColumn::Spotter,
+    CsvColumn::Dx,
+    CsvColumn::Freq,
+    CsvColumn::Band,
+    CsvColumn::Mode,
+    CsvColumn::Snr,
+    CsvColumn::Comment,
+];
+
+// Columns with their names as used in the header row
+const COLUMNS: [(CsvColumn, &str); 17] = [
+    (CsvColumn::Received, "received"),
+    (CsvColumn::Time, "time"),
+    (CsvColumn::Spotter, "spotter"),
+    (CsvColumn::Dx, "dx"),
+    (CsvColumn::Freq, "freq_khz"),
+    (CsvColumn::Band, "band"),
+    (CsvColumn::Mode, "mode"),
+    (CsvColumn::Comment, "comment"),
+    (CsvColumn::Snr, "snr"),
+    (CsvColumn::Speed, "speed"),
+    (CsvColumn::Skimmer, "skimmer"),
+    (CsvColumn::SpotterGrid, "spotter_grid"),
+    (CsvColumn::DxGrid, "dx_grid"),
+    (CsvColumn::SpotterDxcc, "spotter_dxcc"),
+    (CsvColumn::DxDxcc, "dx_dxcc"),
+    (CsvColumn::Source, "source"),
+    (CsvColumn::Raw, "raw"),
+];
+
+// Format of points in time, understood by common spreadsheet applications
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Column of a CSV row, see `SpotRecord` for the meaning of the values.
+/// Unknown values are written as empty fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvColumn {
+    /// Point in time the spot was received, in UTC
+    Received,
+
+    /// Point in time of the spot, in UTC
+    Time,
+
+    /// Callsign of the spotter
+    Spotter,
+
+    /// Callsign of the spotted station
+    Dx,
+
+    /// Frequency in kHz
+    Freq,
+
+    /// Band, e.g. `20m`
+    Band,
+
+    /// Mode, e.g. `CW`
+    Mode,
+
+    /// Comment of the spot
+    Comment,
+
+    /// Signal to noise ratio in dB
+    Snr,
+
+    /// Speed in words per minute or baud
+    Speed,
+
+    /// `1` if the spot was sent by a skimmer, `0` otherwise
+    Skimmer,
+
+    /// Maidenhead locator of the spotter
+    SpotterGrid,
+
+    /// Maidenhead locator of the spotted station
+    DxGrid,
+
+    /// DXCC entity number of the spotter
+    SpotterDxcc,
+
+    /// DXCC entity number of the spotted station
+    DxDxcc,
+
+    /// Listener the spot was received by
+    Source,
+
+    /// Received line as is
+    Raw,
+}
+
+impl CsvColumn {
+    /// Get the column by its name as used in the header row, e.g. `freq_khz`, ignoring the case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        COLUMNS
+            .iter()
+            .find(|(_, column)| *column == name)
+            .map(|(column, _)| *column)
+    }
+
+    /// Get the value of the column for a spot.
+    fn value(&self, line: &ReceivedLine, record: &SpotRecord) -> String {
+        fn opt<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(T::to_string).unwrap_or_default()
+        }
+
+        match self {
+            CsvColumn::Received => record.received.format(TIME_FORMAT).to_string(),
+            CsvColumn::Time => record.time.format(TIME_FORMAT).to_string(),
+            CsvColumn::Spotter => record.spotter.clone(),
+            CsvColumn::Dx => record.dx.clone(),
+            CsvColumn::Freq => format!("{:.1}", record.freq as f64 / 1000.0),
+            CsvColumn::Band => opt(&record.band),
+            CsvColumn::Mode => opt(&record.mode),
+            CsvColumn::Comment => opt(&record.comment),
+            CsvColumn::Snr => opt(&record.snr),
+            CsvColumn::Speed => opt(&record.speed),
+            CsvColumn::Skimmer => (record.skimmer as u8).to_string(),
+            CsvColumn::SpotterGrid => opt(&record.spotter_grid),
+            CsvColumn::DxGrid => opt(&record.dx_grid),
+            CsvColumn::SpotterDxcc => opt(&record.spotter_dxcc),
+            CsvColumn::DxDxcc => opt(&record.dx_dxcc),
+            CsvColumn::Source => record.source.clone(),
+            CsvColumn::Raw => line.raw.clone(),
+        }
+    }
+}
+
+impl fmt::Display for CsvColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = COLUMNS
+            .iter()
+            .find(|(column, _)| column == self)
+            .map(|(_, name)| *name)
+            .unwrap();
+        write!(f, "{}", name)
+    }
+}
+
+/// Create the header row of the given columns without trailing line break.
+pub fn csv_header(columns: &[CsvColumn]) -> String {
+    columns
+        .iter()
+        .map(|column| column.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+impl ReceivedLine {
+    /// Convert a spot into a single CSV row of the given columns without trailing line break.
+    /// Fields are quoted according to RFC 4180 if required.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns`: Columns of the row
+    ///
+    /// # Result
+    ///
+    /// Returns the row or `None` if the line is not a parsed spot.
+    pub fn to_csv(&self, columns: &[CsvColumn]) -> Option<String> {
+        let record = SpotRecord::from_line(self)?;
+
+        Some(
+            columns
+                .iter()
+                .map(|column| quote(column.value(self, &record)))
+                .collect::<Vec<String>>()
+                .join(","),
+        )
+    }
+}
+
+/// Quote a field if it contains a separator, quote or line break, or starts or ends with whitespace.
+fn quote(field: String) -> String {
+    let required = field.contains([',', '"', '\r', '\n'])
+        || field.starts_with(char::is_whitespace)
+        || field.ends_with(char::is_whitespace);
+
+    if required {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
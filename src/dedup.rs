@@ -0,0 +1,387 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::band::{Band, Mode};
+use crate::filter::Filter;
+use crate::line::ReceivedLine;
+use crate::message::ClusterMessage;
+
+// Default frequency tolerance in Hz
+const DEFAULT_TOLERANCE: u64 = 500;
+
+// Longest time window, used for windows exceeding the range of `chrono::Duration`
+const MAX_WINDOW: chrono::Duration = chrono::Duration::milliseconds(i64::MAX);
+
+/// Properties of a spot, in addition to the callsign of the spotted station and the frequency,
+/// which must be equal for a spot to be considered a duplicate
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DedupKey {
+    /// Callsign of the spotter, i.e. only repeated spots of the same spotter are dropped
+    pub spotter: bool,
+
+    /// Comment of the spot
+    pub comment: bool,
+
+    /// Mode of the spot
+    pub mode: bool,
+}
+
+/// Removal of spots already received within a time window, e.g. the identical spots repeated by every node of the network.
+///
+/// Spots are identified by the callsign of the spotted station, the frequency within a tolerance of 0.5 kHz
+/// and the properties selected by the `DedupKey`, which by default is none.
+/// A repeated spot is dropped if the first spot was received less than the time window ago.
+/// Lines which are not spots are always accepted.
+///
+/// Clones of a deduplicator share their state. Add clones to the filters of multiple listeners
+/// or call `Filter::accept` on the merged output of the listeners to deduplicate across listeners.
+#[derive(Debug, Clone)]
+pub struct Deduplicator {
+    /// Time window in which repeated spots are dropped
+    window: chrono::Duration,
+
+    /// Maximum difference in Hz between the frequencies of duplicates
+    tolerance: u64,
+
+    /// Properties of a spot to compare
+    key: DedupKey,
+
+    /// Accept spots tagged with priority without checking them
+    bypass: bool,
+
+    /// Spots seen within the time window
+    state: Arc<Mutex<State>>,
+}
+
+/// Identification of a spot apart from its frequency
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SpotKey {
+    /// Callsign of the spotted station
+    dx: String,
+
+    /// Callsign of the spotter, if part of the key
+    spotter: Option<String>,
+
+    /// Comment, if part of the key
+    comment: Option<String>,
+
+    /// Mode, if part of the key
+    mode: Option<Mode>,
+}
+
+/// State shared between clones of a deduplicator
+#[derive(Debug, Default)]
+struct State {
+    /// Frequency and point in time each spot was first seen
+    seen: HashMap<SpotKey, Vec<(u64, DateTime<Utc>)>>,
+
+    /// Point in time expired spots were last removed
+    pruned: Option<DateTime<Utc>>,
+}
+
+impl Deduplicator {
+    /// Create a new deduplicator.
+    ///
+    /// # Arguments
+    ///
+    /// * `window`: Time window in which repeated spots are dropped
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `Deduplicator`.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window: chrono::Duration::from_std(window).unwrap_or(MAX_WINDOW),
+            tolerance: DEFAULT_TOLERANCE,
+            key: DedupKey::default(),
+            bypass: false,
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Set the maximum difference between the frequencies of duplicates, e.g. `0.1` for skimmer spots differing by ±0.1 kHz.
+    ///
+    /// # Arguments
+    ///
+    /// * `khz`: Tolerance in kHz
+    pub fn with_tolerance(mut self, khz: f64) -> Self {
+        self.tolerance = (khz.max(0.0) * 1000.0).round() as u64;
+        self
+    }
+
+    /// Set the properties of a spot, which must be equal for the spot to be considered a duplicate.
+    pub fn with_key(mut self, key: DedupKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Accept spots tagged with priority, e.g. by a `Watchlist`, without checking them.
+    pub fn with_priority_bypass(mut self, bypass: bool) -> Self {
+        self.bypass = bypass;
+        self
+    }
+
+    /// Forget all spots seen so far.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.seen.clear();
+        state.pruned = None;
+    }
+
+    /// Get the key and the frequency of a spot.
+    fn identify(&self, line: &ReceivedLine) -> Option<(SpotKey, u64)> {
+        let msg = line.parsed.as_ref()?;
+        let (spotter, dx) = msg.calls()?;
+        let freq = msg.freq()?;
+        let comment = match msg {
+            ClusterMessage::DxSpot(spot) => spot.comment.as_deref(),
+            ClusterMessage::SkimmerSpot(spot) => spot.info.as_deref(),
+            _ => None,
+        };
+
+        let key = SpotKey {
+            dx: dx.to_ascii_uppercase(),
+            spotter: self.key.spotter.then(|| spotter.to_ascii_uppercase()),
+            comment: self
+                .key
+                .comment
+                .then(|| comment.unwrap_or_default().trim().to_string()),
+            mode: if self.key.mode { line.mode } else { None },
+        };
+
+        Some((key, freq))
+    }
+}
+
+impl Filter for Deduplicator {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        if self.bypass && line.priority {
+            return true;
+        }
+
+        let (key, freq) = match self.identify(line) {
+            Some(spot) => spot,
+            None => return true,
+        };
+        let now = line.received;
+        let window = self.window;
+
+        let mut state = self.state.lock().unwrap();
+        let prune = match state.pruned {
+            Some(pruned) => now - pruned >= window,
+            None => true,
+        };
+        if prune {
+            state.seen.retain(|_, spots| {
+                spots.retain(|(_, first)| now - *first < window);
+                !spots.is_empty()
+            });
+            state.pruned = Some(now);
+        }
+
+        let spots = state.seen.entry(key).or_default();
+        let duplicate = spots
+            .iter()
+            .any(|(seen, first)| seen.abs_diff(freq) <= self.tolerance && now - *first < window);
+        if !duplicate {
+            spots.push((freq, now));
+        }
+
+        !duplicate
+    }
+
+    fn is_stateful(&self) -> bool {
+        true
+    }
+}
+
+/// Callsign of a spotted station and band of the spot
+type CallBand = (String, Option<Band>);
+
+/// Throttling of repeated spots of the same station, e.g. to collapse the re-spots of popular DXpeditions.
+///
+/// Only the first spot of a callsign per band is accepted within the given period,
+/// afterwards the next spot of the callsign on that band is accepted again.
+/// Lines which are not spots are always accepted.
+///
+/// Clones of a throttle share their state.
+#[derive(Debug, Clone)]
+pub struct CallThrottle {
+    /// Period in which further spots of a callsign on a band are dropped
+    period: chrono::Duration,
+
+    /// Accept spots tagged with priority without checking them
+    bypass: bool,
+
+    /// Point in time of the last accepted spot per callsign and band
+    state: Arc<Mutex<HashMap<CallBand, DateTime<Utc>>>>,
+}
+
+impl CallThrottle {
+    /// Create a new throttle.
+    ///
+    /// # Arguments
+    ///
+    /// * `period`: Period in which further spots of a callsign on a band are dropped
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `CallThrottle`.
+    pub fn new(period: Duration) -> Self {
+        Self {
+            period: chrono::Duration::from_std(period).unwrap_or(MAX_WINDOW),
+            bypass: false,
+            state: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Accept spots tagged with priority, e.g. by a `Watchlist`, without checking them.
+    pub fn with_priority_bypass(mut self, bypass: bool) -> Self {
+        self.bypass = bypass;
+        self
+    }
+
+    /// Forget all spots seen so far.
+    pub fn clear(&self) {
+        self.state.lock().unwrap().clear();
+    }
+}
+
+impl Filter for CallThrottle {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        if self.bypass && line.priority {
+            return true;
+        }
+
+        let dx = match line.parsed.as_ref().and_then(|msg| msg.calls()) {
+            Some((_, dx)) => dx.to_ascii_uppercase(),
+            None => return true,
+        };
+        let now = line.received;
+        let period = self.period;
+
+        let mut state = self.state.lock().unwrap();
+        match state.get(&(dx.clone(), line.band)) {
+            Some(last) if now - *last < period => false,
+            _ => {
+                state.retain(|_, last| now - *last < period);
+                state.insert((dx, line.band), now);
+                true
+            }
+        }
+    }
+
+    fn is_stateful(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spot(spotter: &str, freq: &str, dx: &str, comment: &str, secs: i64) -> ReceivedLine {
+        let mut line = ReceivedLine::new(format!(
+            "DX de {}:  {}  {}  {}  1200Z",
+            spotter, freq, dx, comment
+        ));
+        line.received = DateTime::from_timestamp(1_800_000_000 + secs, 0).unwrap();
+        line
+    }
+
+    #[test]
+    fn window() {
+        let dedup = Deduplicator::new(Duration::from_secs(60));
+
+        assert!(dedup.accept(&spot("W3LPL", "14025.0", "DL1ABC", "CW", 0)));
+        assert!(!dedup.accept(&spot("K1ABC", "14025.0", "DL1ABC", "CW", 59)));
+        assert!(dedup.accept(&spot("K1ABC", "14025.0", "DL1ABC", "CW", 60)));
+
+        // The window starts with the first spot, repeated spots do not extend it
+        assert!(!dedup.accept(&spot("K1ABC", "14025.0", "DL1ABC", "CW", 119)));
+        assert!(dedup.accept(&spot("K1ABC", "14025.0", "DL1ABC", "CW", 120)));
+    }
+
+    #[test]
+    fn tolerance() {
+        let dedup = Deduplicator::new(Duration::from_secs(60)).with_tolerance(0.1);
+
+        assert!(dedup.accept(&spot("W3LPL", "7026.0", "OK1ABC", "CW", 0)));
+        assert!(!dedup.accept(&spot("W3LPL", "7026.1", "OK1ABC", "CW", 1)));
+        assert!(!dedup.accept(&spot("W3LPL", "7025.9", "OK1ABC", "CW", 2)));
+        assert!(dedup.accept(&spot("W3LPL", "7026.2", "OK1ABC", "CW", 3)));
+        assert!(dedup.accept(&spot("W3LPL", "7026.0", "OK1XYZ", "CW", 4)));
+    }
+
+    #[test]
+    fn key() {
+        let dedup = Deduplicator::new(Duration::from_secs(60)).with_key(DedupKey {
+            spotter: true,
+            comment: true,
+            mode: false,
+        });
+
+        assert!(dedup.accept(&spot("W3LPL", "14025.0", "DL1ABC", "CW 599", 0)));
+        assert!(!dedup.accept(&spot("w3lpl", "14025.0", "dl1abc", "CW 599", 1)));
+        assert!(dedup.accept(&spot("K1ABC", "14025.0", "DL1ABC", "CW 599", 2)));
+        assert!(dedup.accept(&spot("W3LPL", "14025.0", "DL1ABC", "CW 579", 3)));
+    }
+
+    #[test]
+    fn shared_state_and_clear() {
+        let dedup = Deduplicator::new(Duration::from_secs(60));
+        let clone = dedup.clone();
+
+        assert!(dedup.accept(&spot("W3LPL", "14025.0", "DL1ABC", "CW", 0)));
+        assert!(!clone.accept(&spot("K1ABC", "14025.0", "DL1ABC", "CW", 1)));
+
+        clone.clear();
+        assert!(dedup.accept(&spot("K1ABC", "14025.0", "DL1ABC", "CW", 2)));
+    }
+
+    #[test]
+    fn priority_bypass() {
+        let dedup = Deduplicator::new(Duration::from_secs(60)).with_priority_bypass(true);
+        let mut line = spot("W3LPL", "14025.0", "DL1ABC", "CW", 0);
+        line.priority = true;
+
+        assert!(dedup.accept(&spot("W3LPL", "14025.0", "DL1ABC", "CW", 0)));
+        assert!(dedup.accept(&line));
+    }
+
+    #[test]
+    fn non_spots_accepted() {
+        let dedup = Deduplicator::new(Duration::from_secs(60));
+        let line = ReceivedLine::new("To ALL de DL1ABC <1234Z>: QRV on 6m".into());
+
+        assert!(dedup.accept(&line));
+        assert!(dedup.accept(&line));
+    }
+
+    #[test]
+    fn stateless_filters_first() {
+        let chain = crate::filter::FilterChain::new()
+            .with(Deduplicator::new(Duration::from_secs(60)))
+            .with(crate::filter::BeaconFilter::without());
+
+        // The beacon spot is dropped before the deduplicator remembers it
+        assert!(!chain.accept(&spot("W3LPL", "14025.0", "DL1ABC", "BCN", 0)));
+        assert!(chain.accept(&spot("K1ABC", "14025.0", "DL1ABC", "CW", 1)));
+    }
+
+    #[test]
+    fn throttle() {
+        let throttle = CallThrottle::new(Duration::from_secs(60));
+
+        assert!(throttle.accept(&spot("W3LPL", "14025.0", "DL1ABC", "CW", 0)));
+        assert!(!throttle.accept(&spot("K1ABC", "14030.0", "DL1ABC", "CW", 30)));
+        assert!(throttle.accept(&spot("K1ABC", "7025.0", "DL1ABC", "CW", 30)));
+        assert!(throttle.accept(&spot("K1ABC", "14030.0", "DL1ABC", "CW", 60)));
+    }
+}
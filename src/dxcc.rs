@@ -0,0 +1,254 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+
+use crate::enrich::{self, Continent, Enricher, Entity};
+use crate::line::ReceivedLine;
+
+// Prefixes of frequently spotted DXCC entities.
+// Fields: prefixes, name, DXCC entity number, continent, CQ zone, ITU zone.
+const ENTITIES: [(&str, &str, u16, Continent, u8, u8); 61] = [
+    (
+        "K W N AA AB AC AD AE AF AG AH AI AJ AK",
+        "United States",
+        291,
+        Continent::Na,
+        5,
+        8,
+    ),
+    ("VE VA VO VY", "Canada", 1, Continent::Na, 5, 9),
+    ("XE XF", "Mexico", 50, Continent::Na, 6, 10),
+    (
+        "DA DB DC DD DE DF DG DH DJ DK DL DM DN DO DP DQ DR",
+        "Fed. Rep. of Germany",
+        230,
+        Continent::Eu,
+        14,
+        28,
+    ),
+    ("F", "France", 227, Continent::Eu, 14, 27),
+    ("G M 2E", "England", 223, Continent::Eu, 14, 27),
+    ("GM MM 2M", "Scotland", 279, Continent::Eu, 14, 27),
+    ("GW MW 2W", "Wales", 294, Continent::Eu, 14, 27),
+    ("EI EJ", "Ireland", 245, Continent::Eu, 14, 27),
+    ("I", "Italy", 248, Continent::Eu, 15, 28),
+    (
+        "EA EB EC ED EE EF EG EH",
+        "Spain",
+        281,
+        Continent::Eu,
+        14,
+        37,
+    ),
+    (
+        "EA8 EB8 EC8 ED8 EE8 EF8 EG8 EH8",
+        "Canary Islands",
+        29,
+        Continent::Af,
+        33,
+        36,
+    ),
+    ("CT CQ CR CS", "Portugal", 272, Continent::Eu, 14, 37),
+    (
+        "ON OO OP OQ OR OS OT",
+        "Belgium",
+        209,
+        Continent::Eu,
+        14,
+        27,
+    ),
+    (
+        "PA PB PC PD PE PF PG PH PI",
+        "Netherlands",
+        263,
+        Continent::Eu,
+        14,
+        27,
+    ),
+    ("OE", "Austria", 206, Continent::Eu, 15, 28),
+    ("HB HE", "Switzerland", 287, Continent::Eu, 14, 28),
+    ("OK OL", "Czech Republic", 503, Continent::Eu, 15, 28),
+    ("OM", "Slovak Republic", 504, Continent::Eu, 15, 28),
+    ("SP SQ SN SO 3Z HF", "Poland", 269, Continent::Eu, 15, 28),
+    ("HA HG", "Hungary", 239, Continent::Eu, 15, 28),
+    ("YO YP YQ YR", "Romania", 275, Continent::Eu, 20, 28),
+    ("LZ", "Bulgaria", 212, Continent::Eu, 20, 28),
+    ("SV SW SX SY SZ J4", "Greece", 236, Continent::Eu, 20, 28),
+    ("9A", "Croatia", 497, Continent::Eu, 15, 28),
+    ("S5", "Slovenia", 499, Continent::Eu, 15, 28),
+    ("YU YT", "Serbia", 296, Continent::Eu, 15, 28),
+    ("SM SA SK SL 7S 8S", "Sweden", 284, Continent::Eu, 14, 18),
+    ("LA LB LN", "Norway", 266, Continent::Eu, 14, 18),
+    ("OH OG OF", "Finland", 224, Continent::Eu, 15, 18),
+    ("OZ OU 5P 5Q", "Denmark", 221, Continent::Eu, 14, 18),
+    ("ES", "Estonia", 52, Continent::Eu, 15, 29),
+    ("YL", "Latvia", 145, Continent::Eu, 15, 29),
+    ("LY", "Lithuania", 146, Continent::Eu, 15, 29),
+    ("R U", "European Russia", 54, Continent::Eu, 16, 29),
+    (
+        "R8 R9 R0 UA8 UA9 UA0 RA8 RA9 RA0 RK9 RK0 RU9 RU0 RV9 RV0 RW9 RW0 RX9 RX0 RZ9 RZ0",
+        "Asiatic Russia",
+        15,
+        Continent::As,
+        17,
+        30,
+    ),
+    (
+        "UR US UT UU UV UW UX UY UZ EM EN EO",
+        "Ukraine",
+        288,
+        Continent::Eu,
+        16,
+        29,
+    ),
+    ("4X 4Z", "Israel", 336, Continent::As, 20, 39),
+    ("A6", "United Arab Emirates", 391, Continent::As, 21, 39),
+    ("VU AT AU AV AW", "India", 324, Continent::As, 22, 41),
+    (
+        "JA JE JF JG JH JI JJ JK JL JM JN JO JP JQ JR JS 7J 7K 7L 7M 7N 8J 8K 8L 8M 8N",
+        "Japan",
+        339,
+        Continent::As,
+        25,
+        45,
+    ),
+    ("BY BA BD BG BH", "China", 318, Continent::As, 24, 44),
+    (
+        "HL DS 6K 6L 6M 6N",
+        "Republic of Korea",
+        137,
+        Continent::As,
+        25,
+        44,
+    ),
+    ("HS E2", "Thailand", 387, Continent::As, 26, 49),
+    ("9M2 9W2", "West Malaysia", 299, Continent::As, 28, 54),
+    (
+        "YB YC YD YE YF YG YH",
+        "Indonesia",
+        327,
+        Continent::Oc,
+        28,
+        51,
+    ),
+    (
+        "DU DV DW DX DY DZ 4D 4E 4F 4G 4H 4I",
+        "Philippines",
+        375,
+        Continent::Oc,
+        27,
+        50,
+    ),
+    ("VK", "Australia", 150, Continent::Oc, 30, 59),
+    ("ZL", "New Zealand", 170, Continent::Oc, 32, 60),
+    ("ZS ZR ZT ZU", "South Africa", 462, Continent::Af, 38, 57),
+    ("5Z 5Y", "Kenya", 430, Continent::Af, 37, 48),
+    ("CN 5C 5D 5E 5F 5G", "Morocco", 446, Continent::Af, 33, 37),
+    ("SU 6A 6B", "Egypt", 478, Continent::Af, 34, 38),
+    ("PY PP PQ PR PS PT PU", "Brazil", 108, Continent::Sa, 11, 15),
+    (
+        "LU LW LO LP LQ LR LS LT",
+        "Argentina",
+        100,
+        Continent::Sa,
+        13,
+        14,
+    ),
+    ("CE CA CB XQ XR 3G", "Chile", 112, Continent::Sa, 12, 14),
+    ("CX", "Uruguay", 144, Continent::Sa, 13, 14),
+    ("OA OB OC", "Peru", 136, Continent::Sa, 10, 12),
+    ("HK HJ 5J 5K", "Colombia", 116, Continent::Sa, 9, 12),
+    ("YV YW YX YY 4M", "Venezuela", 148, Continent::Sa, 9, 12),
+    ("KL AL NL WL", "Alaska", 6, Continent::Na, 1, 1),
+];
+
+/// Lookup of the DXCC entity of a callsign by its prefix.
+///
+/// The built-in table covers frequently spotted entities only.
+/// Additional prefixes can be added with `insert`, e.g. from a `cty.dat` file.
+#[derive(Debug, Clone)]
+pub struct PrefixLookup {
+    /// Entities by prefix
+    prefixes: HashMap<String, Entity>,
+
+    /// Length of the longest known prefix
+    longest: usize,
+}
+
+impl Default for PrefixLookup {
+    fn default() -> Self {
+        let mut lookup = Self::empty();
+
+        for (prefixes, name, dxcc, continent, cq_zone, itu_zone) in ENTITIES {
+            let entity = Entity {
+                name: name.into(),
+                dxcc,
+                continent,
+                cq_zone,
+                itu_zone,
+            };
+            for prefix in prefixes.split_whitespace() {
+                lookup.insert(prefix, entity.clone());
+            }
+        }
+
+        lookup
+    }
+}
+
+impl PrefixLookup {
+    /// Create a lookup without any known prefixes.
+    pub fn empty() -> Self {
+        Self {
+            prefixes: HashMap::new(),
+            longest: 0,
+        }
+    }
+
+    /// Add a prefix, an existing prefix is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix`: Prefix of the callsigns of the entity
+    /// * `entity`: Entity of the prefix
+    pub fn insert(&mut self, prefix: &str, entity: Entity) {
+        let prefix = prefix.to_ascii_uppercase();
+        self.longest = self.longest.max(prefix.len());
+        self.prefixes.insert(prefix, entity);
+    }
+
+    /// Get the entity of a callsign by its longest matching prefix.
+    ///
+    /// # Arguments
+    ///
+    /// * `callsign`: Callsign, may contain SSIDs as well as prefix or portable designators
+    ///
+    /// # Result
+    ///
+    /// Returns the entity or `None` if no prefix matches.
+    pub fn lookup(&self, callsign: &str) -> Option<&Entity> {
+        let call = enrich::base_call(callsign).to_ascii_uppercase();
+
+        (1..=self.longest.min(call.len()))
+            .rev()
+            .filter(|len| call.is_char_boundary(*len))
+            .find_map(|len| self.prefixes.get(&call[..len]))
+    }
+}
+
+impl Enricher for PrefixLookup {
+    fn enrich(&self, line: &mut ReceivedLine) {
+        let calls = line
+            .parsed
+            .as_ref()
+            .and_then(|msg| msg.calls())
+            .map(|(de, dx)| (de.to_string(), dx.to_string()));
+
+        if let Some((de, dx)) = calls {
+            line.spotter_entity = self.lookup(&de).cloned();
+            line.dx_entity = self.lookup(&dx).cloned();
+        }
+    }
+}
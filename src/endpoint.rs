@@ -0,0 +1,202 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+// Port used by most cluster nodes for telnet
+const TELNET_PORT: u16 = 7300;
+
+// Port registered for telnet over TLS
+const TLS_PORT: u16 = 992;
+
+// Ports of WebSocket connections
+const WS_PORT: u16 = 80;
+const WSS_PORT: u16 = 443;
+
+/// Possible errors while parsing an endpoint
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EndpointError {
+    #[error("invalid endpoint '{0}'")]
+    InvalidUri(String),
+
+    #[error("unknown scheme '{0}'")]
+    UnknownScheme(String),
+
+    #[error("invalid port '{0}'")]
+    InvalidPort(String),
+
+    #[error("scheme '{0}' requires the feature '{1}'")]
+    UnsupportedScheme(Scheme, &'static str),
+}
+
+/// Transport selected by the scheme of an endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scheme {
+    /// Plain TCP connection, `telnet://`
+    #[default]
+    Telnet,
+
+    /// TCP connection encrypted using TLS, `tls://` or `telnets://`
+    Tls,
+
+    /// WebSocket connection, `ws://`
+    Ws,
+
+    /// WebSocket connection encrypted using TLS, `wss://`
+    Wss,
+}
+
+impl Scheme {
+    /// Port used if the endpoint does not contain one.
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Scheme::Telnet => TELNET_PORT,
+            Scheme::Tls => TLS_PORT,
+            Scheme::Ws => WS_PORT,
+            Scheme::Wss => WSS_PORT,
+        }
+    }
+
+    /// True if the connection is encrypted using TLS.
+    pub fn is_secure(&self) -> bool {
+        matches!(self, Scheme::Tls | Scheme::Wss)
+    }
+
+    /// True if the cluster protocol is spoken over a WebSocket connection.
+    pub fn is_websocket(&self) -> bool {
+        matches!(self, Scheme::Ws | Scheme::Wss)
+    }
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Scheme::Telnet => "telnet",
+            Scheme::Tls => "tls",
+            Scheme::Ws => "ws",
+            Scheme::Wss => "wss",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Scheme {
+    type Err = EndpointError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "telnet" => Ok(Scheme::Telnet),
+            "tls" | "telnets" => Ok(Scheme::Tls),
+            "ws" => Ok(Scheme::Ws),
+            "wss" => Ok(Scheme::Wss),
+            _ => Err(EndpointError::UnknownScheme(s.into())),
+        }
+    }
+}
+
+/// Address of a cluster server given as URI, e.g. `telnet://dxc.example:7300`, `tls://dxc.example`
+/// or `wss://gateway.example/cluster`, see `Listener::from_uri`.
+///
+/// Endpoints without scheme, e.g. `dxc.example:7300`, are reached via telnet.
+/// Without port, the default port of the scheme is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    /// Transport to connect with
+    pub scheme: Scheme,
+
+    /// Host of the cluster server, without brackets for IPv6 addresses
+    pub host: String,
+
+    /// Port of the cluster server
+    pub port: u16,
+
+    /// Path of the WebSocket endpoint without leading slash, empty for other schemes
+    pub path: String,
+}
+
+impl Endpoint {
+    /// Parse an endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri`: Endpoint, e.g. `telnet://dxc.example:7300` or `[2001:db8::1]:7300`
+    ///
+    /// # Result
+    ///
+    /// Returns the parsed endpoint or an error if the endpoint is invalid.
+    pub fn parse(uri: &str) -> Result<Self, EndpointError> {
+        let invalid = || EndpointError::InvalidUri(uri.into());
+
+        let (scheme, rest) = match uri.trim().split_once("://") {
+            Some((scheme, rest)) => (scheme.parse()?, rest),
+            None => (Scheme::Telnet, uri.trim()),
+        };
+
+        let (authority, path) = match rest.split_once('/') {
+            Some((authority, path)) => (authority, path),
+            None => (rest, ""),
+        };
+        if !path.is_empty() && !scheme.is_websocket() {
+            return Err(invalid());
+        }
+
+        // IPv6 addresses are enclosed in brackets to separate them from the port
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(rest) => {
+                let (host, rest) = rest.split_once(']').ok_or_else(invalid)?;
+                match rest {
+                    "" => (host, None),
+                    rest => (host, Some(rest.strip_prefix(':').ok_or_else(invalid)?)),
+                }
+            }
+            None => match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        if host.is_empty() || host.contains(|c: char| c.is_whitespace() || c == '@') {
+            return Err(invalid());
+        }
+
+        let port = match port {
+            Some(port) => port
+                .parse::<u16>()
+                .ok()
+                .filter(|port| *port != 0)
+                .ok_or_else(|| EndpointError::InvalidPort(port.into()))?,
+            None => scheme.default_port(),
+        };
+
+        Ok(Self {
+            scheme,
+            host: host.into(),
+            port,
+            path: path.into(),
+        })
+    }
+}
+
+impl FromStr for Endpoint {
+    type Err = EndpointError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "{}://[{}]:{}", self.scheme, self.host, self.port)?;
+        } else {
+            write!(f, "{}://{}:{}", self.scheme, self.host, self.port)?;
+        }
+        if self.scheme.is_websocket() {
+            write!(f, "/{}", self.path)?;
+        }
+        Ok(())
+    }
+}
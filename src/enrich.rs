@@ -0,0 +1,184 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+
+use crate::line::ReceivedLine;
+use crate::message::ClusterMessage;
+
+/// Continent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Continent {
+    /// Africa
+    Af,
+
+    /// Antarctica
+    An,
+
+    /// Asia
+    As,
+
+    /// Europe
+    Eu,
+
+    /// North America
+    Na,
+
+    /// Oceania
+    Oc,
+
+    /// South America
+    Sa,
+}
+
+impl Continent {
+    /// Get the continent by its two letter abbreviation, e.g. `EU`, ignoring the case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_uppercase().as_str() {
+            "AF" => Some(Continent::Af),
+            "AN" => Some(Continent::An),
+            "AS" => Some(Continent::As),
+            "EU" => Some(Continent::Eu),
+            "NA" => Some(Continent::Na),
+            "OC" => Some(Continent::Oc),
+            "SA" => Some(Continent::Sa),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Continent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Continent::Af => "AF",
+            Continent::An => "AN",
+            Continent::As => "AS",
+            Continent::Eu => "EU",
+            Continent::Na => "NA",
+            Continent::Oc => "OC",
+            Continent::Sa => "SA",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// DXCC entity of a callsign
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entity {
+    /// Name of the entity
+    pub name: String,
+
+    /// DXCC entity number
+    pub dxcc: u16,
+
+    /// Continent
+    pub continent: Continent,
+
+    /// CQ zone
+    pub cq_zone: u8,
+
+    /// ITU zone
+    pub itu_zone: u8,
+}
+
+/// Enrichment of received lines with additional information.
+///
+/// Enrichers run in the delivery path of the listener, right before a line is delivered.
+/// Implement this trait to add e.g. country or zone information to the spots.
+pub trait Enricher: Send + Sync {
+    /// Enrich a received line.
+    ///
+    /// # Arguments
+    ///
+    /// * `line`: Received line to enrich
+    fn enrich(&self, line: &mut ReceivedLine);
+}
+
+/// Normalization of spots and messages to get a consistent stream regardless of the node software.
+///
+/// - Callsigns are written in uppercase
+/// - Repeated whitespace in comments and texts is collapsed
+/// - A leading `de ` is removed from callsigns and comments
+/// - Frequencies are rounded to 100 Hz, i.e. one decimal place in kHz
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Normalizer;
+
+impl Normalizer {
+    /// Normalize a callsign.
+    fn call(call: &mut String) {
+        *call = strip_de(call).to_ascii_uppercase();
+    }
+
+    /// Normalize a comment or text.
+    fn text(text: &mut String) {
+        *text = strip_de(text)
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+
+    /// Normalize a frequency in Hz.
+    fn freq(freq: &mut u64) {
+        *freq = (*freq + 50) / 100 * 100;
+    }
+}
+
+impl Enricher for Normalizer {
+    fn enrich(&self, line: &mut ReceivedLine) {
+        match line.parsed.as_mut() {
+            Some(ClusterMessage::DxSpot(spot)) => {
+                Self::call(&mut spot.call_de);
+                Self::call(&mut spot.call_dx);
+                Self::freq(&mut spot.freq);
+                if let Some(comment) = spot.comment.as_mut() {
+                    Self::text(comment);
+                }
+            }
+            Some(ClusterMessage::SkimmerSpot(spot)) => {
+                Self::call(&mut spot.call_de);
+                Self::call(&mut spot.call_dx);
+                Self::freq(&mut spot.freq);
+            }
+            Some(ClusterMessage::Announce(msg)) | Some(ClusterMessage::Talk(msg)) => {
+                Self::call(&mut msg.call_de);
+                Self::call(&mut msg.target);
+                Self::text(&mut msg.text);
+            }
+            Some(ClusterMessage::Wwv(wwv)) => Self::call(&mut wwv.call_de),
+            Some(ClusterMessage::Wcy(wcy)) => Self::call(&mut wcy.call_de),
+            _ => (),
+        }
+    }
+}
+
+/// Remove a leading `de ` ignoring the case.
+fn strip_de(text: &str) -> &str {
+    let text = text.trim();
+    match text.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("de ") => text[3..].trim_start(),
+        _ => text,
+    }
+}
+
+/// Get the part of a callsign which determines its DXCC entity.
+/// Removes SSIDs and skimmer markers, e.g. `DL1ABC-#`, and portable designators, e.g. `DL1ABC/P`.
+/// A prefix designator like in `EA8/DL1ABC` is preferred over the home callsign.
+pub fn base_call(callsign: &str) -> &str {
+    // Designators not changing the entity
+    const SUFFIXES: [&str; 6] = ["P", "M", "MM", "AM", "QRP", "A"];
+
+    let callsign = callsign.split('-').next().unwrap_or(callsign);
+    let mut parts = callsign
+        .split('/')
+        .filter(|part| !part.is_empty() && !SUFFIXES.contains(&part.to_ascii_uppercase().as_str()));
+
+    match (parts.next(), parts.next()) {
+        (Some(first), Some(second)) if first.len() < second.len() => first,
+        (Some(first), Some(second)) if second.len() < first.len() => second,
+        (Some(first), _) => first,
+        (None, _) => callsign,
+    }
+}
@@ -0,0 +1,93 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+use crate::line::Source;
+use crate::listener::{ListenError, SetupStep};
+
+/// Lifecycle event of a listener, see `Listener::events` and `ListenerPool::events`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Event {
+    /// Point in time the event occurred
+    pub time: DateTime<Utc>,
+
+    /// Listener the event occurred at
+    pub source: Source,
+
+    /// Kind of the event
+    pub kind: EventKind,
+}
+
+impl Event {
+    /// Create a new event occurring now.
+    ///
+    /// # Arguments
+    ///
+    /// * `source`: Listener the event occurred at
+    /// * `kind`: Kind of the event
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of an `Event`.
+    pub fn new(source: Source, kind: EventKind) -> Self {
+        Self {
+            time: Utc::now(),
+            source,
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.source, self.kind)
+    }
+}
+
+/// Kind of a lifecycle event
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EventKind {
+    /// Connection to the server established
+    Connected,
+
+    /// Logged in with the given callsign and set up the session
+    Authenticated(String),
+
+    /// Step of setting up a new session failed, the session is closed
+    SetupFailed(SetupStep, ListenError),
+
+    /// Established session ended by an error
+    Lost(ListenError),
+
+    /// Waiting to reconnect, with the number of the upcoming attempt
+    Reconnecting(u32),
+
+    /// Stopped by an error, no further reconnects
+    GaveUp(ListenError),
+
+    /// Stopped on request
+    Stopped,
+
+    /// Filters replaced by `ListenerPool::reload`
+    FiltersUpdated,
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventKind::Connected => write!(f, "connected"),
+            EventKind::Authenticated(callsign) => write!(f, "authenticated as {}", callsign),
+            EventKind::SetupFailed(step, err) => write!(f, "{} failed ({})", step, err),
+            EventKind::Lost(err) => write!(f, "connection lost ({})", err),
+            EventKind::Reconnecting(attempt) => write!(f, "reconnecting (attempt {})", attempt),
+            EventKind::GaveUp(err) => write!(f, "gave up ({})", err),
+            EventKind::Stopped => write!(f, "stopped"),
+            EventKind::FiltersUpdated => write!(f, "filters updated"),
+        }
+    }
+}
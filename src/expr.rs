@@ -0,0 +1,561 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::band::{Band, Mode};
+use crate::enrich::Continent;
+use crate::filter::{
+    BandFilter, CallPattern, CallTarget, CallsignFilter, EntityFilter, EntityRule, Filter,
+    FilterChain, ModeFilter, SpotterFilter, SpotterRule,
+};
+use crate::line::ReceivedLine;
+
+/// Possible errors while parsing a filter expression
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum FilterParseError {
+    #[error("unexpected end of filter expression")]
+    UnexpectedEnd,
+
+    #[error("unexpected '{0}' in filter expression")]
+    UnexpectedToken(String),
+
+    #[error("unterminated string in filter expression")]
+    UnterminatedString,
+
+    #[error("unknown filter field '{0}'")]
+    UnknownField(String),
+
+    #[error("operator '{1}' not supported by filter field '{0}'")]
+    InvalidOperator(String, String),
+
+    #[error("invalid value '{1}' for filter field '{0}'")]
+    InvalidValue(String, String),
+}
+
+/// Filter parsed from a textual expression, e.g. to store filters in configuration files.
+///
+/// An expression consists of conditions combined by `and`, `or` and `not` and grouped by parentheses.
+/// A condition compares a field of a spot with a value, e.g. `band = 20m`, `mode != CW` or `dx_cont in (AF, SA)`.
+/// Values containing whitespace or special characters are quoted, e.g. `dx ~ "^3Y0"`.
+///
+/// | Field                           | Operators                      | Values                                |
+/// |---------------------------------|--------------------------------|---------------------------------------|
+/// | `band`                          | `=`, `!=`, `in`                | Band, e.g. `20m` or `70cm`            |
+/// | `freq`                          | `<`, `<=`, `>`, `>=`           | Frequency in kHz                      |
+/// | `mode`                          | `=`, `!=`, `in`                | `CW`, `SSB`, `AM`, `FM` or `DIGI`     |
+/// | `dx`, `spotter`                 | `=`, `!=`, `in`, `~`           | Callsign with wildcards or regex      |
+/// | `skimmer`                       | `=`, `!=`, `in`                | Callsign of a skimmer                 |
+/// | `dx_cont`, `spotter_cont`       | `=`, `!=`, `in`                | Continent, e.g. `EU`                  |
+/// | `dx_dxcc`, `spotter_dxcc`       | `=`, `!=`, `in`                | DXCC entity number                    |
+/// | `dx_cq`, `spotter_cq`           | `=`, `!=`, `in`                | CQ zone                               |
+/// | `dx_itu`, `spotter_itu`         | `=`, `!=`, `in`                | ITU zone                              |
+///
+/// The expression is evaluated for spots only, lines which are not spots are always accepted.
+/// Fields of the DXCC entities require an enricher adding the entities, e.g. `PrefixLookup`.
+#[derive(Clone)]
+pub struct FilterExpr {
+    /// Expression as parsed
+    source: String,
+
+    /// Root node of the parsed expression
+    root: Node,
+}
+
+impl FilterExpr {
+    /// Parse a filter expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr`: Filter expression, e.g. `band in (20m, 40m) and mode = CW and not spotter_cont = AS`
+    ///
+    /// # Result
+    ///
+    /// Returns the parsed filter or an error if the expression is invalid.
+    pub fn parse(expr: &str) -> Result<Self, FilterParseError> {
+        let tokens = tokenize(expr)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let root = parser.disjunction()?;
+        match parser.next() {
+            Some(token) => Err(FilterParseError::UnexpectedToken(token.to_string())),
+            None => Ok(Self {
+                source: expr.trim().to_string(),
+                root,
+            }),
+        }
+    }
+}
+
+impl FromStr for FilterExpr {
+    type Err = FilterParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl FromStr for FilterChain {
+    type Err = FilterParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FilterExpr::parse(s).map(|expr| FilterChain::new().with(expr))
+    }
+}
+
+impl fmt::Display for FilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl fmt::Debug for FilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FilterExpr").field(&self.source).finish()
+    }
+}
+
+impl Filter for FilterExpr {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        let spot = line
+            .parsed
+            .as_ref()
+            .is_some_and(|msg| msg.calls().is_some() && msg.freq().is_some());
+
+        !spot || self.root.eval(line)
+    }
+}
+
+/// Node of a parsed filter expression
+#[derive(Clone)]
+enum Node {
+    /// Single condition
+    Condition(Arc<dyn Filter>),
+
+    /// All nodes must match
+    And(Vec<Node>),
+
+    /// Any node must match
+    Or(Vec<Node>),
+
+    /// Node must not match
+    Not(Box<Node>),
+}
+
+impl Node {
+    /// Evaluate the node for a spot.
+    fn eval(&self, line: &ReceivedLine) -> bool {
+        match self {
+            Node::Condition(filter) => filter.accept(line),
+            Node::And(nodes) => nodes.iter().all(|node| node.eval(line)),
+            Node::Or(nodes) => nodes.iter().any(|node| node.eval(line)),
+            Node::Not(node) => !node.eval(line),
+        }
+    }
+}
+
+/// Token of a filter expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// Opening parenthesis
+    Open,
+
+    /// Closing parenthesis
+    Close,
+
+    /// Separator of values
+    Comma,
+
+    /// Comparison operator
+    Operator(String),
+
+    /// Keyword, field name or unquoted value
+    Word(String),
+
+    /// Quoted value
+    Quoted(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+            Token::Operator(op) | Token::Word(op) => write!(f, "{}", op),
+            Token::Quoted(value) => write!(f, "\"{}\"", value),
+        }
+    }
+}
+
+/// Split a filter expression into tokens.
+fn tokenize(expr: &str) -> Result<Vec<Token>, FilterParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => (),
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            ',' => tokens.push(Token::Comma),
+            '=' | '~' => tokens.push(Token::Operator(c.to_string())),
+            '!' | '<' | '>' => {
+                let mut op = c.to_string();
+                if chars.next_if_eq(&'=').is_some() {
+                    op.push('=');
+                } else if c == '!' {
+                    return Err(FilterParseError::UnexpectedToken(op));
+                }
+                tokens.push(Token::Operator(op));
+            }
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') if chars.peek() == Some(&'"') => {
+                            value.push(chars.next().unwrap())
+                        }
+                        Some(c) => value.push(c),
+                        None => return Err(FilterParseError::UnterminatedString),
+                    }
+                }
+                tokens.push(Token::Quoted(value));
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|c| !c.is_whitespace() && !"(),=~!<>\"".contains(*c))
+                {
+                    word.push(c);
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser of filter expressions
+struct Parser {
+    /// Tokens of the expression
+    tokens: Vec<Token>,
+
+    /// Position of the next token
+    pos: usize,
+}
+
+impl Parser {
+    /// Get the next token.
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consume the next token if it is the given keyword.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.tokens.get(self.pos) {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Consume the next token, which must be the given one.
+    fn expect(&mut self, expected: Token) -> Result<(), FilterParseError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(FilterParseError::UnexpectedToken(token.to_string())),
+            None => Err(FilterParseError::UnexpectedEnd),
+        }
+    }
+
+    /// Parse conditions combined by `or`.
+    fn disjunction(&mut self) -> Result<Node, FilterParseError> {
+        let mut nodes = vec![self.conjunction()?];
+        while self.keyword("or") {
+            nodes.push(self.conjunction()?);
+        }
+
+        Ok(if nodes.len() == 1 {
+            nodes.remove(0)
+        } else {
+            Node::Or(nodes)
+        })
+    }
+
+    /// Parse conditions combined by `and`.
+    fn conjunction(&mut self) -> Result<Node, FilterParseError> {
+        let mut nodes = vec![self.negation()?];
+        while self.keyword("and") {
+            nodes.push(self.negation()?);
+        }
+
+        Ok(if nodes.len() == 1 {
+            nodes.remove(0)
+        } else {
+            Node::And(nodes)
+        })
+    }
+
+    /// Parse a negated condition, a group or a single condition.
+    fn negation(&mut self) -> Result<Node, FilterParseError> {
+        if self.keyword("not") {
+            return Ok(Node::Not(Box::new(self.negation()?)));
+        }
+
+        match self.next() {
+            Some(Token::Open) => {
+                let node = self.disjunction()?;
+                self.expect(Token::Close)?;
+                Ok(node)
+            }
+            Some(Token::Word(field)) => self.condition(&field.to_ascii_lowercase()),
+            Some(token) => Err(FilterParseError::UnexpectedToken(token.to_string())),
+            None => Err(FilterParseError::UnexpectedEnd),
+        }
+    }
+
+    /// Parse the operator and the values of a condition.
+    fn condition(&mut self, field: &str) -> Result<Node, FilterParseError> {
+        let op = match self.next() {
+            Some(Token::Operator(op)) => op,
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("in") => "in".into(),
+            Some(token) => return Err(FilterParseError::UnexpectedToken(token.to_string())),
+            None => return Err(FilterParseError::UnexpectedEnd),
+        };
+
+        let values = if op == "in" {
+            self.list()?
+        } else {
+            vec![self.value()?]
+        };
+
+        let conditions = values
+            .iter()
+            .map(|value| condition(field, &op, value).map(Node::Condition))
+            .collect::<Result<Vec<Node>, FilterParseError>>()?;
+
+        Ok(match op.as_str() {
+            "!=" => Node::Not(Box::new(Node::Or(conditions))),
+            _ => Node::Or(conditions),
+        })
+    }
+
+    /// Parse a parenthesized list of values.
+    fn list(&mut self) -> Result<Vec<String>, FilterParseError> {
+        self.expect(Token::Open)?;
+        let mut values = vec![self.value()?];
+        loop {
+            match self.next() {
+                Some(Token::Comma) => values.push(self.value()?),
+                Some(Token::Close) => return Ok(values),
+                Some(token) => return Err(FilterParseError::UnexpectedToken(token.to_string())),
+                None => return Err(FilterParseError::UnexpectedEnd),
+            }
+        }
+    }
+
+    /// Parse a single value.
+    fn value(&mut self) -> Result<String, FilterParseError> {
+        match self.next() {
+            Some(Token::Word(value)) | Some(Token::Quoted(value)) => Ok(value),
+            Some(token) => Err(FilterParseError::UnexpectedToken(token.to_string())),
+            None => Err(FilterParseError::UnexpectedEnd),
+        }
+    }
+}
+
+/// Create the filter of a single condition, where `!=` is created like `=` and negated by the caller.
+fn condition(field: &str, op: &str, value: &str) -> Result<Arc<dyn Filter>, FilterParseError> {
+    let invalid_value = || FilterParseError::InvalidValue(field.into(), value.into());
+    let equality = matches!(op, "=" | "!=" | "in");
+
+    let (target, entity_field) = match field.split_once('_') {
+        Some(("dx", rest)) => (CallTarget::Dx, rest),
+        Some(("spotter", rest)) => (CallTarget::Spotter, rest),
+        _ => (CallTarget::Any, ""),
+    };
+
+    let filter: Arc<dyn Filter> = match field {
+        "band" if equality => {
+            let band = Band::from_name(value).ok_or_else(invalid_value)?;
+            Arc::new(BandFilter::new().accept_band(band))
+        }
+        "freq" if matches!(op, "<" | "<=" | ">" | ">=") => {
+            let khz: f64 = value.parse().map_err(|_| invalid_value())?;
+            let hz = (khz * 1000.0).round() as u64;
+            let (lower, upper) = match op {
+                "<" => (0, hz.saturating_sub(1)),
+                "<=" => (0, hz),
+                ">" => (hz + 1, u64::MAX),
+                _ => (hz, u64::MAX),
+            };
+            Arc::new(BandFilter::new().accept_range(lower, upper))
+        }
+        "mode" if equality => {
+            let mode = Mode::from_name(value).ok_or_else(invalid_value)?;
+            Arc::new(ModeFilter::new().accept_mode(mode))
+        }
+        "dx" | "spotter" if equality || op == "~" => {
+            let pattern = if op == "~" {
+                CallPattern::regex(value).map_err(|_| invalid_value())?
+            } else {
+                CallPattern::Wildcard(value.into())
+            };
+            let target = match field {
+                "dx" => CallTarget::Dx,
+                _ => CallTarget::Spotter,
+            };
+            Arc::new(CallsignFilter::new(target).accept_call(pattern))
+        }
+        "skimmer" if equality => {
+            Arc::new(SpotterFilter::new().accept_spotter(SpotterRule::Skimmer(value.into())))
+        }
+        _ if target != CallTarget::Any && equality => {
+            let rule = match entity_field {
+                "cont" => {
+                    EntityRule::Continent(Continent::from_name(value).ok_or_else(invalid_value)?)
+                }
+                "dxcc" => EntityRule::Dxcc(value.parse().map_err(|_| invalid_value())?),
+                "cq" => EntityRule::CqZone(value.parse().map_err(|_| invalid_value())?),
+                "itu" => EntityRule::ItuZone(value.parse().map_err(|_| invalid_value())?),
+                _ => return Err(FilterParseError::UnknownField(field.into())),
+            };
+            Arc::new(EntityFilter::new(target).accept_entity(rule))
+        }
+        "band" | "freq" | "mode" | "dx" | "spotter" | "skimmer" => {
+            return Err(FilterParseError::InvalidOperator(field.into(), op.into()))
+        }
+        _ if target != CallTarget::Any
+            && matches!(entity_field, "cont" | "dxcc" | "cq" | "itu") =>
+        {
+            return Err(FilterParseError::InvalidOperator(field.into(), op.into()))
+        }
+        _ => return Err(FilterParseError::UnknownField(field.into())),
+    };
+
+    Ok(filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spot(spotter: &str, freq: &str, dx: &str, comment: &str) -> ReceivedLine {
+        ReceivedLine::new(format!(
+            "DX de {}:  {}  {}  {}  1200Z",
+            spotter, freq, dx, comment
+        ))
+    }
+
+    fn accepts(expr: &str, line: &ReceivedLine) -> bool {
+        FilterExpr::parse(expr).unwrap().accept(line)
+    }
+
+    #[test]
+    fn band_and_mode() {
+        let line = spot("W3LPL", "14025.0", "DL1ABC", "CW 599");
+
+        assert!(accepts("band = 20m and mode = CW", &line));
+        assert!(accepts("band in (40m, 20m)", &line));
+        assert!(!accepts("band != 20m", &line));
+        assert!(!accepts("mode = SSB", &line));
+    }
+
+    #[test]
+    fn frequency_bounds() {
+        let line = spot("W3LPL", "14025.0", "DL1ABC", "CW");
+
+        assert!(accepts("freq >= 14025", &line));
+        assert!(accepts("freq <= 14025", &line));
+        assert!(!accepts("freq > 14025", &line));
+        assert!(!accepts("freq < 14025", &line));
+    }
+
+    #[test]
+    fn callsigns() {
+        let line = spot("W3LPL", "14025.0", "3Y0J", "CW");
+
+        assert!(accepts("dx ~ \"^3Y0\"", &line));
+        assert!(accepts("dx = 3Y*", &line));
+        assert!(accepts("spotter in (K1ABC, W3LPL)", &line));
+        assert!(!accepts("spotter = K1ABC", &line));
+    }
+
+    #[test]
+    fn precedence() {
+        let line = spot("W3LPL", "14025.0", "DL1ABC", "CW");
+
+        // `and` binds stronger than `or`, `not` stronger than `and`
+        assert!(accepts("mode = SSB and band = 40m or band = 20m", &line));
+        assert!(!accepts("mode = SSB and (band = 40m or band = 20m)", &line));
+        assert!(accepts("not mode = SSB and band = 20m", &line));
+        assert!(!accepts("not (mode = CW and band = 20m)", &line));
+        assert!(accepts("NOT MODE = ssb AND band = 20M", &line));
+    }
+
+    #[test]
+    fn non_spots_accepted() {
+        let line = ReceivedLine::new("To ALL de DL1ABC <1234Z>: QRV on 6m".into());
+
+        assert!(accepts("band = 6m and mode = SSB", &line));
+    }
+
+    #[test]
+    fn display() {
+        let expr = FilterExpr::parse("  band = 20m and not dx_cont = EU ").unwrap();
+
+        assert_eq!(expr.to_string(), "band = 20m and not dx_cont = EU");
+    }
+
+    #[test]
+    fn errors() {
+        let err = |expr: &str| FilterExpr::parse(expr).err();
+
+        assert_eq!(err("band ="), Some(FilterParseError::UnexpectedEnd));
+        assert_eq!(
+            err("band = 20m)"),
+            Some(FilterParseError::UnexpectedToken(")".into()))
+        );
+        assert_eq!(
+            err("band ! 20m"),
+            Some(FilterParseError::UnexpectedToken("!".into()))
+        );
+        assert_eq!(
+            err("dx ~ \"^3Y0"),
+            Some(FilterParseError::UnterminatedString)
+        );
+        assert_eq!(
+            err("color = red"),
+            Some(FilterParseError::UnknownField("color".into()))
+        );
+        assert_eq!(
+            err("band < 20m"),
+            Some(FilterParseError::InvalidOperator("band".into(), "<".into()))
+        );
+        assert_eq!(
+            err("band = 21m"),
+            Some(FilterParseError::InvalidValue("band".into(), "21m".into()))
+        );
+        assert_eq!(
+            err("dx_cont = XX"),
+            Some(FilterParseError::InvalidValue(
+                "dx_cont".into(),
+                "XX".into()
+            ))
+        );
+        assert_eq!(
+            err("band in (20m 40m)"),
+            Some(FilterParseError::UnexpectedToken("40m".into()))
+        );
+    }
+}
@@ -0,0 +1,472 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+use crate::adif::adif_header;
+use crate::csv::{csv_header, CsvColumn};
+use crate::line::ReceivedLine;
+use crate::sink::{SinkError, SpotSink};
+
+/// Format of the lines written to a file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FileFormat {
+    /// Received line as is
+    #[default]
+    Raw,
+
+    /// Received line with all derived information as a single line of JSON
+    #[cfg(feature = "json")]
+    Json,
+
+    /// Spots as ADIF records marked as SWL reports, e.g. to import them into a logging program.
+    /// New files start with an ADIF header, lines other than spots are skipped.
+    Adif,
+
+    /// Spots as CSV rows of the given columns, e.g. `DEFAULT_CSV_COLUMNS`, for spreadsheet applications.
+    /// New files start with a header row, lines other than spots are skipped.
+    Csv(Vec<CsvColumn>),
+}
+
+impl FileFormat {
+    /// Encode a line in the format, including the trailing line break.
+    fn encode(&self, line: &ReceivedLine) -> Option<String> {
+        let mut data = match self {
+            FileFormat::Raw => line.raw.clone(),
+            #[cfg(feature = "json")]
+            FileFormat::Json => line.to_json(),
+            FileFormat::Adif => line.to_adif()?,
+            FileFormat::Csv(columns) => line.to_csv(columns)?,
+        };
+        data.push('\n');
+        Some(data)
+    }
+
+    /// Get the header written at the beginning of new files, if any.
+    fn header(&self) -> Option<String> {
+        match self {
+            FileFormat::Adif => Some(adif_header()),
+            FileFormat::Csv(columns) => Some(format!("{}\n", csv_header(columns))),
+            _ => None,
+        }
+    }
+}
+
+/// Compression of the files written by a `FileSink`.
+/// The compressed stream is completed whenever the sink is flushed, the next line starts a new stream appended to the file.
+/// Common tools decompress such files as a single file. Lines written after the last flush are lost if the sink is dropped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Plain text
+    #[default]
+    None,
+
+    /// Gzip compression
+    #[cfg(feature = "gzip")]
+    Gzip,
+
+    /// Zstandard compression
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+/// Rotation of the files written by a `FileSink`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rotation {
+    /// Append to a single file
+    #[default]
+    Never,
+
+    /// Start a new file if the file would exceed the given size in bytes.
+    /// For compressed files the uncompressed size of the lines written since the file was opened is added to the size of the existing file.
+    /// The full file is renamed by appending the UTC time of the rotation to its name, e.g. `spots-20261016T120000Z.log`.
+    Size(u64),
+
+    /// Write to a file per UTC day of reception, named with the date, e.g. `spots-2026-10-16.log`
+    Daily,
+}
+
+/// Synchronization of written lines to the disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileSync {
+    /// Leave the synchronization to the operating system
+    #[default]
+    Never,
+
+    /// Synchronize when the sink is flushed and before a file is rotated
+    OnFlush,
+
+    /// Synchronize after every line
+    EveryLine,
+}
+
+/// Sink appending received lines to a file, e.g. for long-term spot archives.
+#[derive(Debug)]
+pub struct FileSink {
+    /// Path of the file, also used as base name of rotated files
+    pub path: PathBuf,
+
+    /// Format of the written lines
+    pub format: FileFormat,
+
+    /// Compression of the files
+    pub compression: Compression,
+
+    /// Rotation of the files
+    pub rotation: Rotation,
+
+    /// Synchronization of written lines to the disk
+    pub sync: FileSync,
+
+    /// Currently open file
+    file: Option<OpenFile>,
+}
+
+/// File opened for writing
+#[derive(Debug)]
+struct OpenFile {
+    /// Writer of the file
+    file: Writer,
+
+    /// Size of the file in bytes
+    size: u64,
+
+    /// UTC day the file was opened for
+    date: NaiveDate,
+}
+
+impl FileSink {
+    /// Create a new sink appending raw lines to a single file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: Path of the file
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `FileSink`. The file is opened, and created if missing, with the first line.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            format: FileFormat::default(),
+            compression: Compression::default(),
+            rotation: Rotation::default(),
+            sync: FileSync::default(),
+            file: None,
+        }
+    }
+
+    /// Close the current file, synchronizing it according to the policy.
+    async fn close(&mut self) -> Result<(), SinkError> {
+        if let Some(mut open) = self.file.take() {
+            open.file.finish().await?;
+            if self.sync != FileSync::Never {
+                open.file.sync().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Open the file for the given day, rotating the current file if required.
+    async fn prepare(&mut self, date: NaiveDate, len: u64) -> Result<&mut OpenFile, SinkError> {
+        let rotate = match (&self.file, self.rotation) {
+            (Some(open), Rotation::Daily) => open.date != date,
+            (Some(open), Rotation::Size(max)) => open.size > 0 && open.size + len > max,
+            _ => false,
+        };
+
+        if rotate {
+            self.close().await?;
+            if let Rotation::Size(_) = self.rotation {
+                let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+                fs::rename(&self.path, unique(&with_suffix(&self.path, &stamp)).await).await?;
+            }
+        }
+
+        if self.file.is_none() {
+            let path = match self.rotation {
+                Rotation::Daily => with_suffix(&self.path, &date.format("%Y-%m-%d").to_string()),
+                Rotation::Never | Rotation::Size(_) => self.path.clone(),
+            };
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await?;
+            let mut size = file.metadata().await?.len();
+            let mut file = Writer::new(file, self.compression);
+            if let (0, Some(header)) = (size, self.format.header()) {
+                file.write_all(header.as_bytes()).await?;
+                size += header.len() as u64;
+            }
+            self.file = Some(OpenFile { file, size, date });
+        }
+
+        Ok(self.file.as_mut().unwrap())
+    }
+}
+
+#[async_trait]
+impl SpotSink for FileSink {
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        let data = match self.format.encode(&line) {
+            Some(data) => data,
+            None => return Ok(()),
+        };
+
+        let sync = self.sync;
+        let open = self
+            .prepare(line.received.date_naive(), data.len() as u64)
+            .await?;
+        open.file.write_all(data.as_bytes()).await?;
+        open.size += data.len() as u64;
+
+        if sync == FileSync::EveryLine {
+            open.file.flush().await?;
+            open.file.sync().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), SinkError> {
+        if self.compression != Compression::None {
+            return self.close().await;
+        }
+
+        if let Some(open) = self.file.as_mut() {
+            open.file.flush().await?;
+            if self.sync != FileSync::Never {
+                open.file.sync().await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writer of a plain or compressed file
+#[derive(Debug)]
+enum Writer {
+    /// Plain file
+    Plain(File),
+
+    /// Gzip compressed file
+    #[cfg(feature = "gzip")]
+    Gzip(async_compression::tokio::write::GzipEncoder<File>),
+
+    /// Zstandard compressed file
+    #[cfg(feature = "zstd")]
+    Zstd(async_compression::tokio::write::ZstdEncoder<File>),
+}
+
+impl Writer {
+    /// Create a writer of the file with the given compression.
+    fn new(file: File, compression: Compression) -> Self {
+        match compression {
+            Compression::None => Writer::Plain(file),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                Writer::Gzip(async_compression::tokio::write::GzipEncoder::new(file))
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                Writer::Zstd(async_compression::tokio::write::ZstdEncoder::new(file))
+            }
+        }
+    }
+
+    /// Write the data, compressed if configured.
+    async fn write_all(&mut self, data: &[u8]) -> std::io::Result<()> {
+        match self {
+            Writer::Plain(file) => file.write_all(data).await,
+            #[cfg(feature = "gzip")]
+            Writer::Gzip(encoder) => encoder.write_all(data).await,
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(encoder) => encoder.write_all(data).await,
+        }
+    }
+
+    /// Write all buffered data to the file.
+    async fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Writer::Plain(file) => file.flush().await,
+            #[cfg(feature = "gzip")]
+            Writer::Gzip(encoder) => encoder.flush().await,
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(encoder) => encoder.flush().await,
+        }
+    }
+
+    /// Write all buffered data and complete the compressed stream.
+    async fn finish(&mut self) -> std::io::Result<()> {
+        match self {
+            Writer::Plain(file) => file.flush().await,
+            #[cfg(feature = "gzip")]
+            Writer::Gzip(encoder) => encoder.shutdown().await,
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(encoder) => encoder.shutdown().await,
+        }
+    }
+
+    /// Synchronize the written data to the disk.
+    async fn sync(&mut self) -> std::io::Result<()> {
+        match self {
+            Writer::Plain(file) => file.sync_all().await,
+            #[cfg(feature = "gzip")]
+            Writer::Gzip(encoder) => encoder.get_mut().sync_all().await,
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(encoder) => encoder.get_mut().sync_all().await,
+        }
+    }
+}
+
+/// Append a suffix to the name of a file, keeping all extensions, e.g. `spots.log.gz` becomes `spots-suffix.log.gz`.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match name.get(1..).and_then(|rest| rest.find('.')) {
+        Some(pos) => format!("{}-{}{}", &name[..=pos], suffix, &name[pos + 1..]),
+        None => format!("{}-{}", name, suffix),
+    };
+
+    path.with_file_name(name)
+}
+
+/// Get a path not used by any existing file by appending a counter if required.
+async fn unique(path: &Path) -> PathBuf {
+    let mut candidate = path.to_path_buf();
+    let mut counter = 1;
+
+    while fs::try_exists(&candidate).await.unwrap_or(false) {
+        candidate = with_suffix(path, &counter.to_string());
+        counter += 1;
+    }
+
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    /// Create an empty directory for the files of a test.
+    fn directory(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("dxcllistener-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn line(raw: &str, secs: i64) -> ReceivedLine {
+        let mut line = ReceivedLine::unparsed(raw.into());
+        line.received = DateTime::from_timestamp(1_800_000_000 + secs, 0).unwrap();
+        line
+    }
+
+    /// Get the names and contents of the files in a directory, sorted by name.
+    fn files(dir: &Path) -> Vec<(String, String)> {
+        let mut files: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                (
+                    path.file_name().unwrap().to_string_lossy().into_owned(),
+                    std::fs::read_to_string(&path).unwrap(),
+                )
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn suffix() {
+        assert_eq!(
+            with_suffix(Path::new("a/spots.log"), "1"),
+            Path::new("a/spots-1.log")
+        );
+        assert_eq!(
+            with_suffix(Path::new("spots.log.gz"), "1"),
+            Path::new("spots-1.log.gz")
+        );
+        assert_eq!(with_suffix(Path::new("spots"), "1"), Path::new("spots-1"));
+        assert_eq!(with_suffix(Path::new(".spots"), "1"), Path::new(".spots-1"));
+    }
+
+    #[tokio::test]
+    async fn append() {
+        let dir = directory("append");
+        std::fs::write(dir.join("spots.log"), "old\n").unwrap();
+
+        let mut sink = FileSink::new(dir.join("spots.log"));
+        sink.deliver(line("first", 0)).await.unwrap();
+        sink.deliver(line("second", 1)).await.unwrap();
+        sink.flush().await.unwrap();
+
+        assert_eq!(
+            files(&dir),
+            [("spots.log".into(), "old\nfirst\nsecond\n".into())]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn size_rotation() {
+        let dir = directory("size");
+        let mut sink = FileSink::new(dir.join("spots.log"));
+        sink.rotation = Rotation::Size(10);
+
+        for (n, raw) in ["line 1", "line 2", "line 3"].into_iter().enumerate() {
+            sink.deliver(line(raw, n as i64)).await.unwrap();
+        }
+        sink.flush().await.unwrap();
+
+        // Each line exceeds the remaining space, the current file is kept under its own name
+        let files = files(&dir);
+        assert_eq!(files.len(), 3);
+        assert_eq!(
+            files.last().unwrap(),
+            &("spots.log".into(), "line 3\n".into())
+        );
+        assert!(files[..2]
+            .iter()
+            .all(|(name, _)| name.starts_with("spots-")));
+        let mut rotated: Vec<_> = files[..2].iter().map(|(_, data)| data.as_str()).collect();
+        rotated.sort();
+        assert_eq!(rotated, ["line 1\n", "line 2\n"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn daily_rotation() {
+        let dir = directory("daily");
+        let mut sink = FileSink::new(dir.join("spots.log"));
+        sink.rotation = Rotation::Daily;
+
+        // 2027-01-15 08:00 UTC, the next line is received the day after
+        sink.deliver(line("first", 0)).await.unwrap();
+        sink.deliver(line("second", 60)).await.unwrap();
+        sink.deliver(line("third", 86_400)).await.unwrap();
+        sink.flush().await.unwrap();
+
+        assert_eq!(
+            files(&dir),
+            [
+                ("spots-2027-01-15.log".into(), "first\nsecond\n".into()),
+                ("spots-2027-01-16.log".into(), "third\n".into())
+            ]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
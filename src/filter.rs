@@ -0,0 +1,860 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chrono::{Datelike, NaiveTime, Weekday};
+use regex::{Regex, RegexBuilder};
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use crate::band::{Band, Mode};
+use crate::enrich::{Continent, Entity};
+use crate::line::ReceivedLine;
+use crate::message::ClusterMessage;
+
+// Frequencies of the NCDXF/IARU international beacon project in Hz
+const NCDXF_FREQUENCIES: [u64; 5] = [14_100_000, 18_110_000, 21_150_000, 24_930_000, 28_200_000];
+
+// Maximum difference in Hz between the frequency of a spot and a beacon frequency.
+// Kept to rounding errors, as regular stations are often spotted close to the beacon frequencies.
+const BEACON_TOLERANCE: u64 = 10;
+
+// Words in comments of beacon spots
+const BEACON_PATTERN: &str = r"(?i)\b(BCN|BEACON|NCDXF)\b";
+
+/// Client-side filter of received lines.
+///
+/// Filters are applied by the listener right before a line is delivered, after all enrichers ran.
+pub trait Filter: Send + Sync {
+    /// Check whether a received line shall be delivered.
+    ///
+    /// # Arguments
+    ///
+    /// * `line`: Received line
+    ///
+    /// # Result
+    ///
+    /// Returns `true` if the line shall be delivered, `false` if it shall be dropped.
+    fn accept(&self, line: &ReceivedLine) -> bool;
+
+    /// Check whether the filter remembers the lines it accepted, e.g. a `Deduplicator`.
+    /// Stateful filters are applied after the stateless filters of a `FilterChain`.
+    fn is_stateful(&self) -> bool {
+        false
+    }
+}
+
+/// Chain of filters.
+/// A line is accepted if all filters of the chain accept it, i.e. an empty chain accepts all lines.
+/// Stateless filters are applied first, so stateful filters only remember lines not dropped by any stateless filter.
+/// Stateful filters are applied in the order they were added, put e.g. a `Deduplicator` after a `Sampler` to remember sampled spots only.
+#[derive(Clone, Default)]
+pub struct FilterChain {
+    /// Filters of the chain
+    filters: Vec<Arc<dyn Filter>>,
+}
+
+impl FilterChain {
+    /// Create an empty chain accepting all lines.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a filter to the chain.
+    pub fn with<F: Filter + 'static>(mut self, filter: F) -> Self {
+        self.push(filter);
+        self
+    }
+
+    /// Append a filter to the chain.
+    pub fn push<F: Filter + 'static>(&mut self, filter: F) {
+        self.filters.push(Arc::new(filter));
+    }
+
+    /// Check if the chain contains no filters.
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+}
+
+impl fmt::Debug for FilterChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilterChain")
+            .field("filters", &self.filters.len())
+            .finish()
+    }
+}
+
+impl Filter for FilterChain {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        let stage = |stateful: bool| {
+            self.filters
+                .iter()
+                .filter(move |filter| filter.is_stateful() == stateful)
+        };
+
+        stage(false).all(|filter| filter.accept(line))
+            && stage(true).all(|filter| filter.accept(line))
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.filters.iter().any(|filter| filter.is_stateful())
+    }
+}
+
+/// Filter accepting a line if any of the given filters accepts it
+#[derive(Clone, Default)]
+pub struct AnyOf {
+    /// Alternative filters
+    filters: Vec<Arc<dyn Filter>>,
+}
+
+impl AnyOf {
+    /// Create a filter without alternatives, which rejects all lines.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an alternative filter.
+    pub fn or<F: Filter + 'static>(mut self, filter: F) -> Self {
+        self.filters.push(Arc::new(filter));
+        self
+    }
+}
+
+impl Filter for AnyOf {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        self.filters.iter().any(|filter| filter.accept(line))
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.filters.iter().any(|filter| filter.is_stateful())
+    }
+}
+
+/// Filter accepting a line if the inner filter rejects it
+#[derive(Debug, Clone)]
+pub struct Not<F>(pub F);
+
+impl<F: Filter> Filter for Not<F> {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        !self.0.accept(line)
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.0.is_stateful()
+    }
+}
+
+impl<F> Filter for F
+where
+    F: Fn(&ReceivedLine) -> bool + Send + Sync,
+{
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        self(line)
+    }
+}
+
+/// Filter of spots by band or frequency range.
+/// Spots on a rejected band or range are dropped. If any band or range is accepted, only spots on them are delivered.
+/// Lines without frequency, e.g. announcements, are always accepted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BandFilter {
+    /// Accepted frequency ranges in Hz
+    accepted: Vec<(u64, u64)>,
+
+    /// Rejected frequency ranges in Hz
+    rejected: Vec<(u64, u64)>,
+}
+
+impl BandFilter {
+    /// Create a filter accepting spots on all bands.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept spots on a band.
+    pub fn accept_band(self, band: Band) -> Self {
+        let (lower, upper) = band.range();
+        self.accept_range(lower, upper)
+    }
+
+    /// Accept spots within a frequency range.
+    ///
+    /// # Arguments
+    ///
+    /// * `lower`: Lower bound of the range in Hz, inclusive
+    /// * `upper`: Upper bound of the range in Hz, inclusive
+    pub fn accept_range(mut self, lower: u64, upper: u64) -> Self {
+        self.accepted.push((lower, upper));
+        self
+    }
+
+    /// Reject spots on a band.
+    pub fn reject_band(self, band: Band) -> Self {
+        let (lower, upper) = band.range();
+        self.reject_range(lower, upper)
+    }
+
+    /// Reject spots within a frequency range.
+    ///
+    /// # Arguments
+    ///
+    /// * `lower`: Lower bound of the range in Hz, inclusive
+    /// * `upper`: Upper bound of the range in Hz, inclusive
+    pub fn reject_range(mut self, lower: u64, upper: u64) -> Self {
+        self.rejected.push((lower, upper));
+        self
+    }
+}
+
+impl Filter for BandFilter {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        let freq = match line.parsed.as_ref().and_then(|msg| msg.freq()) {
+            Some(freq) => freq,
+            None => return true,
+        };
+        let within = |(lower, upper): &(u64, u64)| (*lower..=*upper).contains(&freq);
+
+        !self.rejected.iter().any(within)
+            && (self.accepted.is_empty() || self.accepted.iter().any(within))
+    }
+}
+
+/// Filter of spots by mode as named in the spot or inferred from the band plan.
+/// Spots of a rejected mode are dropped. If any mode is accepted, only spots of these modes are delivered,
+/// in which case spots of unknown mode are dropped as well.
+/// Lines which are not spots are always accepted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModeFilter {
+    /// Accepted modes
+    accepted: Vec<Mode>,
+
+    /// Rejected modes
+    rejected: Vec<Mode>,
+}
+
+impl ModeFilter {
+    /// Create a filter accepting spots of all modes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept spots of a mode.
+    pub fn accept_mode(mut self, mode: Mode) -> Self {
+        self.accepted.push(mode);
+        self
+    }
+
+    /// Reject spots of a mode.
+    pub fn reject_mode(mut self, mode: Mode) -> Self {
+        self.rejected.push(mode);
+        self
+    }
+}
+
+impl Filter for ModeFilter {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        if line.parsed.as_ref().and_then(|msg| msg.freq()).is_none() {
+            return true;
+        }
+
+        match line.mode {
+            Some(mode) => {
+                !self.rejected.contains(&mode)
+                    && (self.accepted.is_empty() || self.accepted.contains(&mode))
+            }
+            None => self.accepted.is_empty(),
+        }
+    }
+}
+
+/// Pattern matching a callsign
+#[derive(Debug, Clone)]
+pub enum CallPattern {
+    /// Callsign matches the whole pattern ignoring the case, where `*` matches any number of characters and `?` a single character, e.g. `VK9*`
+    Wildcard(String),
+
+    /// Callsign matches the regular expression, e.g. `^3Y0`
+    Regex(Regex),
+}
+
+impl CallPattern {
+    /// Create a pattern from a regular expression, which is matched ignoring the case.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern`: Regular expression
+    ///
+    /// # Result
+    ///
+    /// Returns the pattern or an error if the regular expression is invalid.
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(CallPattern::Regex)
+    }
+
+    /// Check if a callsign matches the pattern.
+    pub fn matches(&self, call: &str) -> bool {
+        match self {
+            CallPattern::Wildcard(pattern) => wildcard_match(
+                pattern.to_ascii_uppercase().as_bytes(),
+                call.to_ascii_uppercase().as_bytes(),
+            ),
+            CallPattern::Regex(regex) => regex.is_match(call),
+        }
+    }
+}
+
+/// Callsign of a spot a `CallsignFilter` applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallTarget {
+    /// Callsign of the spotted station
+    Dx,
+
+    /// Callsign of the spotter
+    Spotter,
+
+    /// Callsign of either the spotted station or the spotter
+    Any,
+}
+
+/// Filter of spots by callsign.
+/// Spots with a callsign matching a rejected pattern are dropped. If any pattern is accepted,
+/// only spots with a callsign matching one of them are delivered.
+/// Lines which are not spots are always accepted.
+#[derive(Debug, Clone)]
+pub struct CallsignFilter {
+    /// Callsign of the spot to match
+    target: CallTarget,
+
+    /// Accepted patterns
+    accepted: Vec<CallPattern>,
+
+    /// Rejected patterns
+    rejected: Vec<CallPattern>,
+}
+
+impl CallsignFilter {
+    /// Create a filter accepting all spots.
+    ///
+    /// # Arguments
+    ///
+    /// * `target`: Callsign of the spot to match
+    pub fn new(target: CallTarget) -> Self {
+        Self {
+            target,
+            accepted: Vec::new(),
+            rejected: Vec::new(),
+        }
+    }
+
+    /// Accept spots with a callsign matching the pattern.
+    pub fn accept_call(mut self, pattern: CallPattern) -> Self {
+        self.accepted.push(pattern);
+        self
+    }
+
+    /// Reject spots with a callsign matching the pattern.
+    pub fn reject_call(mut self, pattern: CallPattern) -> Self {
+        self.rejected.push(pattern);
+        self
+    }
+}
+
+impl Filter for CallsignFilter {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        let (spotter, dx) = match line.parsed.as_ref().and_then(|msg| msg.calls()) {
+            Some(calls) => calls,
+            None => return true,
+        };
+        let calls: &[&str] = match self.target {
+            CallTarget::Dx => &[dx],
+            CallTarget::Spotter => &[spotter],
+            CallTarget::Any => &[dx, spotter],
+        };
+        let matches = |patterns: &[CallPattern]| {
+            patterns
+                .iter()
+                .any(|pattern| calls.iter().any(|call| pattern.matches(call)))
+        };
+
+        !matches(&self.rejected) && (self.accepted.is_empty() || matches(&self.accepted))
+    }
+}
+
+/// Check if the given text matches the whole wildcard pattern.
+fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == b'?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
+/// Rule matching the DXCC entity of a callsign
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityRule {
+    /// DXCC entity number
+    Dxcc(u16),
+
+    /// Continent
+    Continent(Continent),
+
+    /// CQ zone
+    CqZone(u8),
+
+    /// ITU zone
+    ItuZone(u8),
+}
+
+impl EntityRule {
+    /// Check if an entity matches the rule.
+    pub fn matches(&self, entity: &Entity) -> bool {
+        match self {
+            EntityRule::Dxcc(dxcc) => entity.dxcc == *dxcc,
+            EntityRule::Continent(continent) => entity.continent == *continent,
+            EntityRule::CqZone(zone) => entity.cq_zone == *zone,
+            EntityRule::ItuZone(zone) => entity.itu_zone == *zone,
+        }
+    }
+}
+
+/// Filter of spots by the DXCC entity, continent or zone of the spotted station or the spotter.
+/// Requires an enricher adding the entities to the lines, e.g. `PrefixLookup`.
+/// Spots with an entity matching a rejected rule are dropped. If any rule is accepted,
+/// only spots with an entity matching one of them are delivered, in which case spots of unknown entity are dropped as well.
+/// Lines which are not spots are always accepted.
+#[derive(Debug, Clone)]
+pub struct EntityFilter {
+    /// Station of the spot to match
+    target: CallTarget,
+
+    /// Accepted rules
+    accepted: Vec<EntityRule>,
+
+    /// Rejected rules
+    rejected: Vec<EntityRule>,
+}
+
+impl EntityFilter {
+    /// Create a filter accepting all spots.
+    ///
+    /// # Arguments
+    ///
+    /// * `target`: Station of the spot to match
+    pub fn new(target: CallTarget) -> Self {
+        Self {
+            target,
+            accepted: Vec::new(),
+            rejected: Vec::new(),
+        }
+    }
+
+    /// Accept spots with an entity matching the rule.
+    pub fn accept_entity(mut self, rule: EntityRule) -> Self {
+        self.accepted.push(rule);
+        self
+    }
+
+    /// Reject spots with an entity matching the rule.
+    pub fn reject_entity(mut self, rule: EntityRule) -> Self {
+        self.rejected.push(rule);
+        self
+    }
+}
+
+impl Filter for EntityFilter {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        if line.parsed.as_ref().and_then(|msg| msg.calls()).is_none() {
+            return true;
+        }
+
+        let entities: Vec<&Entity> = match self.target {
+            CallTarget::Dx => line.dx_entity.iter().collect(),
+            CallTarget::Spotter => line.spotter_entity.iter().collect(),
+            CallTarget::Any => line.dx_entity.iter().chain(&line.spotter_entity).collect(),
+        };
+        let matches = |rules: &[EntityRule]| {
+            rules
+                .iter()
+                .any(|rule| entities.iter().any(|entity| rule.matches(entity)))
+        };
+
+        !matches(&self.rejected) && (self.accepted.is_empty() || matches(&self.accepted))
+    }
+}
+
+/// Rule matching the spotter of a spot
+#[derive(Debug, Clone)]
+pub enum SpotterRule {
+    /// Callsign of the spotter matches the pattern, e.g. `DL*`
+    Call(CallPattern),
+
+    /// DXCC entity of the spotter matches the rule, requires an enricher adding the entities
+    Entity(EntityRule),
+
+    /// Spot is sent by the given skimmer, e.g. `DK9IP` also matches the spotter `DK9IP-#`
+    Skimmer(String),
+}
+
+impl SpotterRule {
+    /// Check if the spotter of a received spot matches the rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `spotter`: Callsign of the spotter
+    /// * `entity`: DXCC entity of the spotter, if known
+    pub fn matches(&self, spotter: &str, entity: Option<&Entity>) -> bool {
+        match self {
+            SpotterRule::Call(pattern) => pattern.matches(spotter),
+            SpotterRule::Entity(rule) => entity.is_some_and(|entity| rule.matches(entity)),
+            SpotterRule::Skimmer(call) => spotter
+                .split('-')
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case(call)),
+        }
+    }
+}
+
+/// Filter of spots by their origin, e.g. to receive only spots of spotters in the own region.
+/// Spots of a spotter matching a rejected rule are dropped. If any rule is accepted,
+/// only spots of a spotter matching one of them are delivered.
+/// Lines which are not spots are always accepted.
+#[derive(Debug, Clone, Default)]
+pub struct SpotterFilter {
+    /// Accepted rules
+    accepted: Vec<SpotterRule>,
+
+    /// Rejected rules
+    rejected: Vec<SpotterRule>,
+}
+
+impl SpotterFilter {
+    /// Create a filter accepting spots of all spotters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept spots of spotters matching the rule.
+    pub fn accept_spotter(mut self, rule: SpotterRule) -> Self {
+        self.accepted.push(rule);
+        self
+    }
+
+    /// Reject spots of spotters matching the rule.
+    pub fn reject_spotter(mut self, rule: SpotterRule) -> Self {
+        self.rejected.push(rule);
+        self
+    }
+}
+
+impl Filter for SpotterFilter {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        let spotter = match line.parsed.as_ref().and_then(|msg| msg.calls()) {
+            Some((spotter, _)) => spotter,
+            None => return true,
+        };
+        let entity = line.spotter_entity.as_ref();
+        let matches =
+            |rules: &[SpotterRule]| rules.iter().any(|rule| rule.matches(spotter, entity));
+
+        !matches(&self.rejected) && (self.accepted.is_empty() || matches(&self.accepted))
+    }
+}
+
+/// Filter dropping spots of the own station or network, e.g. to keep the own club's spots from triggering alerts.
+/// A spot is dropped if the spotter is one of the given callsigns, ignoring SSIDs, prefix designators and portable suffixes,
+/// or if the callsign of the spotter starts with one of the given prefixes.
+/// Lines which are not spots are always accepted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OwnSpotFilter {
+    /// Own callsigns
+    calls: Vec<String>,
+
+    /// Callsign prefixes of the own network
+    prefixes: Vec<String>,
+}
+
+impl OwnSpotFilter {
+    /// Create a filter accepting all spots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop spots of a callsign.
+    pub fn with_call(mut self, call: &str) -> Self {
+        self.calls.push(call.to_ascii_uppercase());
+        self
+    }
+
+    /// Drop spots of all spotters with a callsign starting with the prefix, e.g. `DL0` for club stations.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefixes.push(prefix.to_ascii_uppercase());
+        self
+    }
+}
+
+impl Filter for OwnSpotFilter {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        let spotter = match line.parsed.as_ref().and_then(|msg| msg.calls()) {
+            Some((spotter, _)) => spotter.to_ascii_uppercase(),
+            None => return true,
+        };
+        let call = spotter.split('-').next().unwrap_or(&spotter);
+
+        let own = call
+            .split('/')
+            .any(|part| self.calls.iter().any(|own| own == part));
+        let network = self
+            .prefixes
+            .iter()
+            .any(|prefix| spotter.starts_with(prefix.as_str()));
+
+        !own && !network
+    }
+}
+
+/// Filter of beacon spots, since beacon monitors want only these and DXers usually none of them.
+/// A spot is considered a beacon spot if the callsign ends with `/B`, the frequency is exactly one of the NCDXF beacon frequencies,
+/// or the comment names a beacon, e.g. `BCN`.
+/// Lines which are not spots are always accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeaconFilter {
+    /// True to accept only beacon spots, false to drop them
+    beacons: bool,
+}
+
+impl BeaconFilter {
+    /// Create a filter accepting only beacon spots.
+    pub fn only() -> Self {
+        Self { beacons: true }
+    }
+
+    /// Create a filter dropping beacon spots.
+    pub fn without() -> Self {
+        Self { beacons: false }
+    }
+}
+
+impl Filter for BeaconFilter {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        match line.parsed.as_ref() {
+            Some(msg) if msg.freq().is_some() => is_beacon(msg) == self.beacons,
+            _ => true,
+        }
+    }
+}
+
+/// Check if a message is a spot of a beacon.
+pub fn is_beacon(msg: &ClusterMessage) -> bool {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| Regex::new(BEACON_PATTERN).unwrap());
+
+    let (call, freq, comment) = match msg {
+        ClusterMessage::DxSpot(spot) => (&spot.call_dx, spot.freq, spot.comment.as_deref()),
+        ClusterMessage::SkimmerSpot(spot) => (&spot.call_dx, spot.freq, spot.info.as_deref()),
+        _ => return false,
+    };
+
+    call.to_ascii_uppercase().ends_with("/B")
+        || NCDXF_FREQUENCIES
+            .iter()
+            .any(|beacon| beacon.abs_diff(freq) <= BEACON_TOLERANCE)
+        || comment.is_some_and(|comment| pattern.is_match(comment))
+}
+
+/// Filter of spots by the time of day and the day of the week in UTC they are received at,
+/// e.g. for grayline chasers or to mute alerts overnight.
+/// If any time window is given, only spots received within one of them are delivered.
+/// If any weekday is given, only spots received on one of them are delivered.
+/// Lines which are not spots are always accepted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimeFilter {
+    /// Time windows as start and end in UTC
+    windows: Vec<(NaiveTime, NaiveTime)>,
+
+    /// Weekdays
+    weekdays: Vec<Weekday>,
+}
+
+impl TimeFilter {
+    /// Create a filter accepting spots at any time.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept spots received within a time window.
+    ///
+    /// # Arguments
+    ///
+    /// * `start`: Start of the window in UTC, inclusive
+    /// * `end`: End of the window in UTC, exclusive. A window ending before its start spans midnight,
+    ///   a window ending at its start spans the whole day.
+    pub fn with_window(mut self, start: NaiveTime, end: NaiveTime) -> Self {
+        self.windows.push((start, end));
+        self
+    }
+
+    /// Accept spots received on a weekday in UTC.
+    pub fn with_weekday(mut self, weekday: Weekday) -> Self {
+        self.weekdays.push(weekday);
+        self
+    }
+}
+
+impl Filter for TimeFilter {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        if line.parsed.as_ref().and_then(|msg| msg.freq()).is_none() {
+            return true;
+        }
+
+        let time = line.received.time();
+        let within = |(start, end): &(NaiveTime, NaiveTime)| {
+            if start < end {
+                *start <= time && time < *end
+            } else {
+                *start <= time || time < *end
+            }
+        };
+
+        (self.windows.is_empty() || self.windows.iter().any(within))
+            && (self.weekdays.is_empty() || self.weekdays.contains(&line.received.weekday()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Stateful filter remembering the lines it was asked for
+    #[derive(Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Filter for Recorder {
+        fn accept(&self, line: &ReceivedLine) -> bool {
+            self.0.lock().unwrap().push(line.raw.clone());
+            true
+        }
+
+        fn is_stateful(&self) -> bool {
+            true
+        }
+    }
+
+    fn spot(dx: &str, comment: &str) -> ReceivedLine {
+        ReceivedLine::new(format!(
+            "DX de W3LPL:     14025.0  {}  {}  1200Z",
+            dx, comment
+        ))
+    }
+
+    #[test]
+    fn empty_chain() {
+        let chain = FilterChain::new();
+
+        assert!(chain.is_empty());
+        assert!(!chain.is_stateful());
+        assert!(chain.accept(&spot("DL1ABC", "CW")));
+    }
+
+    #[test]
+    fn stateful_last() {
+        let recorder = Recorder::default();
+        let seen = recorder.0.clone();
+        let chain = FilterChain::new()
+            .with(recorder)
+            .with(|line: &ReceivedLine| !line.raw.contains("FT8"))
+            .with(BeaconFilter::without());
+
+        assert!(chain.is_stateful());
+        assert!(chain.accept(&spot("DL1ABC", "CW")));
+        assert!(!chain.accept(&spot("OK1ABC", "FT8")));
+        assert!(!chain.accept(&spot("DK0WCY/B", "BCN")));
+
+        // The stateful filter only saw the line accepted by all stateless filters
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert!(seen[0].contains("DL1ABC"));
+    }
+
+    #[test]
+    fn combinators() {
+        let cw = |line: &ReceivedLine| line.raw.contains("CW");
+        let ft8 = |line: &ReceivedLine| line.raw.contains("FT8");
+
+        let any = AnyOf::new().or(cw).or(ft8);
+        assert!(any.accept(&spot("DL1ABC", "CW")));
+        assert!(any.accept(&spot("DL1ABC", "FT8")));
+        assert!(!any.accept(&spot("DL1ABC", "SSB")));
+        assert!(!AnyOf::new().accept(&spot("DL1ABC", "CW")));
+
+        let not = Not(cw);
+        assert!(!not.accept(&spot("DL1ABC", "CW")));
+        assert!(not.accept(&spot("DL1ABC", "SSB")));
+
+        // Combinators containing a stateful filter are stateful themselves
+        assert!(Not(Recorder::default()).is_stateful());
+        assert!(AnyOf::new().or(cw).or(Recorder::default()).is_stateful());
+        assert!(!AnyOf::new().or(cw).is_stateful());
+    }
+
+    #[test]
+    fn time_window() {
+        let at = |hour: u32, minute: u32| {
+            let mut line = spot("DL1ABC", "CW");
+            line.received = chrono::NaiveDate::from_ymd_opt(2026, 10, 16)
+                .unwrap()
+                .and_hms_opt(hour, minute, 0)
+                .unwrap()
+                .and_utc();
+            line
+        };
+        let time = |hour: u32| NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+
+        let day = TimeFilter::new().with_window(time(6), time(18));
+        assert!(!day.accept(&at(5, 59)));
+        assert!(day.accept(&at(6, 0)));
+        assert!(!day.accept(&at(18, 0)));
+
+        let night = TimeFilter::new().with_window(time(22), time(2));
+        assert!(night.accept(&at(23, 0)));
+        assert!(night.accept(&at(1, 59)));
+        assert!(!night.accept(&at(2, 0)));
+
+        // A window ending at its start spans the whole day
+        let full = TimeFilter::new().with_window(time(6), time(6));
+        assert!(full.accept(&at(5, 59)));
+        assert!(full.accept(&at(6, 0)));
+        assert!(full.accept(&at(12, 0)));
+
+        // 2026-10-16 is a Friday
+        let weekend = TimeFilter::new()
+            .with_weekday(Weekday::Sat)
+            .with_weekday(Weekday::Sun);
+        assert!(!weekend.accept(&at(12, 0)));
+    }
+}
@@ -0,0 +1,98 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::time::Duration;
+
+use crate::auth::AuthPrompt;
+
+// Command prompt of DXSpider, e.g. `N0CALL de GB7XXX 16-Oct-2026 1200Z dxspider >`.
+const DXSPIDER_PROMPT: &str = r"^\S+ de \S+\s+\d{1,2}-\w{3}-\d{4} \d{4}Z .*>\s*$";
+
+// Command prompt of AR-Cluster, e.g. `N0CALL de NODE 16-Oct-2026 1200Z >` or `N0CALL de NODE>`.
+const ARCLUSTER_PROMPT: &str = r"^\S+ de \S+(\s+\d{1,2}-\w{3}-\d{4} \d{4}Z\s*)?>\s*$";
+
+// Chat lines of the AR-Cluster conference mode, e.g. `<N0CALL>: hello` or `*** N0CALL joined conference`.
+const ARCLUSTER_NOISE: &str = r"^(<\S+>:?\s|\*\*\* .*(joined|left|entered|exited) .*conference)";
+
+// Idle connections are dropped by AR-Cluster, so send something from time to time.
+const ARCLUSTER_KEEPALIVE: Duration = Duration::from_secs(5 * 60);
+
+/// Cluster server software specific behavior
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum ClusterFlavor {
+    /// Unknown server software, no special handling
+    #[default]
+    Generic,
+
+    /// DXSpider
+    DxSpider,
+
+    /// AR-Cluster v6
+    ArCluster,
+
+    /// Telnet feed of the Reverse Beacon Network.
+    /// The feed sends tens of skimmer spots per second and does not support any commands.
+    Rbn,
+}
+
+impl ClusterFlavor {
+    /// Commands recommended to be sent right after the login.
+    pub fn login_commands(&self) -> Vec<String> {
+        match self {
+            ClusterFlavor::Generic | ClusterFlavor::Rbn => Vec::new(),
+            // Disable paging of long outputs and beeps on new spots
+            ClusterFlavor::DxSpider => vec!["set/page 0".into(), "unset/beep".into()],
+            // Disable paging of long outputs
+            ClusterFlavor::ArCluster => vec!["set/page 0".into()],
+        }
+    }
+
+    /// Authentication prompts used by the server in addition to the built-in ones.
+    pub fn auth_prompts(&self) -> Vec<AuthPrompt> {
+        match self {
+            ClusterFlavor::Generic | ClusterFlavor::DxSpider | ClusterFlavor::Rbn => Vec::new(),
+            ClusterFlavor::ArCluster => vec![AuthPrompt::Suffix("call:".into())],
+        }
+    }
+
+    /// True if the server echoes the callsign and sends chatter after the login
+    /// which shall be hidden until the first spot arrives.
+    pub fn suppress_login_chatter(&self) -> bool {
+        match self {
+            ClusterFlavor::Generic => false,
+            ClusterFlavor::DxSpider | ClusterFlavor::ArCluster | ClusterFlavor::Rbn => true,
+        }
+    }
+
+    /// Regular expression matching the command prompt of the server.
+    pub fn prompt_pattern(&self) -> Option<&'static str> {
+        match self {
+            ClusterFlavor::Generic | ClusterFlavor::Rbn => None,
+            ClusterFlavor::DxSpider => Some(DXSPIDER_PROMPT),
+            ClusterFlavor::ArCluster => Some(ARCLUSTER_PROMPT),
+        }
+    }
+
+    /// Regular expression matching lines which are neither spots nor messages
+    /// and shall not be delivered.
+    pub fn noise_pattern(&self) -> Option<&'static str> {
+        match self {
+            ClusterFlavor::Generic | ClusterFlavor::DxSpider | ClusterFlavor::Rbn => None,
+            ClusterFlavor::ArCluster => Some(ARCLUSTER_NOISE),
+        }
+    }
+
+    /// Recommended interval to send the keepalive command in to keep an idle connection alive.
+    pub fn keepalive(&self) -> Option<Duration> {
+        match self {
+            ClusterFlavor::Generic | ClusterFlavor::DxSpider | ClusterFlavor::Rbn => None,
+            ClusterFlavor::ArCluster => Some(ARCLUSTER_KEEPALIVE),
+        }
+    }
+}
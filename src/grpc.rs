@@ -0,0 +1,183 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::{BroadcastStream, TcpListenerStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+use crate::expr::FilterExpr;
+use crate::filter::Filter;
+use crate::line::ReceivedLine;
+use crate::record::SpotRecord;
+
+use proto::spot_service_server::{SpotService, SpotServiceServer};
+use proto::{FilterRequest, SpotEnvelope};
+
+/// Messages and service definitions generated from `proto/dxcllistener.proto`
+#[allow(clippy::all)]
+pub mod proto {
+    include!("grpc/dxcllistener.v1.rs");
+}
+
+/// Stream of spot envelopes returned to subscribers
+type EnvelopeStream = Pin<Box<dyn Stream<Item = Result<SpotEnvelope, Status>> + Send>>;
+
+/// gRPC server streaming received spots to subscribers, see `proto/dxcllistener.proto`.
+///
+/// Subscribers pass a filter expression to only receive matching spots, see `FilterExpr`.
+/// Subscriptions with an invalid expression fail with status `INVALID_ARGUMENT`.
+/// Subscribers not keeping up with the stream miss spots instead of slowing down the server.
+#[derive(Debug, Clone)]
+pub struct GrpcServer {
+    /// Address to listen on, e.g. `0.0.0.0:7390`
+    pub addr: SocketAddr,
+
+    /// Number of spots buffered per subscriber
+    pub queue: usize,
+}
+
+/// Implementation of the spot service, to be added to a custom tonic server with `GrpcServer::service`.
+#[derive(Debug)]
+pub struct SpotFeed {
+    /// Receiver of the received spots, resubscribed for every subscriber
+    lines: broadcast::Receiver<Arc<ReceivedLine>>,
+}
+
+impl GrpcServer {
+    /// Create a new server with default settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr`: Address to listen on
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `GrpcServer`.
+    pub fn new(addr: SocketAddr) -> Self {
+        Self { addr, queue: 1000 }
+    }
+
+    /// Start listening for subscribers and stream the received spots to them.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: Channel of the received lines, e.g. the merged output of multiple listeners
+    ///
+    /// # Result
+    ///
+    /// Returns the handle of the server task or an error if listening on the address failed.
+    /// The server stops and ends all subscriptions once the input channel is closed or the task is aborted.
+    pub async fn spawn(
+        self,
+        input: mpsc::UnboundedReceiver<ReceivedLine>,
+    ) -> Result<JoinHandle<Result<(), tonic::transport::Error>>, std::io::Error> {
+        let listener = TcpListener::bind(self.addr).await?;
+        let (feed, forwarder) = SpotFeed::spawn(input, self.queue);
+
+        Ok(tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(SpotServiceServer::new(feed))
+                .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async {
+                    let _ = forwarder.await;
+                })
+                .await
+        }))
+    }
+
+    /// Create the service to be added to a custom tonic server, e.g. along with other services.
+    /// Must be called within a tokio runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: Channel of the received lines
+    ///
+    /// # Result
+    ///
+    /// Returns the service. All subscriptions end once the input channel is closed.
+    pub fn service(
+        self,
+        input: mpsc::UnboundedReceiver<ReceivedLine>,
+    ) -> SpotServiceServer<SpotFeed> {
+        SpotServiceServer::new(SpotFeed::spawn(input, self.queue).0)
+    }
+}
+
+impl SpotFeed {
+    /// Create the feed and spawn the task forwarding the received spots to the subscribers.
+    fn spawn(
+        mut input: mpsc::UnboundedReceiver<ReceivedLine>,
+        queue: usize,
+    ) -> (Self, JoinHandle<()>) {
+        let (sender, lines) = broadcast::channel(queue.max(1));
+
+        let forwarder = tokio::spawn(async move {
+            while let Some(line) = input.recv().await {
+                if line.parsed.as_ref().and_then(|msg| msg.calls()).is_some() {
+                    let _ = sender.send(Arc::new(line));
+                }
+            }
+        });
+
+        (Self { lines }, forwarder)
+    }
+}
+
+#[tonic::async_trait]
+impl SpotService for SpotFeed {
+    type SubscribeStream = EnvelopeStream;
+
+    async fn subscribe(
+        &self,
+        request: Request<FilterRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let filter = request.into_inner().filter;
+        let filter = match filter.trim() {
+            "" => None,
+            expr => Some(
+                FilterExpr::parse(expr).map_err(|err| Status::invalid_argument(err.to_string()))?,
+            ),
+        };
+
+        let stream = BroadcastStream::new(self.lines.resubscribe()).filter_map(move |line| {
+            let line = line.ok()?;
+            match &filter {
+                Some(filter) if !filter.accept(&line) => None,
+                _ => envelope(&line).map(Ok),
+            }
+        });
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Create the envelope of a received spot.
+fn envelope(line: &ReceivedLine) -> Option<SpotEnvelope> {
+    let record = SpotRecord::from_line(line)?;
+
+    Some(SpotEnvelope {
+        raw: line.raw.clone(),
+        received_ms: record.received.timestamp_millis(),
+        time_ms: record.time.timestamp_millis(),
+        spotter: record.spotter,
+        dx: record.dx,
+        freq: record.freq,
+        band: record.band.map(|band| band.to_string()).unwrap_or_default(),
+        mode: record.mode.map(|mode| mode.to_string()).unwrap_or_default(),
+        comment: record.comment.unwrap_or_default(),
+        snr: record.snr.map(i32::from),
+        speed: record.speed.map(u32::from),
+        skimmer: record.skimmer,
+        spotter_grid: record.spotter_grid.unwrap_or_default(),
+        dx_grid: record.dx_grid.unwrap_or_default(),
+        spotter_dxcc: record.spotter_dxcc.map(u32::from),
+        dx_dxcc: record.dx_dxcc.map(u32::from),
+        source: record.source,
+    })
+}
@@ -0,0 +1,218 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::enrich::Entity;
+use crate::line::ReceivedLine;
+use crate::record::SpotRecord;
+use crate::sink::{SinkError, SpotSink};
+
+// Maximum time to wait for the response to a request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Measurement of spots outside of the known bands
+const UNKNOWN_BAND: &str = "unknown";
+
+impl ReceivedLine {
+    /// Convert a spot into a single point in InfluxDB line protocol without trailing line break.
+    ///
+    /// The measurement is the band of the spot, e.g. `20m`, or `unknown`. The point is tagged with
+    /// `continent` and `spotter_continent` if added by an enricher, `mode` if known, `skimmer`,
+    /// and `source`, the listener the spot was received by, i.e. its label if set.
+    /// Fields are the callsigns `dx` and `spotter`, the frequency `freq` in Hz, and `snr`, `speed`
+    /// and the DXCC entity number `dxcc` of the spotted station if known.
+    /// The timestamp is the point in time the spot was received, in nanoseconds.
+    ///
+    /// # Result
+    ///
+    /// Returns the point or `None` if the line is not a parsed spot.
+    pub fn to_line_protocol(&self) -> Option<String> {
+        let record = SpotRecord::from_line(self)?;
+        let continent =
+            |entity: &Option<Entity>| entity.as_ref().map(|entity| entity.continent.to_string());
+
+        let measurement = record
+            .band
+            .map_or(UNKNOWN_BAND.to_string(), |band| band.to_string());
+        let mut point = escape(&measurement, &[',', ' ']);
+
+        let tags = [
+            ("continent", continent(&self.dx_entity)),
+            ("mode", record.mode.map(|mode| mode.to_string())),
+            ("skimmer", Some(record.skimmer.to_string())),
+            ("source", Some(record.source.clone())),
+            ("spotter_continent", continent(&self.spotter_entity)),
+        ];
+        for (key, value) in tags {
+            if let Some(value) = value {
+                point.push_str(&format!(",{}={}", key, escape(&value, &[',', '=', ' '])));
+            }
+        }
+
+        let mut fields = vec![
+            format!("dx=\"{}\"", escape(&record.dx, &['"', '\\'])),
+            format!("spotter=\"{}\"", escape(&record.spotter, &['"', '\\'])),
+            format!("freq={}i", record.freq),
+        ];
+        if let Some(snr) = record.snr {
+            fields.push(format!("snr={}i", snr));
+        }
+        if let Some(speed) = record.speed {
+            fields.push(format!("speed={}i", speed));
+        }
+        if let Some(dxcc) = record.dx_dxcc {
+            fields.push(format!("dxcc={}i", dxcc));
+        }
+
+        point.push(' ');
+        point.push_str(&fields.join(","));
+        if let Some(time) = record.received.timestamp_nanos_opt() {
+            point.push_str(&format!(" {}", time));
+        }
+
+        Some(point)
+    }
+}
+
+/// Sink writing spots in batches to InfluxDB using the HTTP write API, e.g. to graph spot rates in Grafana.
+///
+/// See `ReceivedLine::to_line_protocol` for the written points. Points of failed writes are kept
+/// and written along with the next batch, unless refused by the server with a client error.
+/// The oldest points are dropped once `max_pending` is exceeded.
+/// Lines other than spots are ignored.
+#[derive(Debug)]
+pub struct InfluxSink {
+    /// URL of the write endpoint including database or bucket, e.g.
+    /// `http://localhost:8086/api/v2/write?org=ham&bucket=spots` or `http://localhost:8086/write?db=spots`
+    pub url: String,
+
+    /// API token sent with every request, if required
+    pub token: Option<String>,
+
+    /// Number of points written in a single request
+    pub batch_size: usize,
+
+    /// Maximum number of points kept while writing fails
+    pub max_pending: usize,
+
+    /// HTTP client
+    client: reqwest::Client,
+
+    /// Points not written yet
+    pending: VecDeque<String>,
+
+    /// Number of points delivered since the last write
+    delivered: usize,
+}
+
+impl InfluxSink {
+    /// Create a new sink with default settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `url`: URL of the write endpoint including database or bucket
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of an `InfluxSink` or `SinkError::Backend` if the HTTP client could not be created.
+    pub fn new(url: &str) -> Result<Self, SinkError> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(backend)?;
+
+        Ok(Self {
+            url: url.into(),
+            token: None,
+            batch_size: 100,
+            max_pending: 10_000,
+            client,
+            pending: VecDeque::new(),
+            delivered: 0,
+        })
+    }
+
+    /// Write the pending points in a single request.
+    async fn write(&mut self) -> Result<(), SinkError> {
+        self.delivered = 0;
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let body: String = self
+            .pending
+            .iter()
+            .map(|point| point.clone() + "\n")
+            .collect();
+        let mut req = self.client.post(&self.url).body(body);
+        if let Some(token) = &self.token {
+            req = req.header(reqwest::header::AUTHORIZATION, format!("Token {}", token));
+        }
+
+        let resp = req.send().await.map_err(backend)?;
+        let status = resp.status();
+        // Points refused by the server would be refused again, only keep them if the server is unavailable
+        if status.is_success()
+            || (status.is_client_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS)
+        {
+            self.pending.clear();
+        }
+
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(SinkError::Backend(format!(
+                "write responded with {}: {}",
+                status,
+                resp.text().await.unwrap_or_default()
+            )))
+        }
+    }
+}
+
+#[async_trait]
+impl SpotSink for InfluxSink {
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        let point = match line.to_line_protocol() {
+            Some(point) => point,
+            None => return Ok(()),
+        };
+
+        self.pending.push_back(point);
+        self.delivered += 1;
+        if self.pending.len() > self.max_pending {
+            self.pending.pop_front();
+        }
+
+        if self.delivered >= self.batch_size {
+            self.write().await
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn flush(&mut self) -> Result<(), SinkError> {
+        self.write().await
+    }
+}
+
+/// Escape the given special characters with a backslash.
+fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Convert an HTTP client error.
+fn backend(err: reqwest::Error) -> SinkError {
+    SinkError::Backend(err.to_string())
+}
@@ -0,0 +1,138 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use std::io::Write;
+use std::path::Path;
+use tokio::net::UnixDatagram;
+
+use crate::line::ReceivedLine;
+use crate::record::SpotRecord;
+use crate::sink::{SinkError, SpotSink};
+
+// Socket of the journal accepting entries in the native protocol
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+// Priority of the entries, informational
+const PRIORITY: &str = "6";
+
+/// Sink writing each spot as structured entry to the systemd journal, e.g. when running as system service.
+///
+/// The message of an entry is the received line, the information of the spot is added as fields
+/// `SPOT_DX`, `SPOT_SPOTTER`, `SPOT_FREQ` in Hz, `SPOT_BAND`, `SPOT_MODE`, `SPOT_COMMENT`, `SPOT_SNR`, `SPOT_SPEED`,
+/// `SPOT_SKIMMER`, `SPOT_DX_GRID`, `SPOT_SPOTTER_GRID`, `SPOT_DX_DXCC`, `SPOT_SPOTTER_DXCC` and `SPOT_SOURCE`
+/// if known. Spots are queried with e.g. `journalctl -t dxcllistener SPOT_BAND=20m -o json`.
+///
+/// Spots are written as JSON lines to stdout instead if the journal is not available.
+/// Lines other than spots are ignored.
+#[derive(Debug)]
+pub struct JournalSink {
+    /// Identifier of the entries, see `SYSLOG_IDENTIFIER`
+    pub identifier: String,
+
+    /// Socket connected to the journal, `None` if writing to stdout
+    socket: Option<UnixDatagram>,
+}
+
+impl JournalSink {
+    /// Create a new sink writing to the journal, or to stdout if the journal is not available.
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `JournalSink` or an error if the socket could not be created.
+    /// Must be called within a tokio runtime.
+    pub fn new() -> Result<Self, SinkError> {
+        let socket = if Path::new(JOURNAL_SOCKET).exists() {
+            let socket = UnixDatagram::unbound()?;
+            socket.connect(JOURNAL_SOCKET)?;
+            Some(socket)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            identifier: "dxcllistener".into(),
+            socket,
+        })
+    }
+
+    /// Create a new sink writing JSON lines to stdout.
+    pub fn stdout() -> Self {
+        Self {
+            identifier: "dxcllistener".into(),
+            socket: None,
+        }
+    }
+
+    /// Check if the sink writes to the journal.
+    pub fn is_journal(&self) -> bool {
+        self.socket.is_some()
+    }
+}
+
+#[async_trait]
+impl SpotSink for JournalSink {
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        let record = match SpotRecord::from_line(&line) {
+            Some(record) => record,
+            None => return Ok(()),
+        };
+
+        let socket = match &self.socket {
+            Some(socket) => socket,
+            None => {
+                let mut stdout = std::io::stdout().lock();
+                writeln!(stdout, "{}", line.to_json())?;
+                return Ok(stdout.flush()?);
+            }
+        };
+
+        let fields = [
+            ("MESSAGE", Some(line.raw.clone())),
+            ("PRIORITY", Some(PRIORITY.into())),
+            ("SYSLOG_IDENTIFIER", Some(self.identifier.clone())),
+            ("SPOT_DX", Some(record.dx)),
+            ("SPOT_SPOTTER", Some(record.spotter)),
+            ("SPOT_FREQ", Some(record.freq.to_string())),
+            ("SPOT_BAND", record.band.map(|band| band.to_string())),
+            ("SPOT_MODE", record.mode.map(|mode| mode.to_string())),
+            ("SPOT_COMMENT", record.comment),
+            ("SPOT_SNR", record.snr.map(|snr| snr.to_string())),
+            ("SPOT_SPEED", record.speed.map(|speed| speed.to_string())),
+            ("SPOT_SKIMMER", Some(record.skimmer.to_string())),
+            ("SPOT_DX_GRID", record.dx_grid),
+            ("SPOT_SPOTTER_GRID", record.spotter_grid),
+            ("SPOT_DX_DXCC", record.dx_dxcc.map(|dxcc| dxcc.to_string())),
+            (
+                "SPOT_SPOTTER_DXCC",
+                record.spotter_dxcc.map(|dxcc| dxcc.to_string()),
+            ),
+            ("SPOT_SOURCE", Some(record.source)),
+        ];
+
+        let mut entry = Vec::new();
+        for (name, value) in fields {
+            if let Some(value) = value {
+                push_field(&mut entry, name, &value);
+            }
+        }
+        socket.send(&entry).await?;
+
+        Ok(())
+    }
+}
+
+/// Append a field to an entry in the native journal protocol.
+/// Values containing line breaks are written in the binary format prefixed with their length.
+fn push_field(entry: &mut Vec<u8>, name: &str, value: &str) {
+    entry.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        entry.push(b'\n');
+        entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        entry.push(b'=');
+    }
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(b'\n');
+}
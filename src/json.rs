@@ -0,0 +1,14 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::line::ReceivedLine;
+
+impl ReceivedLine {
+    /// Convert the received line into a single line of JSON without trailing line break.
+    pub fn to_json(&self) -> String {
+        // Only fails for maps with non-string keys or failing custom implementations of `Serialize`,
+        // neither is used by the derived implementations of the received line and its fields
+        serde_json::to_string(self).expect("received line is always serializable")
+    }
+}
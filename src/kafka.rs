@@ -0,0 +1,103 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
+use std::time::Duration;
+
+use crate::line::ReceivedLine;
+use crate::sink::{SinkError, SpotSink};
+
+// Maximum time to wait for outstanding messages while flushing
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sink publishing each spot as JSON to a Kafka topic.
+///
+/// Messages are keyed by the callsign of the spotted station, so all spots of a station end up in the same partition.
+/// Spots are handed to the producer queue without waiting for their delivery to keep up with high volumes,
+/// the delivery fails if the queue is full. Flushing the sink waits for all queued messages to be delivered.
+/// Lines other than spots are ignored.
+pub struct KafkaSink {
+    /// Topic to publish the spots to
+    pub topic: String,
+
+    /// Producer of the messages
+    producer: FutureProducer,
+}
+
+impl KafkaSink {
+    /// Create a new sink with default settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `brokers`: Comma separated list of brokers, e.g. `localhost:9092`
+    /// * `topic`: Topic to publish the spots to
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `KafkaSink` or `SinkError::Backend` if the producer could not be created.
+    pub fn new(brokers: &str, topic: &str) -> Result<Self, SinkError> {
+        let mut config = ClientConfig::new();
+        config.set("bootstrap.servers", brokers);
+        Self::with_config(&config, topic)
+    }
+
+    /// Create a new sink with the given producer configuration, e.g. to set up authentication or compression.
+    ///
+    /// # Arguments
+    ///
+    /// * `config`: Configuration of the producer, including `bootstrap.servers`
+    /// * `topic`: Topic to publish the spots to
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `KafkaSink` or `SinkError::Backend` if the producer could not be created.
+    pub fn with_config(config: &ClientConfig, topic: &str) -> Result<Self, SinkError> {
+        let producer = config.create().map_err(backend)?;
+
+        Ok(Self {
+            topic: topic.into(),
+            producer,
+        })
+    }
+}
+
+impl std::fmt::Debug for KafkaSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaSink")
+            .field("topic", &self.topic)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl SpotSink for KafkaSink {
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        let dx = match line.parsed.as_ref().and_then(|msg| msg.calls()) {
+            Some((_, dx)) => dx.to_string(),
+            None => return Ok(()),
+        };
+
+        let json = line.to_json();
+        let record = FutureRecord::to(&self.topic).key(&dx).payload(&json);
+        self.producer
+            .send_result(record)
+            .map(|_| ())
+            .map_err(|(err, _)| backend(err))
+    }
+
+    async fn flush(&mut self) -> Result<(), SinkError> {
+        let producer = self.producer.clone();
+        tokio::task::spawn_blocking(move || producer.flush(FLUSH_TIMEOUT))
+            .await
+            .map_err(|err| SinkError::Backend(err.to_string()))?
+            .map_err(backend)
+    }
+}
+
+/// Convert a Kafka error.
+fn backend(err: rdkafka::error::KafkaError) -> SinkError {
+    SinkError::Backend(err.to_string())
+}
@@ -0,0 +1,490 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+// Special characters of the KISS protocol
+const FEND: u8 = 0xC0;
+const FESC: u8 = 0xDB;
+const TFEND: u8 = 0xDC;
+const TFESC: u8 = 0xDD;
+
+// KISS command of data frames
+const DATA_FRAME: u8 = 0x00;
+
+// Control field of AX.25 UI frames, without the poll/final bit
+const UI_CONTROL: u8 = 0x03;
+
+// Poll/final bit of the AX.25 control field
+const POLL_FINAL: u8 = 0x10;
+
+// AX.25 protocol identifier for frames without layer 3 protocol
+const NO_LAYER3: u8 = 0xF0;
+
+// Length of an AX.25 address in bytes
+const ADDRESS_LEN: usize = 7;
+
+// Maximum number of digipeaters in an AX.25 frame
+const MAX_DIGIPEATERS: usize = 8;
+
+/// Frame received from a KISS TNC
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KissFrame {
+    /// Port of the TNC the frame was received on
+    pub port: u8,
+
+    /// Command of the frame, `0` for data frames
+    pub command: u8,
+
+    /// Content of the frame, an AX.25 frame for data frames
+    pub data: Vec<u8>,
+}
+
+/// Decoder splitting a byte stream of a KISS TNC into frames
+#[derive(Debug, Clone, Default)]
+pub struct KissDecoder {
+    /// Content of the frame received so far
+    buffer: Vec<u8>,
+
+    /// True if the last byte was an escape character
+    escaped: bool,
+}
+
+impl KissDecoder {
+    /// Create a new decoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode received bytes.
+    /// Frames may span multiple calls, incomplete frames are kept until the next call.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`: Received bytes
+    ///
+    /// # Result
+    ///
+    /// Returns the frames completed by the given bytes.
+    pub fn decode(&mut self, data: &[u8]) -> Vec<KissFrame> {
+        let mut frames = Vec::new();
+
+        for byte in data.iter().copied() {
+            match (self.escaped, byte) {
+                (_, FEND) => {
+                    self.escaped = false;
+                    if let Some((kind, data)) = self.buffer.split_first() {
+                        frames.push(KissFrame {
+                            port: kind >> 4,
+                            command: kind & 0x0F,
+                            data: data.to_vec(),
+                        });
+                    }
+                    self.buffer.clear();
+                }
+                (false, FESC) => self.escaped = true,
+                (true, TFEND) => {
+                    self.escaped = false;
+                    self.buffer.push(FEND);
+                }
+                (true, TFESC) => {
+                    self.escaped = false;
+                    self.buffer.push(FESC);
+                }
+                (_, byte) => {
+                    self.escaped = false;
+                    self.buffer.push(byte);
+                }
+            }
+        }
+
+        frames
+    }
+}
+
+/// Address of an AX.25 frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ax25Address {
+    /// Callsign without SSID
+    pub callsign: String,
+
+    /// Secondary station identifier
+    pub ssid: u8,
+}
+
+impl Ax25Address {
+    /// Decode an address from its seven bytes.
+    fn decode(data: &[u8]) -> Self {
+        let callsign = data[..ADDRESS_LEN - 1]
+            .iter()
+            .map(|byte| (byte >> 1) as char)
+            .collect::<String>()
+            .trim_end()
+            .to_string();
+
+        Self {
+            callsign,
+            ssid: (data[ADDRESS_LEN - 1] >> 1) & 0x0F,
+        }
+    }
+}
+
+impl fmt::Display for Ax25Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ssid == 0 {
+            write!(f, "{}", self.callsign)
+        } else {
+            write!(f, "{}-{}", self.callsign, self.ssid)
+        }
+    }
+}
+
+/// AX.25 frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ax25Frame {
+    /// Station the frame is addressed to, e.g. `DX` for broadcast spots
+    pub destination: Ax25Address,
+
+    /// Station which sent the frame
+    pub source: Ax25Address,
+
+    /// Digipeaters the frame is routed through
+    pub digipeaters: Vec<Ax25Address>,
+
+    /// Control field
+    pub control: u8,
+
+    /// Protocol identifier, only present in UI and I frames
+    pub pid: Option<u8>,
+
+    /// Information field
+    pub info: Vec<u8>,
+}
+
+impl Ax25Frame {
+    /// Parse an AX.25 frame without flags and checksum, as delivered by a KISS TNC.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`: Content of the frame
+    ///
+    /// # Result
+    ///
+    /// Returns the frame or `None` if the frame is malformed.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        // The lowest bit of the last byte of an address marks the last address
+        let mut addresses = Vec::new();
+        let mut pos = 0;
+        loop {
+            let address = data.get(pos..pos + ADDRESS_LEN)?;
+            addresses.push(Ax25Address::decode(address));
+            pos += ADDRESS_LEN;
+
+            if address[ADDRESS_LEN - 1] & 0x01 == 1 {
+                break;
+            }
+            if addresses.len() >= MAX_DIGIPEATERS + 2 {
+                return None;
+            }
+        }
+        if addresses.len() < 2 {
+            return None;
+        }
+
+        let control = *data.get(pos)?;
+        pos += 1;
+
+        // Only UI and I frames carry a protocol identifier
+        let pid = if control & !POLL_FINAL == UI_CONTROL || control & 0x01 == 0 {
+            let pid = *data.get(pos)?;
+            pos += 1;
+            Some(pid)
+        } else {
+            None
+        };
+
+        let mut addresses = addresses.into_iter();
+        Some(Self {
+            destination: addresses.next()?,
+            source: addresses.next()?,
+            digipeaters: addresses.collect(),
+            control,
+            pid,
+            info: data[pos..].to_vec(),
+        })
+    }
+
+    /// Check if the frame is an unnumbered information (UI) frame without layer 3 protocol,
+    /// as used to broadcast spots and bulletins.
+    pub fn is_ui(&self) -> bool {
+        self.control & !POLL_FINAL == UI_CONTROL && self.pid == Some(NO_LAYER3)
+    }
+}
+
+/// Transport reading the payload of AX.25 UI frames from a KISS TNC, e.g. connected via `SerialTransport`
+/// or reachable via TCP like a software TNC.
+///
+/// The payload of the UI frames received on any port is read as continuous stream of lines.
+/// Line breaks given as carriage return, as usual in packet radio, are converted into line feeds,
+/// and a line break is appended to payloads not ending with one. Other frames are ignored.
+///
+/// The transport is receive-only, written data is discarded. Use a `NoAuthenticator`,
+/// as there is no login when monitoring broadcast spots.
+#[derive(Debug)]
+pub struct KissTransport<T> {
+    /// Destinations of the accepted UI frames, e.g. `DX`. Frames to any destination are accepted if empty.
+    pub destinations: Vec<String>,
+
+    /// Stream to the TNC
+    inner: T,
+
+    /// Decoder of the received KISS frames
+    decoder: KissDecoder,
+
+    /// Payload of the received frames not read yet
+    buffer: Vec<u8>,
+
+    /// Number of bytes of the buffer already read
+    pos: usize,
+}
+
+impl<T> KissTransport<T> {
+    /// Create a new transport accepting UI frames to any destination.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`: Stream to the TNC
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `KissTransport`.
+    pub fn new(inner: T) -> Self {
+        Self {
+            destinations: Vec::new(),
+            inner,
+            decoder: KissDecoder::new(),
+            buffer: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Append the payload of a KISS frame to the buffer if it is an accepted UI frame.
+    fn push_frame(&mut self, frame: KissFrame) {
+        if frame.command != DATA_FRAME {
+            return;
+        }
+
+        let frame = match Ax25Frame::parse(&frame.data) {
+            Some(frame) if frame.is_ui() => frame,
+            _ => return,
+        };
+
+        if !self.destinations.is_empty()
+            && !self
+                .destinations
+                .iter()
+                .any(|dest| dest.eq_ignore_ascii_case(&frame.destination.to_string()))
+        {
+            return;
+        }
+
+        let mut last = None;
+        for byte in frame.info.iter().copied() {
+            match byte {
+                b'\r' => self.buffer.push(b'\n'),
+                b'\n' if last == Some(b'\r') => (),
+                byte => self.buffer.push(byte),
+            }
+            last = Some(byte);
+        }
+        if last.is_some() && !self.buffer.ends_with(b"\n") {
+            self.buffer.push(b'\n');
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for KissTransport<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+
+        while this.pos >= this.buffer.len() {
+            this.buffer.clear();
+            this.pos = 0;
+
+            let mut data = [0u8; 1024];
+            let mut received = ReadBuf::new(&mut data);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut received))?;
+            if received.filled().is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+
+            for frame in this.decoder.decode(received.filled()) {
+                this.push_frame(frame);
+            }
+        }
+
+        let len = buf.remaining().min(this.buffer.len() - this.pos);
+        buf.put_slice(&this.buffer[this.pos..this.pos + len]);
+        this.pos += len;
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for KissTransport<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Encode an AX.25 address, marking the last address of a frame.
+    fn address(callsign: &str, ssid: u8, last: bool) -> Vec<u8> {
+        let mut data: Vec<u8> = format!("{:<6}", callsign).bytes().map(|b| b << 1).collect();
+        data.push(0x60 | (ssid << 1) | u8::from(last));
+        data
+    }
+
+    /// Encode an AX.25 UI frame.
+    fn ui_frame(
+        destination: &str,
+        source: &str,
+        ssid: u8,
+        digipeaters: &[&str],
+        info: &[u8],
+    ) -> Vec<u8> {
+        let mut data = address(destination, 0, false);
+        data.extend(address(source, ssid, digipeaters.is_empty()));
+        for (i, digi) in digipeaters.iter().enumerate() {
+            data.extend(address(digi, 0, i == digipeaters.len() - 1));
+        }
+        data.extend([UI_CONTROL, NO_LAYER3]);
+        data.extend(info);
+        data
+    }
+
+    /// Wrap data into a KISS data frame of port 0.
+    fn kiss(data: &[u8]) -> Vec<u8> {
+        let mut frame = vec![FEND, DATA_FRAME];
+        for byte in data.iter().copied() {
+            match byte {
+                FEND => frame.extend([FESC, TFEND]),
+                FESC => frame.extend([FESC, TFESC]),
+                byte => frame.push(byte),
+            }
+        }
+        frame.push(FEND);
+        frame
+    }
+
+    #[test]
+    fn decode_escaped() {
+        let mut decoder = KissDecoder::new();
+
+        assert_eq!(
+            decoder.decode(&[FEND, 0x10, 0x01, FESC, TFEND, FESC, TFESC, 0x02, FEND]),
+            vec![KissFrame {
+                port: 1,
+                command: 0,
+                data: vec![0x01, FEND, FESC, 0x02],
+            }]
+        );
+    }
+
+    #[test]
+    fn decode_split() {
+        let mut decoder = KissDecoder::new();
+
+        assert!(decoder.decode(&[FEND, FEND, 0x00, 0x41, FESC]).is_empty());
+        assert_eq!(
+            decoder.decode(&[TFEND, 0x42, FEND]),
+            vec![KissFrame {
+                port: 0,
+                command: 0,
+                data: vec![0x41, FEND, 0x42],
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_ui_frame() {
+        let frame = Ax25Frame::parse(&ui_frame("DX", "DB0ABC", 7, &["WIDE1"], b"text")).unwrap();
+
+        assert_eq!(frame.destination.to_string(), "DX");
+        assert_eq!(frame.source.to_string(), "DB0ABC-7");
+        assert_eq!(
+            frame.digipeaters,
+            vec![Ax25Address {
+                callsign: "WIDE1".into(),
+                ssid: 0,
+            }]
+        );
+        assert!(frame.is_ui());
+        assert_eq!(frame.info, b"text");
+    }
+
+    #[test]
+    fn parse_malformed() {
+        // Single address
+        assert_eq!(Ax25Frame::parse(&address("DX", 0, true)), None);
+
+        // Missing control field
+        let mut data = address("DX", 0, false);
+        data.extend(address("DB0ABC", 0, true));
+        assert_eq!(Ax25Frame::parse(&data), None);
+
+        // Supervisory frame without protocol identifier
+        data.push(0x01);
+        let frame = Ax25Frame::parse(&data).unwrap();
+        assert_eq!(frame.pid, None);
+        assert!(!frame.is_ui());
+    }
+
+    #[tokio::test]
+    async fn transport_lines() {
+        let (tnc, mut server) = tokio::io::duplex(1024);
+        let mut transport = KissTransport::new(tnc);
+        transport.destinations = vec!["DX".into()];
+
+        let mut data = kiss(&ui_frame(
+            "DX",
+            "DB0ABC",
+            0,
+            &[],
+            b"DX de W3LPL: 14025.0 DL1ABC\r",
+        ));
+        data.extend(kiss(&ui_frame("BEACON", "DB0ABC", 0, &[], b"ignored")));
+        data.extend(kiss(&ui_frame("DX", "DB0ABC", 0, &[], b"line 1\r\nline 2")));
+        server.write_all(&data).await.unwrap();
+        drop(server);
+
+        let mut received = String::new();
+        transport.read_to_string(&mut received).await.unwrap();
+        assert_eq!(received, "DX de W3LPL: 14025.0 DL1ABC\nline 1\nline 2\n");
+    }
+}
@@ -2,5 +2,143 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+pub mod adif;
+pub mod alerts;
+#[cfg(feature = "amqp")]
+pub mod amqp;
+pub mod auth;
+pub mod band;
+pub mod busted;
+pub mod command;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod connection;
+pub mod csv;
+pub mod dedup;
+#[cfg(feature = "dxcc")]
+pub mod dxcc;
+pub mod endpoint;
+pub mod enrich;
+pub mod event;
+pub mod expr;
+pub mod file;
+pub mod filter;
+pub mod flavor;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "influx")]
+pub mod influx;
+#[cfg(all(unix, feature = "journald"))]
+pub mod journald;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+pub mod kiss;
+pub mod line;
 pub mod listener;
+pub mod message;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+#[cfg(feature = "parser")]
+pub mod parser;
+pub mod pool;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod ratelimit;
+pub mod record;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod relay;
+pub mod sample;
+#[cfg(feature = "serial")]
+pub mod serial;
+pub mod server_filter;
+pub mod sink;
+pub mod socks;
+#[cfg(feature = "spool")]
+pub mod spool;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "ssh")]
+pub mod ssh;
+#[cfg(any(feature = "tls", feature = "native-tls"))]
+pub mod tls;
+pub mod topic;
+pub mod udp;
+pub mod watchlist;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+#[cfg(feature = "websocket-client")]
+pub mod websocket_client;
+pub use adif::*;
+pub use alerts::*;
+#[cfg(feature = "amqp")]
+pub use amqp::*;
+pub use auth::*;
+pub use band::*;
+pub use busted::*;
+pub use command::*;
+#[cfg(feature = "config")]
+pub use config::*;
+pub use connection::*;
+pub use csv::*;
+pub use dedup::*;
+#[cfg(feature = "dxcc")]
+pub use dxcc::*;
+pub use endpoint::*;
+pub use enrich::*;
+pub use event::*;
+pub use expr::*;
+pub use file::*;
+pub use filter::*;
+pub use flavor::*;
+#[cfg(feature = "grpc")]
+pub use grpc::*;
+#[cfg(feature = "influx")]
+pub use influx::*;
+#[cfg(all(unix, feature = "journald"))]
+pub use journald::*;
+#[cfg(feature = "kafka")]
+pub use kafka::*;
+pub use kiss::*;
+pub use line::*;
 pub use listener::*;
+pub use message::*;
+#[cfg(feature = "mqtt")]
+pub use mqtt::*;
+#[cfg(feature = "parser")]
+pub use parser::*;
+pub use pool::*;
+#[cfg(feature = "postgres")]
+pub use postgres::*;
+pub use ratelimit::*;
+pub use record::*;
+#[cfg(feature = "redis")]
+pub use redis::*;
+pub use relay::*;
+pub use sample::*;
+#[cfg(feature = "serial")]
+pub use serial::*;
+pub use server_filter::*;
+pub use sink::*;
+pub use socks::*;
+#[cfg(feature = "spool")]
+pub use spool::*;
+#[cfg(feature = "sqlite")]
+pub use sqlite::*;
+#[cfg(feature = "ssh")]
+pub use ssh::*;
+#[cfg(any(feature = "tls", feature = "native-tls"))]
+pub use tls::*;
+pub use topic::*;
+pub use udp::*;
+pub use watchlist::*;
+#[cfg(feature = "webhook")]
+pub use webhook::*;
+#[cfg(feature = "websocket")]
+pub use websocket::*;
+#[cfg(feature = "websocket-client")]
+pub use websocket_client::*;
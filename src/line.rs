@@ -0,0 +1,216 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::band::{Band, Mode};
+use crate::enrich::Entity;
+use crate::message::ClusterMessage;
+
+// Prefix of caret delimited CC11 spot records.
+const CC11_PREFIX: &str = "CC11^";
+
+// ANSI escape sequences, e.g. color codes like `ESC[1;31m`.
+const ANSI_PATTERN: &str = r"\x1b(\[[0-9;?]*[ -/]*[@-~]|[@-Z\\-_])";
+
+/// Handling of control characters and ANSI escape sequences in received lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Sanitization {
+    /// Keep the received line as it is
+    #[default]
+    Keep,
+
+    /// Remove ANSI escape sequences only
+    StripAnsi,
+
+    /// Remove ANSI escape sequences and all other control characters except tabs
+    StripAll,
+}
+
+impl Sanitization {
+    /// Apply the sanitization to a received line.
+    pub fn apply<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        static ANSI: OnceLock<Regex> = OnceLock::new();
+
+        if *self == Sanitization::Keep || !line.contains(|c: char| c.is_control()) {
+            return Cow::Borrowed(line);
+        }
+
+        let line = ANSI
+            .get_or_init(|| Regex::new(ANSI_PATTERN).unwrap())
+            .replace_all(line, "");
+
+        match self {
+            Sanitization::StripAll => Cow::Owned(
+                line.chars()
+                    .filter(|c| !c.is_control() || *c == '\t')
+                    .collect(),
+            ),
+            _ => line,
+        }
+    }
+}
+
+/// Format of a received line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineFormat {
+    /// Classic human readable format
+    Classic,
+
+    /// Caret delimited CC11 record as sent by nodes in VE7CC mode
+    Cc11,
+}
+
+impl LineFormat {
+    /// Detect the format of a received line.
+    pub fn detect(line: &str) -> Self {
+        if line.trim_start().starts_with(CC11_PREFIX) {
+            LineFormat::Cc11
+        } else {
+            LineFormat::Classic
+        }
+    }
+}
+
+/// Listener a line was received by
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Source {
+    /// Host of the cluster server
+    pub host: String,
+
+    /// Port of the cluster server
+    pub port: u16,
+
+    /// Label of the listener, if any
+    pub label: Option<String>,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "{}", label),
+            None => write!(f, "{}:{}", self.host, self.port),
+        }
+    }
+}
+
+/// Line received from a cluster server
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReceivedLine {
+    /// Received line without trailing whitespace and bell characters
+    pub raw: String,
+
+    /// True if the line is a historical spot requested right after the login
+    pub backlog: bool,
+
+    /// Format of the line
+    pub format: LineFormat,
+
+    /// Type and content of the line, `None` if parsing is disabled
+    pub parsed: Option<ClusterMessage>,
+
+    /// Point in time the line was received
+    pub received: DateTime<Utc>,
+
+    /// Point in time of the spot or message as sent by the server, if known
+    pub timestamp: Option<DateTime<Utc>>,
+
+    /// Band of the spot, if known
+    pub band: Option<Band>,
+
+    /// Mode of the spot as named by the spotter or inferred from the band plan, if known
+    pub mode: Option<Mode>,
+
+    /// Maidenhead locator of the spotter, if known
+    pub spotter_grid: Option<String>,
+
+    /// Maidenhead locator of the spotted station, if known
+    pub dx_grid: Option<String>,
+
+    /// DXCC entity of the spotter, if added by an enricher
+    pub spotter_entity: Option<Entity>,
+
+    /// DXCC entity of the spotted station, if added by an enricher
+    pub dx_entity: Option<Entity>,
+
+    /// True if the callsign of the spotted station is likely busted, if checked by an enricher
+    pub busted: bool,
+
+    /// True if the spotted station is on a watchlist, if checked by an enricher
+    pub priority: bool,
+
+    /// Listener the line was received by
+    pub source: Source,
+}
+
+impl ReceivedLine {
+    /// Create new instance of a live `ReceivedLine` and parse it.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw`: Received line
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `ReceivedLine`.
+    pub fn new(raw: String) -> Self {
+        let parsed = ClusterMessage::parse(&raw);
+        let received = Utc::now();
+        let (spotter_grid, dx_grid) = parsed.grids();
+
+        Self {
+            format: LineFormat::detect(&raw),
+            timestamp: parsed.timestamp(received),
+            band: parsed.band(),
+            mode: parsed.mode(),
+            spotter_grid,
+            dx_grid,
+            spotter_entity: None,
+            dx_entity: None,
+            busted: false,
+            priority: false,
+            parsed: Some(parsed),
+            raw,
+            backlog: false,
+            received,
+            source: Source::default(),
+        }
+    }
+
+    /// Create new instance of a live `ReceivedLine` without parsing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw`: Received line
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `ReceivedLine`.
+    pub fn unparsed(raw: String) -> Self {
+        Self {
+            format: LineFormat::detect(&raw),
+            parsed: None,
+            raw,
+            backlog: false,
+            received: Utc::now(),
+            timestamp: None,
+            band: None,
+            mode: None,
+            spotter_grid: None,
+            dx_grid: None,
+            spotter_entity: None,
+            dx_entity: None,
+            busted: false,
+            priority: false,
+            source: Source::default(),
+        }
+    }
+}
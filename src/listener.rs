@@ -2,24 +2,81 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+#[cfg(feature = "bytes")]
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use regex::Regex;
 use socket2::{SockRef, TcpKeepalive};
+use std::collections::VecDeque;
 use std::fmt;
-use std::str;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
 use thiserror::Error;
-use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::tcp::{ReadHalf, WriteHalf};
+use tokio::io::DuplexStream;
 use tokio::net::TcpStream;
 use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::sync::oneshot;
 use tokio::task::JoinHandle;
 use tokio::time;
 
-// Authentication tokens sent by cluster servers.
-const AUTH_TOKEN: [&str; 2] = ["login:", "Please enter your call:"];
+use crate::alerts::Alerts;
+use crate::auth::{
+    self, AuthPrompt, Authenticator, PromptAuthenticator, DEFAULT_AUTH_RETRIES,
+    DEFAULT_AUTH_TIMEOUT,
+};
+use crate::command;
+use crate::connection::{BoxedTransport, Connection, Decoding, LineTerminator, Transport};
+use crate::endpoint::{Endpoint, EndpointError};
+use crate::enrich::Enricher;
+use crate::event::{Event, EventKind};
+use crate::filter::{Filter, FilterChain};
+use crate::flavor::ClusterFlavor;
+use crate::line::{LineFormat, ReceivedLine, Sanitization, Source};
+use crate::message::ClusterMessage;
+#[cfg(feature = "parser")]
+use crate::parser::ParsedLine;
+use crate::server_filter::{server_filter_commands, ServerFilter};
+use crate::socks::{is_onion, Socks5Proxy};
+#[cfg(feature = "ssh")]
+use crate::ssh::SshTunnel;
+#[cfg(any(feature = "tls", feature = "native-tls"))]
+use crate::tls::{self, TlsConfig};
+#[cfg(feature = "websocket-client")]
+use crate::websocket_client::WebSocketTransport;
+
+// Beginning of lines carrying spots or messages.
+const MESSAGE_PATTERN: &str = r"^(DX de|WWV de|WCY de|To \S+ de) ";
+
+// Historical spot as listed by the `sh/dx` command.
+const BACKLOG_PATTERN: &str = r"^\d+\.\d+\s+\S+\s+\d{1,2}-\w{3}-\d{4}\s+\d{4}Z";
+
+// Command to switch the node into VE7CC mode sending CC11 records.
+const CC11_COMMAND: &str = "set/ve7cc";
+
+// Command to switch the node back into classic mode.
+const CLASSIC_COMMAND: &str = "unset/ve7cc";
+
+/// Default time to wait for further response lines of a command
+pub const DEFAULT_COMMAND_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+// Time after which a partially received line is checked for an unterminated command prompt.
+const PROMPT_IDLE: time::Duration = time::Duration::from_millis(200);
+
+// Messages which may be wrapped across multiple lines by some nodes.
+const WRAPPABLE_PATTERN: &str = r"^(To \S+ de|WWV de|WCY de|WX de) ";
+
+// Time to wait for the continuation of a possibly wrapped message.
+const WRAP_TIMEOUT: time::Duration = time::Duration::from_millis(200);
+
+// Maximum time to wait for the historical spots requested after the login.
+const BACKLOG_TIMEOUT: time::Duration = time::Duration::from_secs(5);
+
+// Period of the spot rate reported in the statistics.
+const RATE_PERIOD: time::Duration = time::Duration::from_secs(60);
 
 /// Possible errors while listening
-#[derive(Error, Debug, PartialEq, Eq)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ListenError {
     #[error("unknown error")]
     UnknownError,
@@ -39,6 +96,15 @@ pub enum ListenError {
     #[error("failed to authenticate at server")]
     AuthenticationError,
 
+    #[error("timeout while waiting for authentication prompt")]
+    AuthenticationTimeout,
+
+    #[error("login rejected by server: {0}")]
+    LoginRejected(String),
+
+    #[error("callsign already connected to server")]
+    CallsignInUse,
+
     #[error("internal error")]
     InternalError,
 
@@ -50,8 +116,106 @@ pub enum ListenError {
 
     #[error("shutdown was already requested")]
     ShutdownAlreadyRequested,
+
+    #[error("listener is not listening")]
+    NotListening,
+
+    #[error("invalid spot: {0}")]
+    InvalidSpot(String),
+
+    #[error("invalid message: {0}")]
+    InvalidMessage(String),
+
+    #[error("invalid server filter: {0}")]
+    InvalidFilter(String),
+
+    #[error("TLS error: {0}")]
+    TlsError(String),
+
+    #[error("WebSocket error: {0}")]
+    WebSocketError(String),
+
+    #[error("proxy error: {0}")]
+    ProxyError(String),
+
+    #[error("SSH error: {0}")]
+    SshError(String),
+
+    #[error("listener aborted after it did not stop in time")]
+    Aborted,
+}
+
+/// Policy to reconnect after a lost connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Delay before reconnecting
+    pub delay: std::time::Duration,
+
+    /// Maximum number of consecutive failed attempts, unlimited if not set
+    pub max_attempts: Option<u32>,
+}
+
+/// Statistics of a listener, e.g. to report its health
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListenerStats {
+    /// Point in time the current session was established, `None` if not connected
+    pub connected_since: Option<DateTime<Utc>>,
+
+    /// Number of received live spots, before the filters
+    pub received: u64,
+
+    /// Number of delivered live spots
+    pub spots: u64,
+
+    /// Number of live spots delivered within the last minute
+    pub spots_per_minute: u64,
+
+    /// Number of lines of any type dropped by the filters
+    pub dropped_lines: u64,
+
+    /// Error which ended the last session or connection attempt, if any
+    pub last_error: Option<ListenError>,
+
+    /// Number of reconnects after a lost session or a failed connection attempt
+    pub reconnects: u64,
+}
+
+/// Step while setting up a new session
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SetupStep {
+    /// Login at the server
+    Authentication,
+
+    /// Command executed after the login
+    LoginCommand(String),
+
+    /// Setup of the server-side filters
+    ServerFilters,
+
+    /// Switch into VE7CC mode
+    Cc11,
+
+    /// Request of historical spots
+    Backlog,
+}
+
+impl fmt::Display for SetupStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SetupStep::Authentication => write!(f, "authentication"),
+            SetupStep::LoginCommand(cmd) => write!(f, "login command '{}'", cmd),
+            SetupStep::ServerFilters => write!(f, "server filters"),
+            SetupStep::Cc11 => write!(f, "VE7CC mode"),
+            SetupStep::Backlog => write!(f, "backlog"),
+        }
+    }
 }
 
+/// Callback reporting a failed setup step of a new session
+pub type SetupHook = Arc<dyn Fn(SetupStep, &ListenError) + Send + Sync>;
+
 pub struct Listener {
     /// Host of the cluster server
     pub host: String,
@@ -59,9 +223,161 @@ pub struct Listener {
     /// Port of the cluster server
     pub port: u16,
 
+    /// Connect using TLS, e.g. to clusters offering an encrypted port.
+    /// A plain TCP connection is used if not set.
+    #[cfg(any(feature = "tls", feature = "native-tls"))]
+    pub tls: Option<TlsConfig>,
+
+    /// Request path of a WebSocket endpoint, e.g. `/cluster` for web-facing cluster gateways.
+    /// If set, the cluster protocol is spoken over a WebSocket connection to `ws://host:port/path`,
+    /// or `wss://host:port/path` if TLS is used.
+    #[cfg(feature = "websocket-client")]
+    pub websocket_path: Option<String>,
+
+    /// Connect through a SOCKS5 proxy, e.g. Tor to reach onion services.
+    /// Set to the Tor daemon by `from_uri` for `.onion` hosts, which are not reachable without.
+    pub proxy: Option<Socks5Proxy>,
+
+    /// Reach the server through an SSH tunnel via a jump host.
+    /// The host of the server is resolved by the jump host.
+    #[cfg(feature = "ssh")]
+    pub ssh: Option<SshTunnel>,
+
     /// Callsign to use for authentication
     pub callsign: String,
 
+    /// Software of the cluster server
+    pub flavor: ClusterFlavor,
+
+    /// Terminator appended to lines sent to the server
+    pub line_terminator: LineTerminator,
+
+    /// Decoding of received lines, e.g. to accept comments with Latin-1 characters
+    pub decoding: Decoding,
+
+    /// Handling of control characters and ANSI escape sequences in received lines
+    pub sanitization: Sanitization,
+
+    /// Additional authentication prompts to look for besides the built-in ones.
+    /// Only used by the default authenticator.
+    pub auth_prompts: Vec<AuthPrompt>,
+
+    /// Ignore the case while matching the authentication prompts.
+    /// Only used by the default authenticator.
+    pub auth_case_insensitive: bool,
+
+    /// Timeout for a single read while waiting for the authentication prompt.
+    /// Only used by the default authenticator.
+    pub auth_timeout: std::time::Duration,
+
+    /// Number of reads until the authentication is aborted.
+    /// Only used by the default authenticator.
+    pub auth_retries: u32,
+
+    /// Delay between receiving the authentication prompt and sending the callsign.
+    /// Only used by the default authenticator.
+    pub callsign_delay: std::time::Duration,
+
+    /// Delay between two characters while sending the callsign, sent at once if not set.
+    /// Only used by the default authenticator.
+    pub typing_delay: Option<std::time::Duration>,
+
+    /// Name to answer with if the server asks new users for it after the login.
+    /// Only used by the default authenticator.
+    pub login_name: Option<String>,
+
+    /// QTH to answer with if the server asks new users for it after the login.
+    /// Only used by the default authenticator.
+    pub login_qth: Option<String>,
+
+    /// Password to answer with if the server asks for it after the callsign.
+    /// Only used by the default authenticator.
+    pub login_password: Option<String>,
+
+    /// Suppress all received lines until the first line carrying a spot or message arrives.
+    /// Used to hide the echoed callsign and the login chatter of the server.
+    pub suppress_login_chatter: bool,
+
+    /// Commands to send right after the login
+    pub login_commands: Vec<String>,
+
+    /// Server-side spot filters to set up after the login, replacing any existing filters of the user.
+    /// Existing filters are kept if empty.
+    pub server_filters: Vec<ServerFilter>,
+
+    /// Number of historical spots to request with `sh/dx` after the login.
+    /// The historical spots are delivered, oldest first and tagged as backlog, before any live spot.
+    /// Zero disables the request.
+    pub backlog: usize,
+
+    /// Interval to send the keepalive command in.
+    /// No keepalive command is sent if not set.
+    pub keepalive_interval: Option<std::time::Duration>,
+
+    /// Command to send to keep an idle connection alive, e.g. an empty line, `sh/time` or `ping`
+    pub keepalive_command: String,
+
+    /// Time to wait for further response lines of a command.
+    /// Used to detect the end of the response if the command prompt of the server is unknown.
+    pub command_timeout: std::time::Duration,
+
+    /// Switch the node into VE7CC mode after the login.
+    /// Spots are then sent as caret delimited CC11 records and tagged accordingly.
+    pub cc11: bool,
+
+    /// Label to identify the listener, e.g. when multiple listeners share a channel.
+    /// Used instead of host and port in the source of delivered lines and events, in sinks and in pool statistics.
+    /// Shown in front of callsign, host and port when the listener is displayed, e.g. in logs and the pool status.
+    pub label: Option<String>,
+
+    /// Parse the received lines into a `ClusterMessage`.
+    /// The raw line is delivered in any case.
+    pub parse: bool,
+
+    /// Reassemble announcements and WWV, WCY or WX messages wrapped across multiple lines.
+    /// Lines starting with whitespace are taken as continuation of such a message.
+    /// Delays the delivery of these messages slightly.
+    pub reassemble: bool,
+
+    /// Enrichers applied to every received line before it is delivered, in the given order
+    pub enrichers: Vec<Arc<dyn Enricher>>,
+
+    /// Filters applied to every received line after the enrichers, lines not accepted are dropped
+    pub filters: FilterChain,
+
+    /// Filter applied before the filters, e.g. by a `ListenerPool` in standby mode to mute backup listeners.
+    /// Lines not accepted are dropped without counting them in the statistics and are not passed to the filters.
+    pub gate: Option<Arc<dyn Filter>>,
+
+    /// Alert rules checked for every delivered line
+    pub alerts: Option<Alerts>,
+
+    /// Channel for lines of unknown type, e.g. lines of an unexpected format or failed to parse.
+    /// If set, these lines are sent to this channel instead of the channel passed to `listen`.
+    /// Only used if parsing is enabled.
+    pub unknown_lines: Option<mpsc::UnboundedSender<ReceivedLine>>,
+
+    /// Channel for lifecycle events, e.g. connected, authenticated or lost connection.
+    /// Set by `ListenerPool::events` for all listeners of a pool.
+    pub events: Option<mpsc::UnboundedSender<Event>>,
+
+    /// Highest SSID to try if the server reports that the callsign is already connected.
+    /// The SSID of the callsign is incremented, or `-1` appended, until the login succeeds.
+    /// The SSID is not changed if not set.
+    pub max_ssid: Option<u8>,
+
+    /// Reconnect after a lost connection.
+    /// The login and all setup steps are repeated for every new session.
+    /// The listener stops after a lost connection if not set.
+    pub reconnect: Option<ReconnectPolicy>,
+
+    /// Called if a setup step of a new session failed
+    pub on_setup_error: Option<SetupHook>,
+
+    /// Custom login procedure.
+    /// If not set, a `PromptAuthenticator` is used.
+    pub authenticator: Option<Arc<dyn Authenticator>>,
+
     /// True if the listener shall run, false if the listener shall stop its execution.
     /// May already be false if an error occurred while listening.
     run: Arc<AtomicBool>,
@@ -71,11 +387,33 @@ pub struct Listener {
 
     /// Shutdown signal
     shutdown: Option<UnboundedSender<()>>,
+
+    /// Requests to the running listener task
+    control: Option<UnboundedSender<Control>>,
+
+    /// Callsign used for the login of the current or last session
+    session_callsign: Arc<Mutex<Option<String>>>,
+
+    /// Last command prompt received from the server
+    prompt: Arc<Mutex<Option<String>>>,
+
+    /// Established stream used by the next call to `listen` instead of connecting to the server
+    transport: Option<BoxedTransport>,
+
+    /// Statistics of the listener, updated by the listener task
+    health: Arc<Mutex<Health>>,
 }
 
 impl fmt::Display for Listener {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}@{}:{}", self.callsign, self.host, self.port)
+        match &self.label {
+            Some(label) => write!(
+                f,
+                "{} ({}@{}:{})",
+                label, self.callsign, self.host, self.port
+            ),
+            None => write!(f, "{}@{}:{}", self.callsign, self.host, self.port),
+        }
     }
 }
 
@@ -96,6 +434,193 @@ impl Listener {
         }
     }
 
+    /// Join the listener to get the result, aborting the listener if it did not stop in time.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout`: Time to wait for the listener to stop, e.g. after `request_stop`
+    ///
+    /// # Result
+    ///
+    /// Returns the result of the listener, or `ListenError::Aborted` if it was aborted.
+    pub async fn join_timeout(&mut self, timeout: std::time::Duration) -> Result<(), ListenError> {
+        let mut h = self.handle.take().ok_or(ListenError::AlreadyJoined)?;
+        match time::timeout(timeout, &mut h).await {
+            Ok(res) => res.map_err(|_| ListenError::InternalError)?,
+            Err(_) => {
+                h.abort();
+                self.run.store(false, Ordering::Relaxed);
+                Err(ListenError::Aborted)
+            }
+        }
+    }
+
+    /// Switch the running session between the classic format and the VE7CC mode sending CC11 records.
+    /// The setting is also used for later sessions.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled`: True to switch into VE7CC mode, false to switch back to the classic format
+    ///
+    /// # Result
+    ///
+    /// Returns `Ok(())` if the request was passed to the running listener.
+    pub fn set_cc11(&mut self, enabled: bool) -> Result<(), ListenError> {
+        self.cc11 = enabled;
+        self.send_control(Control::Cc11(enabled))
+    }
+
+    /// Replace the server-side spot filters of the running session.
+    /// The filters are also used for later sessions.
+    ///
+    /// # Arguments
+    ///
+    /// * `filters`: Filters to set up, may be empty to clear all filters
+    ///
+    /// # Result
+    ///
+    /// Returns `Ok(())` if the request was passed to the running listener.
+    /// Returns `ListenError::InvalidFilter` if a filter is not supported by the server software.
+    pub fn set_server_filters(&mut self, filters: Vec<ServerFilter>) -> Result<(), ListenError> {
+        let cmds = server_filter_commands(&filters, self.flavor)?;
+        self.server_filters = filters;
+        self.send_control(Control::ServerFilters(cmds))
+    }
+
+    /// Replace the filters of the running listener.
+    /// The filters apply to the next received line and are also used for later sessions.
+    ///
+    /// # Arguments
+    ///
+    /// * `filters`: Filters to apply, may be empty to accept all lines
+    ///
+    /// # Result
+    ///
+    /// Returns `Ok(())` if the request was passed to the running listener.
+    pub fn set_filters(&mut self, filters: FilterChain) -> Result<(), ListenError> {
+        self.filters = filters.clone();
+        self.send_control(Control::Filters(filters))
+    }
+
+    /// Send a raw line to the server, e.g. to adjust filters of the node.
+    /// Any response is delivered through the channel like any other received line.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`: Line to send
+    ///
+    /// # Result
+    ///
+    /// Returns `Ok(())` if the request was passed to the running listener.
+    pub fn send(&self, data: &str) -> Result<(), ListenError> {
+        self.send_control(Control::Send(data.into()))
+    }
+
+    /// Submit a DX spot to the cluster.
+    ///
+    /// # Arguments
+    ///
+    /// * `dx_call`: Callsign of the spotted station
+    /// * `freq_khz`: Frequency in kHz
+    /// * `comment`: Comment of the spot, may be empty
+    ///
+    /// # Result
+    ///
+    /// Returns `Ok(())` if the spot was passed to the running listener.
+    /// Returns `ListenError::InvalidSpot` if one of the arguments is not plausible.
+    pub fn spot(&self, dx_call: &str, freq_khz: f64, comment: &str) -> Result<(), ListenError> {
+        self.send(&command::dx_command(dx_call, freq_khz, comment)?)
+    }
+
+    /// Send an announcement.
+    ///
+    /// # Arguments
+    ///
+    /// * `text`: Text of the announcement
+    /// * `full`: True to distribute the announcement to the whole cluster network, false for the local node only
+    ///
+    /// # Result
+    ///
+    /// Returns `Ok(())` if the announcement was passed to the running listener.
+    pub fn announce(&self, text: &str, full: bool) -> Result<(), ListenError> {
+        self.send(&command::announce_command(text, full)?)
+    }
+
+    /// Send a talk message to another station.
+    ///
+    /// # Arguments
+    ///
+    /// * `to_call`: Callsign of the receiving station
+    /// * `text`: Text of the message
+    ///
+    /// # Result
+    ///
+    /// Returns `Ok(())` if the message was passed to the running listener.
+    pub fn talk(&self, to_call: &str, text: &str) -> Result<(), ListenError> {
+        self.send(&command::talk_command(to_call, text)?)
+    }
+
+    /// Send a command to the server and collect its response.
+    /// Spots and messages received in the meantime are still delivered through the channel.
+    /// The response is complete as soon as the command prompt of the server is received
+    /// or no further line arrived within the command timeout.
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd`: Command to send, e.g. `sh/wwv`
+    ///
+    /// # Result
+    ///
+    /// Returns the lines of the response.
+    pub async fn command(&self, cmd: &str) -> Result<Vec<String>, ListenError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.send_control(Control::Command(cmd.into(), reply_tx))?;
+        reply_rx.await.map_err(|_| ListenError::NotListening)?
+    }
+
+    /// Pass a request to the running listener task
+    fn send_control(&self, ctrl: Control) -> Result<(), ListenError> {
+        match &self.control {
+            Some(control) if self.is_running() => {
+                control.send(ctrl).map_err(|_| ListenError::NotListening)
+            }
+            _ => Err(ListenError::NotListening),
+        }
+    }
+
+    /// Get the callsign used for the login of the current or last session.
+    /// Differs from the configured callsign if another SSID was chosen automatically.
+    pub fn session_callsign(&self) -> Option<String> {
+        self.session_callsign.lock().unwrap().clone()
+    }
+
+    /// Get the last command prompt received from the server, e.g. `N0CALL de NODE 16-Oct-2026 1200Z >`.
+    /// Prompts are not delivered as received lines.
+    pub fn prompt(&self) -> Option<String> {
+        self.prompt.lock().unwrap().clone()
+    }
+
+    /// Get the statistics of the listener, kept across sessions.
+    pub fn stats(&self) -> ListenerStats {
+        self.health.lock().unwrap().stats()
+    }
+
+    /// Get the identification of the listener, as attached to its lines and events.
+    pub fn source(&self) -> Source {
+        Source {
+            host: self.host.clone(),
+            port: self.port,
+            label: self.label.clone(),
+        }
+    }
+
+    /// Get a handle to the statistics of the listener, e.g. to check its connection from another task.
+    pub fn stats_handle(&self) -> StatsHandle {
+        StatsHandle {
+            health: self.health.clone(),
+        }
+    }
+
     /// Check if the listener is running
     pub fn is_running(&self) -> bool {
         self.run.load(Ordering::Relaxed)
@@ -116,11 +641,154 @@ impl Listener {
         Self {
             host,
             port,
+            #[cfg(any(feature = "tls", feature = "native-tls"))]
+            tls: None,
+            #[cfg(feature = "websocket-client")]
+            websocket_path: None,
+            proxy: None,
+            #[cfg(feature = "ssh")]
+            ssh: None,
             callsign,
+            flavor: ClusterFlavor::Generic,
+            line_terminator: LineTerminator::default(),
+            decoding: Decoding::default(),
+            sanitization: Sanitization::default(),
+            auth_prompts: Vec::new(),
+            auth_case_insensitive: false,
+            auth_timeout: DEFAULT_AUTH_TIMEOUT,
+            auth_retries: DEFAULT_AUTH_RETRIES,
+            callsign_delay: std::time::Duration::ZERO,
+            typing_delay: None,
+            login_name: None,
+            login_qth: None,
+            login_password: None,
+            suppress_login_chatter: false,
+            login_commands: Vec::new(),
+            server_filters: Vec::new(),
+            backlog: 0,
+            keepalive_interval: None,
+            keepalive_command: String::new(),
+            command_timeout: DEFAULT_COMMAND_TIMEOUT,
+            cc11: false,
+            label: None,
+            parse: true,
+            unknown_lines: None,
+            events: None,
+            reassemble: false,
+            enrichers: Vec::new(),
+            filters: FilterChain::new(),
+            gate: None,
+            alerts: None,
+            max_ssid: None,
+            reconnect: None,
+            on_setup_error: None,
+            authenticator: None,
             run: Arc::new(AtomicBool::new(false)),
             handle: None,
             shutdown: None,
+            control: None,
+            session_callsign: Arc::new(Mutex::new(None)),
+            prompt: Arc::new(Mutex::new(None)),
+            transport: None,
+            health: Arc::new(Mutex::new(Health::default())),
+        }
+    }
+
+    /// Create new instance of `Listener` talking to an in-memory pipe instead of a server,
+    /// e.g. to test the handling of spots against a scripted server without opening sockets.
+    /// The listener behaves like after `listen_on`, i.e. it stops once the pipe is closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `callsign`: Callsign to use for authentication
+    /// * `max_buf_size`: Maximum number of bytes buffered in each direction of the pipe
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `Listener` and the end of the pipe acting as server.
+    pub fn duplex(callsign: String, max_buf_size: usize) -> (Self, DuplexStream) {
+        let (client, server) = tokio::io::duplex(max_buf_size);
+        let mut listener = Self::new("duplex".into(), 0, callsign);
+        listener.transport = Some(Box::new(client));
+        (listener, server)
+    }
+
+    /// Create new instance of `Listener` for a specific cluster server software.
+    /// The settings of the listener are initialized with the recommendations of the given flavor.
+    ///
+    /// # Arguments
+    ///
+    /// * `host`: Host of server
+    /// * `port`: Port of server
+    /// * `callsign`: Callsign to use for authentication
+    /// * `flavor`: Software of the server
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `Listener`.
+    pub fn with_flavor(host: String, port: u16, callsign: String, flavor: ClusterFlavor) -> Self {
+        let mut listener = Self::new(host, port, callsign);
+        listener.apply_flavor(flavor);
+        listener
+    }
+
+    /// Set the software of the server and initialize the settings of the listener with its recommendations,
+    /// e.g. for a listener created with `from_uri`.
+    ///
+    /// # Arguments
+    ///
+    /// * `flavor`: Software of the server
+    pub fn apply_flavor(&mut self, flavor: ClusterFlavor) {
+        self.flavor = flavor;
+        self.auth_prompts = flavor.auth_prompts();
+        self.suppress_login_chatter = flavor.suppress_login_chatter();
+        self.login_commands = flavor.login_commands();
+        self.keepalive_interval = flavor.keepalive();
+    }
+
+    /// Create new instance of `Listener` for a server given as URI,
+    /// e.g. `telnet://dxc.example:7300`, `tls://dxc.example` or `wss://gateway.example/cluster`.
+    /// The transport is selected by the scheme, the port defaults to the well-known port of the scheme, see `Endpoint`.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri`: Endpoint of the server
+    /// * `callsign`: Callsign to use for authentication
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `Listener`, or an error if the endpoint is invalid
+    /// or its scheme requires a feature which is not enabled.
+    pub fn from_uri(uri: &str, callsign: String) -> Result<Self, EndpointError> {
+        let endpoint = Endpoint::parse(uri)?;
+        let mut listener = Self::new(endpoint.host.clone(), endpoint.port, callsign);
+
+        if is_onion(&endpoint.host) {
+            listener.proxy = Some(Socks5Proxy::tor());
         }
+
+        if endpoint.scheme.is_secure() {
+            #[cfg(any(feature = "tls", feature = "native-tls"))]
+            {
+                listener.tls = Some(TlsConfig::new());
+            }
+            #[cfg(not(any(feature = "tls", feature = "native-tls")))]
+            return Err(EndpointError::UnsupportedScheme(endpoint.scheme, "tls"));
+        }
+
+        if endpoint.scheme.is_websocket() {
+            #[cfg(feature = "websocket-client")]
+            {
+                listener.websocket_path = Some(endpoint.path);
+            }
+            #[cfg(not(feature = "websocket-client"))]
+            return Err(EndpointError::UnsupportedScheme(
+                endpoint.scheme,
+                "websocket-client",
+            ));
+        }
+
+        Ok(listener)
     }
 
     /// Listen for data from dx cluster.
@@ -136,45 +804,793 @@ impl Listener {
     /// An `Err(ListenError)` shall be returned in case something went wrong while connecting.
     pub async fn listen(
         &mut self,
-        channel: mpsc::UnboundedSender<String>,
+        channel: mpsc::UnboundedSender<ReceivedLine>,
         connection_timeout: std::time::Duration,
     ) -> Result<(), ListenError> {
-        self.run.store(false, Ordering::Relaxed);
+        self.start(Output::Lines(channel), connection_timeout, None)
+            .await
+    }
 
-        let constring = format!("{}:{}", self.host, self.port);
-        let call = self.callsign.clone();
-        let flag = self.run.clone();
+    /// Listen for data from dx cluster on an already established stream,
+    /// e.g. a tunnel, a TLS wrapper or an in-memory pipe not known to this crate.
+    /// The host and port of the listener only identify the source of the received lines.
+    ///
+    /// As the stream can not be established again, the listener stops after the connection is lost
+    /// regardless of the reconnect policy, and the SSID is not changed if the callsign is already connected.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport`: Established stream to the server
+    /// * `channel`: Communication channel where to send received spots to
+    ///
+    /// # Result
+    ///
+    /// The result shall be `Ok(())` if the listener is waiting for spots.
+    /// An `Err(ListenError)` shall be returned in case something went wrong while starting.
+    pub async fn listen_on<T: Transport + 'static>(
+        &mut self,
+        transport: T,
+        channel: mpsc::UnboundedSender<ReceivedLine>,
+    ) -> Result<(), ListenError> {
+        self.start(
+            Output::Lines(channel),
+            std::time::Duration::ZERO,
+            Some(Box::new(transport)),
+        )
+        .await
+    }
 
-        let stream = time::timeout(connection_timeout, connect(constring))
+    /// Listen for data from dx cluster and forward the received lines as they are.
+    /// The lines are neither decoded nor processed, i.e. the backlog and
+    /// the responses of commands are not available.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel`: Communication channel where to send the received bytes of each line to
+    /// * `conn_timeout`: Connection timeout to server
+    ///
+    /// # Result
+    ///
+    /// The result shall be `Ok(())` if the listener is connected and is waiting for spots.
+    /// An `Err(ListenError)` shall be returned in case something went wrong while connecting.
+    pub async fn listen_raw(
+        &mut self,
+        channel: mpsc::UnboundedSender<Vec<u8>>,
+        connection_timeout: std::time::Duration,
+    ) -> Result<(), ListenError> {
+        self.start(Output::Raw(channel), connection_timeout, None)
             .await
-            .map_err(|_| ListenError::ConnectionTimeout)??;
+    }
 
-        // Create communication channel to later request the shutdown of the task
+    /// Listen for data from dx cluster and forward the received lines as `Bytes`.
+    /// Works like `listen_raw`, each frame contains the exact bytes of a line including the line break.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel`: Communication channel where to send the received frames to
+    /// * `conn_timeout`: Connection timeout to server
+    ///
+    /// # Result
+    ///
+    /// The result shall be `Ok(())` if the listener is connected and is waiting for spots.
+    /// An `Err(ListenError)` shall be returned in case something went wrong while connecting.
+    #[cfg(feature = "bytes")]
+    pub async fn listen_bytes(
+        &mut self,
+        channel: mpsc::UnboundedSender<Bytes>,
+        connection_timeout: std::time::Duration,
+    ) -> Result<(), ListenError> {
+        self.start(Output::Bytes(channel), connection_timeout, None)
+            .await
+    }
+
+    /// Listen for data from dx cluster and parse the received lines.
+    /// Works like `listen` but delivers each line together with its parsed representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel`: Communication channel where to send parsed lines to
+    /// * `conn_timeout`: Connection timeout to server
+    ///
+    /// # Result
+    ///
+    /// The result shall be `Ok(())` if the listener is connected and is waiting for spots.
+    /// An `Err(ListenError)` shall be returned in case something went wrong while connecting.
+    #[cfg(feature = "parser")]
+    pub async fn listen_parsed(
+        &mut self,
+        channel: mpsc::UnboundedSender<ParsedLine>,
+        connection_timeout: std::time::Duration,
+    ) -> Result<(), ListenError> {
+        self.start(Output::Parsed(channel), connection_timeout, None)
+            .await
+    }
+
+    /// Listen for data from dx cluster and convert the received lines into JSON.
+    /// Works like `listen` but delivers each line as a single line of JSON,
+    /// ready to be written to a file or socket in JSON lines format.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel`: Communication channel where to send the JSON lines to
+    /// * `conn_timeout`: Connection timeout to server
+    ///
+    /// # Result
+    ///
+    /// The result shall be `Ok(())` if the listener is connected and is waiting for spots.
+    /// An `Err(ListenError)` shall be returned in case something went wrong while connecting.
+    #[cfg(feature = "json")]
+    pub async fn listen_json(
+        &mut self,
+        channel: mpsc::UnboundedSender<String>,
+        connection_timeout: std::time::Duration,
+    ) -> Result<(), ListenError> {
+        self.start(Output::Json(channel), connection_timeout, None)
+            .await
+    }
+
+    /// Connect to the server, unless a stream is given, and start the listener task.
+    async fn start(
+        &mut self,
+        output: Output,
+        connection_timeout: std::time::Duration,
+        transport: Option<BoxedTransport>,
+    ) -> Result<(), ListenError> {
+        self.run.store(false, Ordering::Relaxed);
+
+        let transport = transport.or_else(|| self.transport.take());
+
+        let constring = format!("{}:{}", self.host, self.port);
+        let authenticator = self.authenticator.clone().unwrap_or_else(|| {
+            Arc::new(PromptAuthenticator {
+                prompts: self.auth_prompts.clone(),
+                timeout: self.auth_timeout,
+                retries: self.auth_retries,
+                case_insensitive: self.auth_case_insensitive,
+                callsign_delay: self.callsign_delay,
+                typing_delay: self.typing_delay,
+                name: self.login_name.clone(),
+                qth: self.login_qth.clone(),
+                password: self.login_password.clone(),
+            })
+        });
+        let server_filters = if self.server_filters.is_empty() {
+            Vec::new()
+        } else {
+            server_filter_commands(&self.server_filters, self.flavor)?
+        };
+        let settings = Settings {
+            address: constring,
+            dial: transport.is_none(),
+            #[cfg(any(feature = "tls", feature = "native-tls"))]
+            tls: self.tls.clone(),
+            #[cfg(feature = "websocket-client")]
+            websocket_path: self.websocket_path.clone(),
+            proxy: self.proxy.clone(),
+            #[cfg(feature = "ssh")]
+            ssh: self.ssh.clone(),
+            connection_timeout,
+            callsign: self.callsign.clone(),
+            max_ssid: self.max_ssid,
+            reconnect: self.reconnect,
+            on_setup_error: self.on_setup_error.clone(),
+            session_callsign: self.session_callsign.clone(),
+            health: self.health.clone(),
+            authenticator,
+            line_terminator: self.line_terminator,
+            decoding: self.decoding,
+            sanitization: self.sanitization,
+            suppress_login_chatter: self.suppress_login_chatter,
+            login_commands: self.login_commands.clone(),
+            server_filters,
+            prompt: self
+                .flavor
+                .prompt_pattern()
+                .map(|pattern| Regex::new(pattern).unwrap()),
+            session_prompt: None,
+            last_prompt: self.prompt.clone(),
+            noise: self
+                .flavor
+                .noise_pattern()
+                .map(|pattern| Regex::new(pattern).unwrap()),
+            keepalive: self.keepalive_interval,
+            keepalive_command: self.keepalive_command.clone(),
+            backlog: self.backlog,
+            cc11: self.cc11,
+            parse: self.parse,
+            unknown: self.unknown_lines.clone(),
+            events: self.events.clone(),
+            reassemble: self.reassemble,
+            enrichers: self.enrichers.clone(),
+            filters: self.filters.clone(),
+            gate: self.gate.clone(),
+            alerts: self.alerts.clone(),
+            source: self.source(),
+            command_timeout: self.command_timeout,
+        };
+        let flag = self.run.clone();
+        let events = settings.events.clone();
+        let source = settings.source.clone();
+
+        let stream = match transport {
+            Some(transport) => transport,
+            None => reconnect(&settings).await?,
+        };
+
+        // Create communication channel to later request the shutdown of the task
         let (shutdown_tx, shutdown_rx) = mpsc::unbounded_channel();
 
+        // Create communication channel to pass requests to the task
+        let (control_tx, control_rx) = mpsc::unbounded_channel();
+
         // Set listener-running flag to true
         flag.store(true, Ordering::Relaxed);
 
         // Start listener main task
         let tsk: JoinHandle<Result<(), ListenError>> = tokio::spawn(async move {
             // Authenticate at server and start listening for spots
-            let res = run(stream, channel, shutdown_rx, &call).await;
+            let res = run(stream, output, shutdown_rx, control_rx, settings).await;
 
             // Set listener-running flag to false
             flag.store(false, Ordering::Relaxed);
+            emit(
+                &events,
+                &source,
+                match &res {
+                    Ok(()) => EventKind::Stopped,
+                    Err(err) => EventKind::GaveUp(err.clone()),
+                },
+            );
             res
         });
 
         self.shutdown = Some(shutdown_tx);
+        self.control = Some(control_tx);
         self.handle = Some(tsk);
 
         Ok(())
     }
 }
 
+/// Channel to reply with the response of a command
+type Reply = oneshot::Sender<Result<Vec<String>, ListenError>>;
+
+/// Requests to a running listener task
+enum Control {
+    /// Switch between classic format and VE7CC mode
+    Cc11(bool),
+
+    /// Send a raw line
+    Send(String),
+
+    /// Replace the server-side filters
+    ServerFilters(Vec<String>),
+
+    /// Replace the filters applied to received lines
+    Filters(FilterChain),
+
+    /// Send a command and reply with its response
+    Command(String, Reply),
+}
+
+/// Settings of a running listener task
+struct Settings {
+    /// Address of the server
+    address: String,
+
+    /// Connect to the address of the server for new sessions, false if the stream was given by the user
+    dial: bool,
+
+    /// Settings of the TLS connection, if any
+    #[cfg(any(feature = "tls", feature = "native-tls"))]
+    tls: Option<TlsConfig>,
+
+    /// Request path of the WebSocket endpoint, if any
+    #[cfg(feature = "websocket-client")]
+    websocket_path: Option<String>,
+
+    /// SOCKS5 proxy to connect through, if any
+    proxy: Option<Socks5Proxy>,
+
+    /// SSH tunnel to reach the server through, if any
+    #[cfg(feature = "ssh")]
+    ssh: Option<SshTunnel>,
+
+    /// Connection timeout to server
+    connection_timeout: time::Duration,
+
+    /// Callsign to use for authentication
+    callsign: String,
+
+    /// Highest SSID to try if the callsign is already connected
+    max_ssid: Option<u8>,
+
+    /// Reconnect after a lost connection, if set
+    reconnect: Option<ReconnectPolicy>,
+
+    /// Called if a setup step of a new session failed
+    on_setup_error: Option<SetupHook>,
+
+    /// Callsign used for the login of the session
+    session_callsign: Arc<Mutex<Option<String>>>,
+
+    /// Statistics of the listener
+    health: Arc<Mutex<Health>>,
+
+    /// Login procedure
+    authenticator: Arc<dyn Authenticator>,
+
+    /// Terminator appended to sent lines
+    line_terminator: LineTerminator,
+
+    /// Decoding of received lines
+    decoding: Decoding,
+
+    /// Handling of control characters in received lines
+    sanitization: Sanitization,
+
+    /// Suppress received lines until the first spot or message arrives
+    suppress_login_chatter: bool,
+
+    /// Commands to send after the login
+    login_commands: Vec<String>,
+
+    /// Commands to set up the server-side filters
+    server_filters: Vec<String>,
+
+    /// Command prompt of the server, if known
+    prompt: Option<Regex>,
+
+    /// Command prompt addressing the callsign of the session, known after the login
+    session_prompt: Option<Regex>,
+
+    /// Last received command prompt
+    last_prompt: Arc<Mutex<Option<String>>>,
+
+    /// Lines not to deliver, if any
+    noise: Option<Regex>,
+
+    /// Interval to send the keepalive command in, if any
+    keepalive: Option<time::Duration>,
+
+    /// Command to keep an idle connection alive
+    keepalive_command: String,
+
+    /// Number of historical spots to request after the login
+    backlog: usize,
+
+    /// Switch the node into VE7CC mode after the login
+    cc11: bool,
+
+    /// Parse the received lines
+    parse: bool,
+
+    /// Channel for lines of unknown type, if any
+    unknown: Option<mpsc::UnboundedSender<ReceivedLine>>,
+
+    /// Channel for lifecycle events, if any
+    events: Option<mpsc::UnboundedSender<Event>>,
+
+    /// Reassemble wrapped messages
+    reassemble: bool,
+
+    /// Enrichers applied to every received line
+    enrichers: Vec<Arc<dyn Enricher>>,
+
+    /// Filters applied to every received line
+    filters: FilterChain,
+
+    /// Filter applied before the filters, without counting the dropped lines
+    gate: Option<Arc<dyn Filter>>,
+
+    /// Alert rules checked for every delivered line
+    alerts: Option<Alerts>,
+
+    /// Identification of the listener
+    source: Source,
+
+    /// Time to wait for further response lines of a command
+    command_timeout: time::Duration,
+}
+
+impl Settings {
+    /// Send a lifecycle event, if requested.
+    fn emit(&self, kind: EventKind) {
+        emit(&self.events, &self.source, kind);
+    }
+
+    /// Deliver a received line if accepted by the filters.
+    /// Lines of unknown type are sent to the channel for unknown lines, if set.
+    fn deliver(&self, pipe: &Output, line: ReceivedLine) -> Result<(), ListenError> {
+        if self.gate.as_ref().is_some_and(|gate| !gate.accept(&line)) {
+            return Ok(());
+        }
+
+        let live_spot = !line.backlog && line.parsed.as_ref().is_some_and(ClusterMessage::is_spot);
+
+        if !self.filters.accept(&line) {
+            self.health.lock().unwrap().drop_line(live_spot);
+            return Ok(());
+        }
+
+        if let Some(alerts) = &self.alerts {
+            alerts.check(&line);
+        }
+
+        if live_spot {
+            self.health.lock().unwrap().spot();
+        }
+
+        match (&self.unknown, &line.parsed) {
+            (Some(unknown), Some(ClusterMessage::Unknown(_))) => {
+                unknown.send(line).map_err(|_| ListenError::ReceiverLost)
+            }
+            _ => pipe.send(line),
+        }
+    }
+
+    /// Create a received line originating from this listener
+    fn receive(&self, raw: String) -> ReceivedLine {
+        let line = if self.parse {
+            ReceivedLine::new(raw)
+        } else {
+            ReceivedLine::unparsed(raw)
+        };
+
+        let mut line = ReceivedLine {
+            source: self.source.clone(),
+            ..line
+        };
+
+        for enricher in self.enrichers.iter() {
+            enricher.enrich(&mut line);
+        }
+
+        line
+    }
+}
+
+/// Receiver of the lines of a listener
+enum Output {
+    /// Processed lines, see `Listener::listen`
+    Lines(mpsc::UnboundedSender<ReceivedLine>),
+
+    /// Received bytes of each line, see `Listener::listen_raw`
+    Raw(mpsc::UnboundedSender<Vec<u8>>),
+
+    /// Received bytes of each line, see `Listener::listen_bytes`
+    #[cfg(feature = "bytes")]
+    Bytes(mpsc::UnboundedSender<Bytes>),
+
+    /// Processed lines together with their parsed representation, see `Listener::listen_parsed`
+    #[cfg(feature = "parser")]
+    Parsed(mpsc::UnboundedSender<ParsedLine>),
+
+    /// Processed lines as single lines of JSON, see `Listener::listen_json`
+    #[cfg(feature = "json")]
+    Json(mpsc::UnboundedSender<String>),
+}
+
+impl Output {
+    /// Check if the lines are forwarded unprocessed.
+    fn is_raw(&self) -> bool {
+        match self {
+            Output::Raw(_) => true,
+            #[cfg(feature = "bytes")]
+            Output::Bytes(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Send a processed line, no processed lines are delivered in raw mode.
+    fn send(&self, line: ReceivedLine) -> Result<(), ListenError> {
+        match self {
+            Output::Lines(pipe) => pipe.send(line).map_err(|_| ListenError::ReceiverLost),
+            #[cfg(feature = "parser")]
+            Output::Parsed(pipe) => pipe
+                .send(ParsedLine::new(line))
+                .map_err(|_| ListenError::ReceiverLost),
+            #[cfg(feature = "json")]
+            Output::Json(pipe) => pipe
+                .send(line.to_json())
+                .map_err(|_| ListenError::ReceiverLost),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Historical spots requested after the login which are not yet delivered
+struct Backlog {
+    /// Number of historical spots still expected
+    remaining: usize,
+
+    /// Point in time after which no more historical spots are awaited
+    deadline: time::Instant,
+
+    /// Received historical spots, newest first
+    spots: Vec<ReceivedLine>,
+
+    /// Live spots and messages held back until the historical spots are delivered
+    live: Vec<ReceivedLine>,
+}
+
+impl Backlog {
+    /// Start waiting for the given number of historical spots
+    fn new(count: usize) -> Self {
+        Self {
+            remaining: count,
+            deadline: time::Instant::now() + BACKLOG_TIMEOUT,
+            spots: Vec::with_capacity(count),
+            live: Vec::new(),
+        }
+    }
+
+    /// Deliver the historical spots, oldest first, followed by the held back live lines
+    fn flush(self, pipe: &Output, settings: &Settings) -> Result<(), ListenError> {
+        let backlog = self.spots.into_iter().rev().map(|line| ReceivedLine {
+            backlog: true,
+            ..line
+        });
+
+        for line in backlog.chain(self.live) {
+            settings.deliver(pipe, line)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Shared handle to the statistics of a listener, see `Listener::stats_handle`
+#[derive(Clone)]
+pub struct StatsHandle {
+    /// Statistics of the listener
+    health: Arc<Mutex<Health>>,
+}
+
+impl StatsHandle {
+    /// Get the statistics of the listener, kept across sessions.
+    pub fn stats(&self) -> ListenerStats {
+        self.health.lock().unwrap().stats()
+    }
+
+    /// Check if the listener is connected and logged in.
+    pub fn is_connected(&self) -> bool {
+        self.health.lock().unwrap().connected_since.is_some()
+    }
+
+    /// Check if both handles refer to the same listener.
+    pub fn same_listener(&self, other: &StatsHandle) -> bool {
+        Arc::ptr_eq(&self.health, &other.health)
+    }
+}
+
+/// Statistics of a listener collected by the listener task
+#[derive(Default)]
+struct Health {
+    /// Point in time the current session was established
+    connected_since: Option<DateTime<Utc>>,
+
+    /// Number of received live spots
+    received: u64,
+
+    /// Number of delivered live spots
+    spots: u64,
+
+    /// Number of lines dropped by the filters
+    dropped_lines: u64,
+
+    /// Points in time of the spots delivered within the rate period
+    recent: VecDeque<time::Instant>,
+
+    /// Error which ended the last session or connection attempt
+    last_error: Option<ListenError>,
+
+    /// Number of reconnects
+    reconnects: u64,
+}
+
+impl Health {
+    /// Count a delivered spot
+    fn spot(&mut self) {
+        self.received += 1;
+        self.spots += 1;
+        self.recent.push_back(time::Instant::now());
+        self.expire();
+    }
+
+    /// Count a line dropped by the filters
+    fn drop_line(&mut self, live_spot: bool) {
+        if live_spot {
+            self.received += 1;
+        }
+        self.dropped_lines += 1;
+    }
+
+    /// Forget spots older than the rate period
+    fn expire(&mut self) {
+        while self
+            .recent
+            .front()
+            .is_some_and(|instant| instant.elapsed() > RATE_PERIOD)
+        {
+            self.recent.pop_front();
+        }
+    }
+
+    /// Get the current statistics
+    fn stats(&mut self) -> ListenerStats {
+        self.expire();
+        ListenerStats {
+            connected_since: self.connected_since,
+            received: self.received,
+            spots: self.spots,
+            spots_per_minute: self.recent.len() as u64,
+            dropped_lines: self.dropped_lines,
+            last_error: self.last_error.clone(),
+            reconnects: self.reconnects,
+        }
+    }
+}
+
+/// Command waiting for its response
+struct PendingCommand {
+    /// Sent command
+    command: String,
+
+    /// Channel to reply with the response
+    reply: Reply,
+
+    /// Received response lines
+    lines: Vec<String>,
+
+    /// Point in time after which the response is considered complete
+    deadline: time::Instant,
+}
+
+/// Commands sent through the command/response interface
+#[derive(Default)]
+struct Commands {
+    /// Command currently waiting for its response
+    active: Option<PendingCommand>,
+
+    /// Commands to send as soon as the active one is complete
+    queue: VecDeque<(String, Reply)>,
+}
+
+impl Commands {
+    /// Queue a command and send it if no other command is waiting for its response
+    async fn push(
+        &mut self,
+        conn: &mut Connection,
+        command: String,
+        reply: Reply,
+        timeout: time::Duration,
+    ) -> Result<(), ListenError> {
+        self.queue.push_back((command, reply));
+        self.next(conn, timeout).await
+    }
+
+    /// Send the next queued command if no other command is waiting for its response
+    async fn next(
+        &mut self,
+        conn: &mut Connection,
+        timeout: time::Duration,
+    ) -> Result<(), ListenError> {
+        if self.active.is_none() {
+            if let Some((command, reply)) = self.queue.pop_front() {
+                conn.send_line(&command).await?;
+                self.active = Some(PendingCommand {
+                    command,
+                    reply,
+                    lines: Vec::new(),
+                    deadline: time::Instant::now() + timeout,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Point in time after which the response of the active command is considered complete
+    fn deadline(&self) -> Option<time::Instant> {
+        self.active.as_ref().map(|cmd| cmd.deadline)
+    }
+
+    /// Add a received line to the response of the active command.
+    /// Returns false if no command is waiting for its response.
+    fn collect(&mut self, line: &str, timeout: time::Duration) -> bool {
+        match self.active.as_mut() {
+            Some(cmd) => {
+                // Ignore echo of the command
+                if cmd.lines.is_empty() && line.trim() == cmd.command.trim() {
+                    return true;
+                }
+                cmd.lines.push(line.into());
+                cmd.deadline = time::Instant::now() + timeout;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Check if the active command already received response lines
+    fn has_response(&self) -> bool {
+        self.active
+            .as_ref()
+            .is_some_and(|cmd| !cmd.lines.is_empty())
+    }
+
+    /// Reply with the response of the active command
+    fn complete(&mut self) {
+        if let Some(cmd) = self.active.take() {
+            // The caller may not be interested in the response anymore
+            let _ = cmd.reply.send(Ok(cmd.lines));
+        }
+    }
+}
+
 /// Open connection to server
-async fn connect(constring: String) -> Result<TcpStream, ListenError> {
-    let tcp = TcpStream::connect(constring)
+async fn connect(settings: &Settings) -> Result<BoxedTransport, ListenError> {
+    #[cfg(feature = "ssh")]
+    let stream: BoxedTransport = match settings.ssh.as_ref() {
+        Some(tunnel) => Box::new(
+            tunnel
+                .open(
+                    &settings.source.host,
+                    settings.source.port,
+                    settings.connection_timeout,
+                )
+                .await?,
+        ),
+        None => dial(settings).await?,
+    };
+    #[cfg(not(feature = "ssh"))]
+    let stream = dial(settings).await?;
+
+    #[cfg(any(feature = "tls", feature = "native-tls"))]
+    let stream = match settings.tls.as_ref() {
+        Some(config) => tls::connect_tls(stream, &settings.source.host, config).await?,
+        None => stream,
+    };
+
+    #[cfg(feature = "websocket-client")]
+    if let Some(path) = settings.websocket_path.as_ref() {
+        #[cfg(any(feature = "tls", feature = "native-tls"))]
+        let scheme = if settings.tls.is_some() { "wss" } else { "ws" };
+        #[cfg(not(any(feature = "tls", feature = "native-tls")))]
+        let scheme = "ws";
+
+        let url = format!(
+            "{}://{}/{}",
+            scheme,
+            settings.address,
+            path.trim_start_matches('/')
+        );
+        return Ok(Box::new(WebSocketTransport::connect(stream, &url).await?));
+    }
+
+    Ok(stream)
+}
+
+/// Open TCP connection to server, through the proxy if set
+async fn dial(settings: &Settings) -> Result<BoxedTransport, ListenError> {
+    let host = &settings.source.host;
+    match settings.proxy.as_ref() {
+        Some(proxy) => {
+            let stream = connect_tcp(&proxy.address).await?;
+            proxy.connect(stream, host, settings.source.port).await
+        }
+        // Resolving the host would leak the onion service to the DNS server
+        None if is_onion(host) => Err(ListenError::ProxyError(
+            "onion services are reachable through Tor only".into(),
+        )),
+        None => connect_tcp(&settings.address).await,
+    }
+}
+
+/// Open TCP connection
+async fn connect_tcp(address: &str) -> Result<BoxedTransport, ListenError> {
+    let tcp = TcpStream::connect(address)
         .await
         .map_err(|_| ListenError::ConnectionError)?;
 
@@ -187,97 +1603,297 @@ async fn connect(constring: String) -> Result<TcpStream, ListenError> {
     sf.set_tcp_keepalive(&ka)
         .map_err(|_| ListenError::InternalError)?;
 
-    Ok(tcp)
+    Ok(Box::new(tcp))
+}
+
+/// Send a lifecycle event to the channel for events, if any.
+fn emit(events: &Option<mpsc::UnboundedSender<Event>>, source: &Source, kind: EventKind) {
+    if let Some(events) = events {
+        // Events are optional, a closed channel is not an error
+        let _ = events.send(Event::new(source.clone(), kind));
+    }
+}
+
+/// Wrap an established stream into a `Connection`
+fn open(stream: BoxedTransport, settings: &Settings) -> Connection {
+    let mut conn = Connection::from_transport(stream);
+    conn.set_line_terminator(settings.line_terminator);
+    conn.set_decoding(settings.decoding);
+    conn
 }
 
 /// Run the client.
-/// First, authenticate at server with callsign.
-/// Afterwards parse received spot and pass the parsed information into the communication channel.
+/// Establish sessions with the server and reconnect after a lost connection if requested.
 async fn run(
-    mut stream: TcpStream,
-    pipe: mpsc::UnboundedSender<String>,
+    stream: BoxedTransport,
+    pipe: Output,
     mut shutdown: mpsc::UnboundedReceiver<()>,
-    callsign: &str,
+    mut control: mpsc::UnboundedReceiver<Control>,
+    mut settings: Settings,
 ) -> Result<(), ListenError> {
-    // Split stream ins reading and writing half
-    let (mut rx, mut tx) = stream.split();
+    let mut stream = Some(stream);
+    let mut attempts = 0;
+    let mut first = true;
 
-    // Authenticate at server
-    auth(&mut rx, &mut tx, callsign).await?;
+    loop {
+        let res = match stream.take() {
+            Some(stream) => Ok(stream),
+            None => reconnect(&settings).await,
+        };
 
-    // Read incoming lines from server
-    read(&mut rx, &mut shutdown, pipe).await?;
+        let res = match res {
+            Ok(stream) => {
+                session(
+                    stream,
+                    &pipe,
+                    &mut shutdown,
+                    &mut control,
+                    &mut settings,
+                    first,
+                )
+                .await
+            }
+            Err(err) => Err(SessionError::Setup(err)),
+        };
+        settings.health.lock().unwrap().connected_since = None;
+
+        let (established, err) = match res {
+            Ok(()) => break,
+            Err(SessionError::Setup(err)) => (false, err),
+            Err(SessionError::Read(err)) => (true, err),
+        };
+        settings.health.lock().unwrap().last_error = Some(err.clone());
+        if established {
+            settings.emit(EventKind::Lost(err.clone()));
+        }
+
+        // Give up on permanent errors, without reconnect policy or if a new stream can not be opened
+        let policy = match settings.reconnect {
+            Some(policy) if is_transient(&err) && settings.dial => policy,
+            _ => return Err(err),
+        };
+
+        // Start counting again after an established session
+        if established {
+            attempts = 0;
+            first = false;
+        }
+
+        if policy.max_attempts.is_some_and(|max| attempts >= max) {
+            Err(err)?;
+        }
+        attempts += 1;
+        settings.health.lock().unwrap().reconnects += 1;
+        settings.emit(EventKind::Reconnecting(attempts));
+
+        // Wait before reconnecting or stop if requested
+        tokio::select! {
+            _ = time::sleep(policy.delay) => (),
+            res = shutdown.recv() => {
+                if res.is_none() {
+                    Err(ListenError::InternalError)?;
+                }
+                break;
+            },
+        }
+    }
 
     Ok(())
 }
 
-/// Authenticate at server
-async fn auth(
-    rx: &mut ReadHalf<'_>,
-    tx: &mut WriteHalf<'_>,
-    callsign: &str,
-) -> Result<(), ListenError> {
-    // Configuration
-    let mut retries = 5;
+/// Error of a session
+enum SessionError {
+    /// Error while setting up the session
+    Setup(ListenError),
 
-    // Create reader
-    let mut reader = BufReader::new(rx);
+    /// Error after the session was established
+    Read(ListenError),
+}
 
-    // Buffer
-    let mut buf = vec![];
+/// Run a single session.
+/// First, authenticate at server with callsign and execute the setup steps.
+/// Afterwards parse received spot and pass the parsed information into the communication channel.
+async fn session(
+    stream: BoxedTransport,
+    pipe: &Output,
+    shutdown: &mut mpsc::UnboundedReceiver<()>,
+    control: &mut mpsc::UnboundedReceiver<Control>,
+    settings: &mut Settings,
+    first: bool,
+) -> Result<(), SessionError> {
+    settings.emit(EventKind::Connected);
+    let mut conn = setup(stream, settings, first)
+        .await
+        .map_err(|(step, err)| {
+            if let Some(hook) = settings.on_setup_error.as_ref() {
+                hook(step.clone(), &err);
+            }
+            settings.emit(EventKind::SetupFailed(step, err.clone()));
+            SessionError::Setup(err)
+        })?;
+    settings.health.lock().unwrap().connected_since = Some(Utc::now());
+    if let Some(callsign) = settings.session_callsign.lock().unwrap().clone() {
+        settings.emit(EventKind::Authenticated(callsign));
+    }
 
-    loop {
-        // Read data with timeout
-        let res = time::timeout(
-            time::Duration::from_millis(500),
-            reader.read_until(b':', &mut buf),
-        )
-        .await;
+    // Read incoming lines from server
+    read(&mut conn, shutdown, control, pipe, settings, first)
+        .await
+        .map_err(SessionError::Read)
+}
 
-        // Check for errors of read function
-        if let Ok(inner) = res {
-            check_read_result(&inner)?;
-        }
+/// Set up a new session.
+/// Authenticate at server and execute the login commands.
+/// Returns the failed step in case of an error.
+async fn setup(
+    stream: BoxedTransport,
+    settings: &mut Settings,
+    first: bool,
+) -> Result<Connection, (SetupStep, ListenError)> {
+    let mut conn = open(stream, settings);
+    let mut callsign = settings.callsign.clone();
 
-        // Process read data
-        if let Ok(line) = str::from_utf8(&buf) {
-            // Check if the read string ends with the auth token
-            if is_auth_token(line) {
-                // Send callsign to server for authentication
-                send_line(tx, callsign).await?;
-                break;
+    // Authenticate at server, retry with another SSID if the callsign is already connected
+    loop {
+        match settings
+            .authenticator
+            .authenticate(&mut conn, &callsign)
+            .await
+        {
+            Ok(()) => break,
+            Err(ListenError::CallsignInUse) if settings.max_ssid.is_some() && settings.dial => {
+                callsign = settings
+                    .max_ssid
+                    .and_then(|max| auth::next_ssid(&callsign, max))
+                    .ok_or((SetupStep::Authentication, ListenError::CallsignInUse))?;
+
+                let stream = reconnect(settings)
+                    .await
+                    .map_err(|err| (SetupStep::Authentication, err))?;
+                conn = open(stream, settings);
             }
+            Err(err) => Err((SetupStep::Authentication, err))?,
         }
+    }
+    settings.session_prompt = Some(
+        Regex::new(&format!(
+            r"(?i)^{} de \S+[^>\r\n]*>",
+            regex::escape(&callsign)
+        ))
+        .unwrap(),
+    );
+    *settings.session_callsign.lock().unwrap() = Some(callsign);
 
-        // Take care of endless loop
-        retries -= 1;
-        if retries == 0 {
-            Err(ListenError::AuthenticationError)?;
-        }
+    // Execute login commands
+    for cmd in settings.login_commands.iter() {
+        conn.send_line(cmd)
+            .await
+            .map_err(|err| (SetupStep::LoginCommand(cmd.clone()), err))?;
     }
 
-    Ok(())
+    // Set up server-side filters
+    for cmd in settings.server_filters.iter() {
+        conn.send_line(cmd)
+            .await
+            .map_err(|err| (SetupStep::ServerFilters, err))?;
+    }
+
+    // Request machine readable spots
+    if settings.cc11 {
+        conn.send_line(CC11_COMMAND)
+            .await
+            .map_err(|err| (SetupStep::Cc11, err))?;
+    }
+
+    // Request historical spots once
+    if first && settings.backlog > 0 {
+        conn.send_line(&format!("sh/dx {}", settings.backlog))
+            .await
+            .map_err(|err| (SetupStep::Backlog, err))?;
+    }
+
+    Ok(conn)
+}
+
+/// Open a new connection to the server
+async fn reconnect(settings: &Settings) -> Result<BoxedTransport, ListenError> {
+    time::timeout(settings.connection_timeout, connect(settings))
+        .await
+        .map_err(|_| ListenError::ConnectionTimeout)?
+}
+
+/// Check if an error is caused by a temporary problem with the connection
+/// which may be solved by reconnecting.
+fn is_transient(err: &ListenError) -> bool {
+    matches!(
+        err,
+        ListenError::ConnectionLost
+            | ListenError::ConnectionError
+            | ListenError::ConnectionTimeout
+            | ListenError::AuthenticationTimeout
+            | ListenError::UnknownError
+    )
 }
 
 /// Read and forward incoming lines
 async fn read(
-    rx: &mut ReadHalf<'_>,
+    conn: &mut Connection,
     shutdown: &mut mpsc::UnboundedReceiver<()>,
-    pipe: mpsc::UnboundedSender<String>,
+    control: &mut mpsc::UnboundedReceiver<Control>,
+    pipe: &Output,
+    settings: &mut Settings,
+    first: bool,
 ) -> Result<(), ListenError> {
-    // Create reader
-    let mut reader = BufReader::new(rx);
-
     // Line buffer
     let mut line = String::with_capacity(100);
 
+    // Drop lines until the first spot or message if requested
+    let mut suppress = settings.suppress_login_chatter;
+
+    // Collect requested historical spots
+    let mut backlog =
+        (first && settings.backlog > 0 && !pipe.is_raw()).then(|| Backlog::new(settings.backlog));
+    let deadline = backlog.as_ref().map(|b| b.deadline);
+
+    // Commands waiting for their response
+    let mut commands = Commands::default();
+
+    // Length of the partially received line already checked for a prompt
+    let mut checked_partial = 0;
+
+    // Message held back until its possible continuation is received
+    let mut wrapped: Option<(String, time::Instant)> = None;
+
+    // Keep idle connection alive
+    let mut keepalive = settings
+        .keepalive
+        .map(|period| time::interval_at(time::Instant::now() + period, period));
+
     loop {
         // Read line or wait for shutdown signal
         tokio::select! {
-            res = reader.read_line(&mut line) => {
-                match check_read_result(&res) {
+            res = conn.read_frame() => {
+                let frame = res?;
+
+                // Forward unprocessed line in raw mode
+                match pipe {
+                    Output::Raw(raw) => {
+                        raw.send(frame).map_err(|_| ListenError::ReceiverLost)?;
+                        continue;
+                    }
+                    #[cfg(feature = "bytes")]
+                    Output::Bytes(raw) => {
+                        // Takes over the buffer of the frame without copying
+                        raw.send(Bytes::from(frame)).map_err(|_| ListenError::ReceiverLost)?;
+                        continue;
+                    }
+                    _ => (),
+                }
+
+                match conn.decode(frame) {
+                    Ok(decoded) => line.push_str(&decoded),
                     Err(ListenError::InvalidData) => continue,
-                    other => other.map(|_| ())?,
+                    Err(err) => Err(err)?,
                 }
             },
             res = shutdown.recv() => {
@@ -286,37 +1902,178 @@ async fn read(
                 }
                 break;
             },
+            _ = time::sleep_until(deadline.unwrap_or_else(time::Instant::now)), if backlog.is_some() => {
+                if let Some(b) = backlog.take() {
+                    b.flush(pipe, settings)?;
+                }
+                continue;
+            },
+            Some(ctrl) = control.recv() => {
+                match ctrl {
+                    Control::Cc11(enabled) => {
+                        settings.cc11 = enabled;
+                        conn.send_line(if enabled { CC11_COMMAND } else { CLASSIC_COMMAND }).await?;
+                    }
+                    Control::Send(data) => {
+                        conn.send_line(&data).await?;
+                    }
+                    Control::ServerFilters(cmds) => {
+                        for cmd in cmds.iter() {
+                            conn.send_line(cmd).await?;
+                        }
+                        settings.server_filters = cmds;
+                    }
+                    Control::Filters(filters) => {
+                        settings.filters = filters;
+                    }
+                    Control::Command(cmd, reply) => {
+                        commands.push(conn, cmd, reply, settings.command_timeout).await?;
+                    }
+                }
+                continue;
+            },
+            _ = time::sleep_until(commands.deadline().unwrap_or_else(time::Instant::now)), if commands.deadline().is_some() => {
+                commands.complete();
+                commands.next(conn, settings.command_timeout).await?;
+                continue;
+            },
+            _ = time::sleep_until(wrapped.as_ref().map_or_else(time::Instant::now, |w| w.1)), if wrapped.is_some() => {
+                if let Some((msg, _)) = wrapped.take() {
+                    settings.deliver(pipe, settings.receive(msg))?;
+                }
+                continue;
+            },
+            _ = tick(&mut keepalive) => {
+                conn.send_line(&settings.keepalive_command).await?;
+                continue;
+            },
+            _ = time::sleep(PROMPT_IDLE) => {
+                // Command prompts are usually not terminated by a line break
+                if pipe.is_raw() || conn.partial_line().len() <= checked_partial {
+                    continue;
+                }
+                checked_partial = conn.partial_line().len();
+                let partial = String::from_utf8_lossy(conn.partial_line()).into_owned();
+                match split_prompt(&partial, settings) {
+                    (Some(_), "") => {
+                        conn.take_partial_line();
+                        line.push_str(&partial);
+                    }
+                    _ => continue,
+                }
+            },
+        }
+        checked_partial = 0;
+
+        // Remove unwanted characters from received line.
+        // Trailing whitespace is removed again as it may have been hidden behind escape sequences.
+        let sanitized = settings.sanitization.apply(clean_line(&line));
+        let clean = clean_line(&sanitized);
+
+        // Detect the command prompt, which may be directly followed by the next line
+        let (prompt, rest) = split_prompt(clean, settings);
+        if let Some(prompt) = prompt {
+            *settings.last_prompt.lock().unwrap() = Some(prompt.into());
+
+            // The prompt following the historical spots completes the listing
+            if backlog.as_ref().is_some_and(|b| !b.spots.is_empty()) {
+                if let Some(b) = backlog.take() {
+                    b.flush(pipe, settings)?;
+                }
+            }
+
+            // The prompt completes the response of a command.
+            // A prompt directly followed by another line may also precede the response.
+            if commands.deadline().is_some() && (rest.is_empty() || commands.has_response()) {
+                commands.complete();
+                commands.next(conn, settings.command_timeout).await?;
+            }
+
+            // A prompt on its own is not delivered, also if no command is pending
+            if rest.is_empty() {
+                line.clear();
+                continue;
+            }
+        }
+        let clean = if rest.is_empty() { clean } else { rest };
+
+        // Drop lines known to be neither spots nor messages
+        if is_noise(clean, settings) {
+            line.clear();
+            continue;
+        }
+
+        // Hold back live lines while historical spots are awaited
+        if let Some(b) = backlog.as_mut() {
+            let held = if is_backlog_spot(clean) {
+                b.spots.push(settings.receive(clean.trim_start().into()));
+                b.remaining -= 1;
+                true
+            } else if is_message(clean) {
+                b.live.push(settings.receive(clean.into()));
+                true
+            } else {
+                false
+            };
+
+            if backlog.as_ref().is_some_and(|b| b.remaining == 0) {
+                if let Some(b) = backlog.take() {
+                    b.flush(pipe, settings)?;
+                }
+            }
+
+            if held {
+                suppress = false;
+                line.clear();
+                continue;
+            }
+        }
+
+        // Append continuation of a wrapped message
+        if let Some((msg, _)) = wrapped.as_mut() {
+            if clean.starts_with(char::is_whitespace) && !clean.trim().is_empty() {
+                msg.push(' ');
+                msg.push_str(clean.trim());
+                line.clear();
+                continue;
+            }
+        }
+        if let Some((msg, _)) = wrapped.take() {
+            settings.deliver(pipe, settings.receive(msg))?;
         }
 
-        // Remove unwanted characters from received line
-        let clean = clean_line(&line);
+        // Collect response of a command
+        if !is_message(clean) && commands.collect(clean, settings.command_timeout) {
+            line.clear();
+            continue;
+        }
+
+        if suppress && !is_message(clean) {
+            line.clear();
+            continue;
+        }
+        suppress = false;
+
+        // Hold back message which may be continued in the next line
+        if settings.reassemble && is_wrappable(clean) {
+            wrapped = Some((clean.into(), time::Instant::now() + WRAP_TIMEOUT));
+            line.clear();
+            continue;
+        }
 
         // Push received line into channel
-        pipe.send(clean.into())
-            .map_err(|_| ListenError::ReceiverLost)?;
+        settings.deliver(pipe, settings.receive(clean.into()))?;
 
         // Clear buffer
         line.clear();
     }
 
-    Ok(())
-}
-
-/// Check result from read function against possible errors.
-///
-/// Possible `ListenError`:
-/// - `ConnectionLost`: Received EOF or peer closed connection
-/// - `InvalidData`: Received data with incompatible encoding (utf-8 required)
-/// - `ConnectionTimeout`: TCP keepalive check failed
-/// - `InternalError`: Unknown/unhandled error
-fn check_read_result(res: &io::Result<usize>) -> Result<usize, ListenError> {
-    match res {
-        Ok(0) => Err(ListenError::ConnectionLost),
-        Err(err) if err.kind() == io::ErrorKind::InvalidData => Err(ListenError::InvalidData),
-        Err(err) if err.kind() == io::ErrorKind::TimedOut => Err(ListenError::ConnectionTimeout),
-        Err(err) if err.kind() == io::ErrorKind::ConnectionReset => Err(ListenError::ConnectionLost),
-        Ok(num) => Ok(*num),
+    // Deliver message still waiting for its continuation
+    if let Some((msg, _)) = wrapped.take() {
+        settings.deliver(pipe, settings.receive(msg))?;
     }
+
+    Ok(())
 }
 
 /// Clean line from unwanted characters.
@@ -325,22 +2082,145 @@ fn clean_line(line: &str) -> &str {
     line.trim_end().trim_end_matches('\u{0007}')
 }
 
-/// Check if a given string ends with one of the authentication tokens.
-fn is_auth_token(token: &str) -> bool {
-    for key in AUTH_TOKEN.iter() {
-        if token.ends_with(key) {
-            return true;
-        }
+/// Check if a given line carries a spot or message.
+fn is_message(line: &str) -> bool {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    LineFormat::detect(line) == LineFormat::Cc11
+        || PATTERN
+            .get_or_init(|| Regex::new(MESSAGE_PATTERN).unwrap())
+            .is_match(line.trim_start())
+}
+
+/// Check if a given line is a message which may be wrapped across multiple lines.
+fn is_wrappable(line: &str) -> bool {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN
+        .get_or_init(|| Regex::new(WRAPPABLE_PATTERN).unwrap())
+        .is_match(line.trim_start())
+}
+
+/// Check if a given line is a historical spot as listed by the `sh/dx` command.
+fn is_backlog_spot(line: &str) -> bool {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN
+        .get_or_init(|| Regex::new(BACKLOG_PATTERN).unwrap())
+        .is_match(line.trim_start())
+}
+
+/// Split the command prompt of the server from a given line.
+/// Returns the prompt, if any, and the remainder of the line following the prompt.
+fn split_prompt<'a>(line: &'a str, settings: &Settings) -> (Option<&'a str>, &'a str) {
+    let line = line.trim_start();
+
+    if settings
+        .prompt
+        .as_ref()
+        .is_some_and(|prompt| prompt.is_match(line))
+    {
+        return (Some(line.trim_end()), "");
     }
 
-    false
+    match settings
+        .session_prompt
+        .as_ref()
+        .and_then(|prompt| prompt.find(line))
+    {
+        Some(m) => (Some(m.as_str()), line[m.end()..].trim_start()),
+        None => (None, ""),
+    }
+}
+
+/// Check if a given line is known to be neither a spot nor a message.
+fn is_noise(line: &str, settings: &Settings) -> bool {
+    settings
+        .noise
+        .as_ref()
+        .is_some_and(|noise| noise.is_match(line.trim_start()))
+}
+
+/// Wait for the next tick of an optional interval.
+/// Never completes if no interval is given.
+async fn tick(interval: &mut Option<time::Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
 }
 
-/// Send a string through a tcp stream.
-/// Appends '\r\n' to the given string before sending it.
-async fn send_line(stream: &mut WriteHalf<'_>, data: &str) -> Result<(), ListenError> {
-    stream
-        .write_all(format!("{}\r\n", data).as_bytes())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    // Time to wait for the listener to react to the scripted server
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Accept the next connection, send the login prompt and get the callsign sent back.
+    async fn login(server: &TcpListener) -> (BufReader<TcpStream>, String) {
+        let (stream, _) = server.accept().await.unwrap();
+        let mut stream = BufReader::new(stream);
+        stream.write_all(b"login: ").await.unwrap();
+
+        let mut callsign = String::new();
+        stream.read_line(&mut callsign).await.unwrap();
+        (stream, callsign.trim().into())
+    }
+
+    #[tokio::test]
+    async fn next_ssid_if_in_use() {
+        let server = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = server.local_addr().unwrap().port();
+        let script = tokio::spawn(async move {
+            let mut callsigns = Vec::new();
+            for _ in 0..2 {
+                let (mut stream, callsign) = login(&server).await;
+                stream
+                    .write_all(format!("{} is already connected\r\n", callsign).as_bytes())
+                    .await
+                    .unwrap();
+                callsigns.push(callsign);
+            }
+
+            let (mut stream, callsign) = login(&server).await;
+            stream.write_all(b"Hello\r\n").await.unwrap();
+            callsigns.push(callsign);
+            (stream, callsigns)
+        });
+
+        let mut listener = Listener::new("127.0.0.1".into(), port, "N0CALL".into());
+        listener.auth_timeout = Duration::from_millis(100);
+        listener.max_ssid = Some(3);
+        let (tx, _rx) = mpsc::unbounded_channel();
+        listener.listen(tx, TIMEOUT).await.unwrap();
+
+        let (_stream, callsigns) = time::timeout(TIMEOUT, script).await.unwrap().unwrap();
+        assert_eq!(callsigns, ["N0CALL", "N0CALL-1", "N0CALL-2"]);
+        time::timeout(TIMEOUT, async {
+            while listener.session_callsign().is_none() {
+                time::sleep(Duration::from_millis(10)).await;
+            }
+        })
         .await
-        .map_err(|_| ListenError::UnknownError)
+        .unwrap();
+        assert_eq!(listener.session_callsign().as_deref(), Some("N0CALL-2"));
+
+        listener.request_stop().unwrap();
+        assert_eq!(
+            time::timeout(TIMEOUT, listener.join()).await.unwrap(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn display() {
+        let mut listener = Listener::new("dxc.example".into(), 7300, "N0CALL".into());
+        assert_eq!(listener.to_string(), "N0CALL@dxc.example:7300");
+
+        listener.label = Some("local".into());
+        assert_eq!(listener.to_string(), "local (N0CALL@dxc.example:7300)");
+    }
 }
@@ -0,0 +1,860 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use regex::Regex;
+use std::sync::OnceLock;
+
+use crate::band::{Band, Mode};
+
+// Spot in classic format, e.g. `DX de DL1ABC:  14025.0  K1ABC  CW 599  1234Z JO62`.
+const DX_PATTERN: &str = r"^DX de ([^:\s]+):?\s+(\d+(?:\.\d+)?)\s+(\S+)\s*(.*?)\s*(\d{4})Z(?:\s+([A-Za-z]{2}\d{2}\S*))?$";
+
+// Spot of a skimmer of the Reverse Beacon Network,
+// e.g. `DX de EA5WU-#:  7026.0  OK1ABC  CW  15 dB  22 WPM  CQ  2102Z` or `DX de KM3T-#:  14074.0  JA1XYZ  FT8  -12 dB  CQ  2102Z`.
+const SKIMMER_PATTERN: &str = r"^DX de ([^:\s]+-#):?\s+(\d+(?:\.\d+)?)\s+(\S+)\s+([A-Za-z][A-Za-z0-9]*)\s+(-?\d+)\s*dB(?:\s+(\d+)\s+(WPM|BPS))?\s*(.*?)\s*(\d{4})Z(?:\s+([A-Za-z]{2}\d{2}\S*))?$";
+
+// Historical spot as listed by the `sh/dx` command, e.g. `14025.0  K1ABC  16-Oct-2026 1234Z  CW 599  <DL1ABC>`.
+const BACKLOG_DX_PATTERN: &str =
+    r"^(\d+(?:\.\d+)?)\s+(\S+)\s+(\d{1,2}-\w{3}-\d{4})\s+(\d{4})Z\s*(.*?)\s*<([^>]+)>$";
+
+// Propagation reports, e.g. `WWV de W0MU <18>: SFI=68, A=4, K=1, No Storms -> No Storms`
+// and `WCY de DK0WCY-1 <21> : K=2 expK=2 A=8 R=0 SFI=70 SA=qui GMF=qui Au=no`.
+const WWV_PATTERN: &str =
+    r"^WWV de ([^\s<:]+)\s*<(\d{1,2})Z?>\s*:\s*SFI=(\d+),?\s*A=(\d+),?\s*K=(\d+),?\s*(.*)$";
+const WCY_PATTERN: &str = r"^WCY de ([^\s<:]+)\s*<(\d{1,2})Z?>\s*:\s*K=(\d+)\s+expK=(\d+)\s+A=(\d+)\s+R=(\d+)\s+SFI=(\d+)\s+SA=(\S+)\s+GMF=(\S+)\s+Au=(\S+)";
+
+// Announcement, e.g. `To ALL de DL1ABC <1234Z>: text`.
+const ANNOUNCE_PATTERN: &str = r"^To (\S+) de ([^\s:<]+)([^:]*):\s*(.*)$";
+
+// Directed message between two users or command prompt, e.g. `N0CALL de DL1ABC 1234Z: text`.
+const TALK_PATTERN: &str = r"^([A-Za-z0-9/\-]+) de ([A-Za-z0-9/\-]+)([^:]*):\s*(.*)$";
+const TIME_PATTERN: &str = r"\b(\d{4})Z\b";
+const PROMPT_PATTERN: &str = r"^[A-Za-z0-9/\-]+ de \S+[^:]*>$";
+
+// Digital mode and decoded SNR in spot comments, e.g. `FT8 -12 dB` or `FT4 +03`.
+const DIGITAL_PATTERN: &str =
+    r"(?i)\b(FT8|FT4|JT65|JT9|JS8|MSK144|Q65)\b(?:\s*([+-]?\d{1,2})\s*dB\b|\s+([+-]\d{1,2})\b)?";
+
+// Maidenhead locators in spot comments, e.g. `JN58` or `JO62kk`.
+const LOCATOR_PATTERN: &str = r"(?i)\b([A-R]{2}[0-9]{2}(?:[A-X]{2})?)\b";
+
+// Words looking like a locator, e.g. the final message of a FT8 QSO.
+const LOCATOR_EXCEPTIONS: [&str; 1] = ["RR73"];
+
+// Format of dates in historical spots and CC11 records, e.g. `16-Oct-2026`.
+const DATE_FORMAT: &str = "%d-%b-%Y";
+
+// Tolerated clock difference to the server before a time is assumed to belong to the previous day.
+const CLOCK_TOLERANCE_MINUTES: i64 = 60;
+
+// Field separator and minimum field count of CC11 records.
+const CC11_SEPARATOR: char = '^';
+const CC11_MIN_FIELDS: usize = 7;
+
+/// Spot of a DX station
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DxSpot {
+    /// Callsign of the spotter
+    pub call_de: String,
+
+    /// Callsign of the spotted station
+    pub call_dx: String,
+
+    /// Frequency in Hz
+    pub freq: u64,
+
+    /// Time of the spot as HHMM in UTC
+    pub utc: u16,
+
+    /// Date of the spot, if sent by the server
+    pub date: Option<NaiveDate>,
+
+    /// Locator of the spotter, if sent by the server
+    pub loc: Option<String>,
+
+    /// Comment of the spotter
+    pub comment: Option<String>,
+
+    /// Digital mode and decoded SNR, if indicated by the comment
+    pub digital: Option<Digital>,
+}
+
+/// Digital mode of a spot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DigitalMode {
+    /// FT8
+    Ft8,
+
+    /// FT4
+    Ft4,
+
+    /// JT65
+    Jt65,
+
+    /// JT9
+    Jt9,
+
+    /// JS8Call
+    Js8,
+
+    /// MSK144 used for meteor scatter
+    Msk144,
+
+    /// Q65
+    Q65,
+}
+
+impl DigitalMode {
+    /// Get the digital mode by its name, e.g. `FT8`, ignoring the case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "FT8" => Some(DigitalMode::Ft8),
+            "FT4" => Some(DigitalMode::Ft4),
+            "JT65" => Some(DigitalMode::Jt65),
+            "JT9" => Some(DigitalMode::Jt9),
+            "JS8" => Some(DigitalMode::Js8),
+            "MSK144" => Some(DigitalMode::Msk144),
+            "Q65" => Some(DigitalMode::Q65),
+            _ => None,
+        }
+    }
+}
+
+/// Digital mode and decoded SNR of a spot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Digital {
+    /// Digital mode
+    pub mode: DigitalMode,
+
+    /// Signal to noise ratio in dB as decoded by the spotter, if sent
+    pub snr: Option<i16>,
+}
+
+impl Digital {
+    /// Detect the digital mode and decoded SNR in the comment of a spot.
+    ///
+    /// # Arguments
+    ///
+    /// * `comment`: Comment of the spot
+    ///
+    /// # Result
+    ///
+    /// Returns the digital mode with SNR or `None` if the comment does not indicate a digital mode.
+    pub fn detect(comment: &str) -> Option<Self> {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        let caps = PATTERN
+            .get_or_init(|| Regex::new(DIGITAL_PATTERN).unwrap())
+            .captures(comment)?;
+
+        Some(Digital {
+            mode: DigitalMode::from_name(&caps[1])?,
+            snr: caps
+                .get(2)
+                .or(caps.get(3))
+                .and_then(|snr| snr.as_str().parse().ok()),
+        })
+    }
+}
+
+/// Spot of a skimmer of the Reverse Beacon Network
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkimmerSpot {
+    /// Callsign of the skimmer
+    pub call_de: String,
+
+    /// Callsign of the spotted station
+    pub call_dx: String,
+
+    /// Frequency in Hz
+    pub freq: u64,
+
+    /// Mode, e.g. `CW`, `RTTY` or `FT8`
+    pub mode: String,
+
+    /// Signal to noise ratio in dB
+    pub snr: i16,
+
+    /// Speed in words per minute (CW) or bauds (RTTY), if sent
+    pub speed: Option<u16>,
+
+    /// Unit of the speed, e.g. `WPM` or `BPS`
+    pub speed_unit: Option<String>,
+
+    /// Type of the transmission, e.g. `CQ`, `BEACON`, `NCDXF B` or `DX`
+    pub info: Option<String>,
+
+    /// Time of the spot as HHMM in UTC
+    pub utc: u16,
+
+    /// Locator of the skimmer, if sent by the server
+    pub loc: Option<String>,
+}
+
+/// WWV propagation report
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wwv {
+    /// Callsign of the reporting station
+    pub call_de: String,
+
+    /// Hour of the report in UTC
+    pub hour: u8,
+
+    /// Solar flux index
+    pub sfi: u16,
+
+    /// A index
+    pub a: u16,
+
+    /// K index
+    pub k: u16,
+
+    /// Forecast text, e.g. `No Storms -> No Storms`
+    pub forecast: Option<String>,
+}
+
+/// WCY propagation report
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wcy {
+    /// Callsign of the reporting station
+    pub call_de: String,
+
+    /// Hour of the report in UTC
+    pub hour: u8,
+
+    /// K index
+    pub k: u16,
+
+    /// Expected K index
+    pub expk: u16,
+
+    /// A index
+    pub a: u16,
+
+    /// Sunspot number
+    pub r: u16,
+
+    /// Solar flux index
+    pub sfi: u16,
+
+    /// Solar activity
+    pub sa: String,
+
+    /// Geomagnetic field
+    pub gmf: String,
+
+    /// Aurora
+    pub au: String,
+}
+
+/// Text message sent by a user, e.g. an announcement or talk message
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextMessage {
+    /// Callsign of the sender
+    pub call_de: String,
+
+    /// Recipient, either a callsign or a group like `ALL` or `LOCAL`
+    pub target: String,
+
+    /// Time of the message as HHMM in UTC, if sent by the server
+    pub utc: Option<u16>,
+
+    /// Text of the message
+    pub text: String,
+}
+
+/// Message received from a cluster server, classified by its type
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClusterMessage {
+    /// Spot of a DX station, either live, historical or in CC11 format
+    DxSpot(DxSpot),
+
+    /// Spot of a skimmer of the Reverse Beacon Network
+    SkimmerSpot(SkimmerSpot),
+
+    /// WWV propagation report
+    Wwv(Wwv),
+
+    /// WCY propagation report
+    Wcy(Wcy),
+
+    /// Announcement to all or local users
+    Announce(TextMessage),
+
+    /// Directed message between two users
+    Talk(TextMessage),
+
+    /// Weather message
+    WxMessage(String),
+
+    /// Command prompt of the server
+    Prompt(String),
+
+    /// Line of unknown type
+    Unknown(String),
+}
+
+impl ClusterMessage {
+    /// Classify a received line.
+    ///
+    /// # Arguments
+    ///
+    /// * `line`: Received line
+    ///
+    /// # Result
+    ///
+    /// Returns the classified message, `ClusterMessage::Unknown` if the type of the line is unknown.
+    pub fn parse(line: &str) -> Self {
+        static ANNOUNCE: OnceLock<Regex> = OnceLock::new();
+        static TALK: OnceLock<Regex> = OnceLock::new();
+        static PROMPT: OnceLock<Regex> = OnceLock::new();
+
+        let line = line.trim();
+        let text = || line.to_string();
+
+        if line.starts_with("CC11^") {
+            parse_cc11_spot(line).map_or_else(|| Self::Unknown(text()), Self::DxSpot)
+        } else if let Some(spot) = parse_skimmer_spot(line) {
+            Self::SkimmerSpot(spot)
+        } else if line.starts_with("DX de ") {
+            parse_dx_spot(line).map_or_else(|| Self::Unknown(text()), Self::DxSpot)
+        } else if let Some(spot) = parse_backlog_spot(line) {
+            Self::DxSpot(spot)
+        } else if line.starts_with("WWV de ") {
+            parse_wwv(line).map_or_else(|| Self::Unknown(text()), Self::Wwv)
+        } else if line.starts_with("WCY de ") {
+            parse_wcy(line).map_or_else(|| Self::Unknown(text()), Self::Wcy)
+        } else if line.starts_with("WX de ") {
+            Self::WxMessage(text())
+        } else if line.starts_with("To ") {
+            parse_text_message(
+                ANNOUNCE.get_or_init(|| Regex::new(ANNOUNCE_PATTERN).unwrap()),
+                line,
+            )
+            .map_or_else(|| Self::Unknown(text()), Self::Announce)
+        } else if PROMPT
+            .get_or_init(|| Regex::new(PROMPT_PATTERN).unwrap())
+            .is_match(line)
+        {
+            Self::Prompt(text())
+        } else if let Some(msg) =
+            parse_text_message(TALK.get_or_init(|| Regex::new(TALK_PATTERN).unwrap()), line)
+        {
+            Self::Talk(msg)
+        } else {
+            Self::Unknown(text())
+        }
+    }
+
+    /// Check if the message is a spot of a DX station or a skimmer.
+    pub fn is_spot(&self) -> bool {
+        matches!(
+            self,
+            ClusterMessage::DxSpot(_) | ClusterMessage::SkimmerSpot(_)
+        )
+    }
+
+    /// Check if the message is a WWV or WCY propagation report.
+    pub fn is_propagation(&self) -> bool {
+        matches!(self, ClusterMessage::Wwv(_) | ClusterMessage::Wcy(_))
+    }
+
+    /// Get the time sent along with the message as HHMM in UTC, if any.
+    pub fn utc(&self) -> Option<u16> {
+        match self {
+            ClusterMessage::DxSpot(spot) => Some(spot.utc),
+            ClusterMessage::SkimmerSpot(spot) => Some(spot.utc),
+            ClusterMessage::Wwv(wwv) => Some(wwv.hour as u16 * 100),
+            ClusterMessage::Wcy(wcy) => Some(wcy.hour as u16 * 100),
+            ClusterMessage::Announce(msg) | ClusterMessage::Talk(msg) => msg.utc,
+            _ => None,
+        }
+    }
+
+    /// Get the full point in time of the message.
+    /// Messages only carry the time of day, which is combined with the date the message
+    /// was received on. A time later than the receive time is assumed to belong to the previous day.
+    /// The date sent along with historical spots is used as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `received`: Point in time the message was received
+    ///
+    /// # Result
+    ///
+    /// Returns the point in time or `None` if the message carries no valid time.
+    pub fn timestamp(&self, received: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let utc = self.utc()?;
+        let time = NaiveTime::from_hms_opt((utc / 100).into(), (utc % 100).into(), 0)?;
+
+        if let Some(date) = self.dx_spot().and_then(|spot| spot.date) {
+            return Some(date.and_time(time).and_utc());
+        }
+
+        let timestamp = received.date_naive().and_time(time).and_utc();
+        if timestamp > received + Duration::minutes(CLOCK_TOLERANCE_MINUTES) {
+            Some(timestamp - Duration::days(1))
+        } else {
+            Some(timestamp)
+        }
+    }
+
+    /// Get the callsigns of the spotter and the spotted station of a spot.
+    pub fn calls(&self) -> Option<(&str, &str)> {
+        match self {
+            ClusterMessage::DxSpot(spot) => Some((&spot.call_de, &spot.call_dx)),
+            ClusterMessage::SkimmerSpot(spot) => Some((&spot.call_de, &spot.call_dx)),
+            _ => None,
+        }
+    }
+
+    /// Get the locators of the spotter and the spotted station of a spot.
+    /// The locator of the spotter sent by the server is preferred. Otherwise the comment is scanned:
+    /// Two locators, e.g. `JO62<ES>JN58`, are taken as locators of the spotter and of the spotted station,
+    /// a single locator as the one of the spotted station.
+    pub fn grids(&self) -> (Option<String>, Option<String>) {
+        let (loc, comment) = match self {
+            ClusterMessage::DxSpot(spot) => (spot.loc.as_deref(), spot.comment.as_deref()),
+            ClusterMessage::SkimmerSpot(spot) => (spot.loc.as_deref(), None),
+            _ => (None, None),
+        };
+
+        let mut found = comment.map(locators).unwrap_or_default().into_iter();
+        let (spotter, dx) = match (found.next(), found.next()) {
+            (Some(first), Some(second)) => (Some(first), Some(second)),
+            (Some(first), None) => (None, Some(first)),
+            _ => (None, None),
+        };
+
+        (loc.map(normalize_locator).or(spotter), dx)
+    }
+
+    /// Get the frequency of a spot in Hz.
+    pub fn freq(&self) -> Option<u64> {
+        match self {
+            ClusterMessage::DxSpot(spot) => Some(spot.freq),
+            ClusterMessage::SkimmerSpot(spot) => Some(spot.freq),
+            _ => None,
+        }
+    }
+
+    /// Get the band of a spot.
+    pub fn band(&self) -> Option<Band> {
+        self.freq().and_then(Band::from_freq)
+    }
+
+    /// Get the mode of a spot.
+    /// The mode named by the spotter is preferred, otherwise the mode is inferred from the band plan.
+    pub fn mode(&self) -> Option<Mode> {
+        let named = match self {
+            ClusterMessage::DxSpot(spot) => spot
+                .digital
+                .map(|_| Mode::Digi)
+                .or_else(|| spot.comment.as_deref().and_then(Mode::from_comment)),
+            ClusterMessage::SkimmerSpot(spot) => Mode::from_name(&spot.mode),
+            _ => None,
+        };
+
+        named.or_else(|| self.freq().and_then(Mode::from_freq))
+    }
+
+    /// Get the digital mode and SNR of a spot, if the spot is of a digital mode.
+    pub fn digital(&self) -> Option<Digital> {
+        match self {
+            ClusterMessage::DxSpot(spot) => spot.digital,
+            ClusterMessage::SkimmerSpot(spot) => Some(Digital {
+                mode: DigitalMode::from_name(&spot.mode)?,
+                snr: Some(spot.snr),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Get the spot if the message is a spot of a DX station.
+    pub fn dx_spot(&self) -> Option<&DxSpot> {
+        match self {
+            ClusterMessage::DxSpot(spot) => Some(spot),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a spot in classic format.
+fn parse_dx_spot(line: &str) -> Option<DxSpot> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let caps = PATTERN
+        .get_or_init(|| Regex::new(DX_PATTERN).unwrap())
+        .captures(line)?;
+
+    Some(DxSpot {
+        call_de: caps[1].to_string(),
+        call_dx: caps[3].to_string(),
+        freq: parse_freq(&caps[2])?,
+        utc: caps[5].parse().ok()?,
+        date: None,
+        loc: caps.get(6).map(|loc| loc.as_str().to_string()),
+        comment: non_empty(&caps[4]),
+        digital: Digital::detect(&caps[4]),
+    })
+}
+
+/// Parse a spot of a skimmer of the Reverse Beacon Network.
+fn parse_skimmer_spot(line: &str) -> Option<SkimmerSpot> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    if !line.starts_with("DX de ") {
+        return None;
+    }
+    let caps = PATTERN
+        .get_or_init(|| Regex::new(SKIMMER_PATTERN).unwrap())
+        .captures(line)?;
+
+    Some(SkimmerSpot {
+        call_de: caps[1].to_string(),
+        call_dx: caps[3].to_string(),
+        freq: parse_freq(&caps[2])?,
+        mode: caps[4].to_string(),
+        snr: caps[5].parse().ok()?,
+        speed: caps.get(6).and_then(|speed| speed.as_str().parse().ok()),
+        speed_unit: caps.get(7).map(|unit| unit.as_str().to_string()),
+        info: non_empty(&caps[8]),
+        utc: caps[9].parse().ok()?,
+        loc: caps.get(10).map(|loc| loc.as_str().to_string()),
+    })
+}
+
+/// Parse a historical spot as listed by the `sh/dx` command.
+fn parse_backlog_spot(line: &str) -> Option<DxSpot> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let caps = PATTERN
+        .get_or_init(|| Regex::new(BACKLOG_DX_PATTERN).unwrap())
+        .captures(line)?;
+
+    Some(DxSpot {
+        call_de: caps[6].to_string(),
+        call_dx: caps[2].to_string(),
+        freq: parse_freq(&caps[1])?,
+        utc: caps[4].parse().ok()?,
+        date: NaiveDate::parse_from_str(&caps[3], DATE_FORMAT).ok(),
+        loc: None,
+        comment: non_empty(&caps[5]),
+        digital: Digital::detect(&caps[5]),
+    })
+}
+
+/// Parse a spot record in CC11 format.
+/// Fields: `CC11^freq^call_dx^date^time^comment^call_de^...`
+fn parse_cc11_spot(line: &str) -> Option<DxSpot> {
+    let fields: Vec<&str> = line.split(CC11_SEPARATOR).collect();
+    if fields.len() < CC11_MIN_FIELDS {
+        return None;
+    }
+
+    Some(DxSpot {
+        call_de: fields[6].trim().to_string(),
+        call_dx: fields[2].trim().to_string(),
+        freq: parse_freq(fields[1].trim())?,
+        utc: fields[4].trim().trim_end_matches('Z').parse().ok()?,
+        date: NaiveDate::parse_from_str(fields[3].trim(), DATE_FORMAT).ok(),
+        loc: None,
+        comment: non_empty(fields[5]),
+        digital: Digital::detect(fields[5]),
+    })
+}
+
+/// Parse a text message with the given pattern.
+/// The pattern captures the target, the sender, the header remainder and the text.
+fn parse_text_message(pattern: &Regex, line: &str) -> Option<TextMessage> {
+    static TIME: OnceLock<Regex> = OnceLock::new();
+    let caps = pattern.captures(line)?;
+    let utc = TIME
+        .get_or_init(|| Regex::new(TIME_PATTERN).unwrap())
+        .captures(&caps[3])
+        .and_then(|time| time[1].parse().ok());
+
+    Some(TextMessage {
+        call_de: caps[2].to_string(),
+        target: caps[1].to_string(),
+        utc,
+        text: caps[4].to_string(),
+    })
+}
+
+/// Parse a WWV propagation report.
+fn parse_wwv(line: &str) -> Option<Wwv> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let caps = PATTERN
+        .get_or_init(|| Regex::new(WWV_PATTERN).unwrap())
+        .captures(line)?;
+
+    Some(Wwv {
+        call_de: caps[1].to_string(),
+        hour: caps[2].parse().ok()?,
+        sfi: caps[3].parse().ok()?,
+        a: caps[4].parse().ok()?,
+        k: caps[5].parse().ok()?,
+        forecast: non_empty(&caps[6]),
+    })
+}
+
+/// Parse a WCY propagation report.
+fn parse_wcy(line: &str) -> Option<Wcy> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let caps = PATTERN
+        .get_or_init(|| Regex::new(WCY_PATTERN).unwrap())
+        .captures(line)?;
+
+    Some(Wcy {
+        call_de: caps[1].to_string(),
+        hour: caps[2].parse().ok()?,
+        k: caps[3].parse().ok()?,
+        expk: caps[4].parse().ok()?,
+        a: caps[5].parse().ok()?,
+        r: caps[6].parse().ok()?,
+        sfi: caps[7].parse().ok()?,
+        sa: caps[8].to_string(),
+        gmf: caps[9].to_string(),
+        au: caps[10].to_string(),
+    })
+}
+
+/// Find all Maidenhead locators in a text.
+fn locators(text: &str) -> Vec<String> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN
+        .get_or_init(|| Regex::new(LOCATOR_PATTERN).unwrap())
+        .captures_iter(text)
+        .map(|caps| normalize_locator(&caps[1]))
+        .filter(|loc| !LOCATOR_EXCEPTIONS.contains(&loc.to_ascii_uppercase().as_str()))
+        .collect()
+}
+
+/// Write a Maidenhead locator in its usual form, e.g. `JO62kk`.
+fn normalize_locator(loc: &str) -> String {
+    let (square, subsquare) = loc.split_at(loc.len().min(4));
+    square.to_ascii_uppercase() + &subsquare.to_ascii_lowercase()
+}
+
+/// Parse a frequency given in kHz into Hz.
+fn parse_freq(freq: &str) -> Option<u64> {
+    let khz: f64 = freq.parse().ok()?;
+    Some((khz * 1000.0).round() as u64)
+}
+
+/// Get the trimmed string or `None` if it is empty.
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parse_dx_spot() {
+        let msg = ClusterMessage::parse(
+            "DX de W3LPL:     14025.0  DL1ABC       CW 599                      1200Z JN58",
+        );
+
+        assert_eq!(
+            msg,
+            ClusterMessage::DxSpot(DxSpot {
+                call_de: "W3LPL".into(),
+                call_dx: "DL1ABC".into(),
+                freq: 14025000,
+                utc: 1200,
+                date: None,
+                loc: Some("JN58".into()),
+                comment: Some("CW 599".into()),
+                digital: None,
+            })
+        );
+        assert!(msg.is_spot());
+    }
+
+    #[test]
+    fn parse_digital_spot() {
+        let msg = ClusterMessage::parse(
+            "DX de DL1ABC:    14074.0  JA1XYZ       FT8 -12 dB  RR73               0815Z",
+        );
+
+        assert_eq!(
+            msg.digital(),
+            Some(Digital {
+                mode: DigitalMode::Ft8,
+                snr: Some(-12),
+            })
+        );
+        assert_eq!(msg.grids(), (None, None));
+    }
+
+    #[test]
+    fn parse_skimmer_spot() {
+        let msg = ClusterMessage::parse(
+            "DX de EA5WU-#:    7026.0  OK1ABC       CW    15 dB  22 WPM  CQ      2102Z",
+        );
+
+        assert_eq!(
+            msg,
+            ClusterMessage::SkimmerSpot(SkimmerSpot {
+                call_de: "EA5WU-#".into(),
+                call_dx: "OK1ABC".into(),
+                freq: 7026000,
+                mode: "CW".into(),
+                snr: 15,
+                speed: Some(22),
+                speed_unit: Some("WPM".into()),
+                info: Some("CQ".into()),
+                utc: 2102,
+                loc: None,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_digital_skimmer_spot() {
+        let ClusterMessage::SkimmerSpot(spot) = ClusterMessage::parse(
+            "DX de KM3T-#:    14074.0  JA1XYZ       FT8   -12 dB  CQ             2102Z",
+        ) else {
+            panic!("not a skimmer spot");
+        };
+
+        assert_eq!(spot.mode, "FT8");
+        assert_eq!(spot.snr, -12);
+        assert_eq!(spot.speed, None);
+    }
+
+    #[test]
+    fn parse_backlog_spot() {
+        let msg = ClusterMessage::parse("14025.0  K1ABC       16-Oct-2026 1234Z  CW 599  <DL1ABC>");
+
+        assert_eq!(
+            msg.dx_spot().map(|spot| (spot.call_de.as_str(), spot.date)),
+            Some(("DL1ABC", NaiveDate::from_ymd_opt(2026, 10, 16)))
+        );
+    }
+
+    #[test]
+    fn parse_wwv() {
+        assert_eq!(
+            ClusterMessage::parse("WWV de W0MU <18>:   SFI=68, A=4, K=1, No Storms -> No Storms"),
+            ClusterMessage::Wwv(Wwv {
+                call_de: "W0MU".into(),
+                hour: 18,
+                sfi: 68,
+                a: 4,
+                k: 1,
+                forecast: Some("No Storms -> No Storms".into()),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_wcy() {
+        assert_eq!(
+            ClusterMessage::parse(
+                "WCY de DK0WCY-1 <21> : K=2 expK=2 A=8 R=0 SFI=70 SA=qui GMF=qui Au=no"
+            ),
+            ClusterMessage::Wcy(Wcy {
+                call_de: "DK0WCY-1".into(),
+                hour: 21,
+                k: 2,
+                expk: 2,
+                a: 8,
+                r: 0,
+                sfi: 70,
+                sa: "qui".into(),
+                gmf: "qui".into(),
+                au: "no".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_announce() {
+        assert_eq!(
+            ClusterMessage::parse("To ALL de DL1ABC <1234Z>: QRV on 6m"),
+            ClusterMessage::Announce(TextMessage {
+                call_de: "DL1ABC".into(),
+                target: "ALL".into(),
+                utc: Some(1234),
+                text: "QRV on 6m".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_talk_and_prompt() {
+        assert_eq!(
+            ClusterMessage::parse("N0CALL de DL1ABC 1234Z: hello"),
+            ClusterMessage::Talk(TextMessage {
+                call_de: "DL1ABC".into(),
+                target: "N0CALL".into(),
+                utc: Some(1234),
+                text: "hello".into(),
+            })
+        );
+        assert_eq!(
+            ClusterMessage::parse("N0CALL de DB0SUE-7 16-Oct-2026 1234Z dxspider >"),
+            ClusterMessage::Prompt("N0CALL de DB0SUE-7 16-Oct-2026 1234Z dxspider >".into())
+        );
+    }
+
+    #[test]
+    fn parse_unknown() {
+        assert_eq!(
+            ClusterMessage::parse("DX de W3LPL: garbage"),
+            ClusterMessage::Unknown("DX de W3LPL: garbage".into())
+        );
+    }
+
+    #[test]
+    fn timestamp_same_day() {
+        let msg = ClusterMessage::parse(
+            "DX de W3LPL:     14025.0  DL1ABC       CW 599                      1200Z",
+        );
+        let received = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 30).unwrap();
+
+        assert_eq!(
+            msg.timestamp(received),
+            Some(Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn timestamp_midnight_rollover() {
+        let msg = ClusterMessage::parse(
+            "DX de W3LPL:     14025.0  DL1ABC       CW 599                      2359Z",
+        );
+        let received = Utc.with_ymd_and_hms(2026, 10, 17, 0, 0, 30).unwrap();
+
+        assert_eq!(
+            msg.timestamp(received),
+            Some(Utc.with_ymd_and_hms(2026, 10, 16, 23, 59, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn timestamp_clock_ahead() {
+        let msg = ClusterMessage::parse(
+            "DX de W3LPL:     14025.0  DL1ABC       CW 599                      1230Z",
+        );
+        let received = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+
+        // A server clock running ahead within the tolerance does not move the spot to the previous day
+        assert_eq!(
+            msg.timestamp(received),
+            Some(Utc.with_ymd_and_hms(2026, 10, 16, 12, 30, 0).unwrap())
+        );
+    }
+}
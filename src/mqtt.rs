@@ -0,0 +1,114 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use rumqttc::{AsyncClient, MqttOptions, QoS};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::line::ReceivedLine;
+use crate::sink::{SinkError, SpotSink};
+use crate::topic::render_topic;
+
+// Number of messages queued while the broker is unavailable
+const QUEUE_CAPACITY: usize = 1000;
+
+// Delay before reconnecting to the broker after a failure
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// Characters with a special meaning in topics
+const RESERVED: [char; 3] = ['/', '+', '#'];
+
+/// Sink publishing each spot as JSON to an MQTT broker.
+///
+/// The topic is given as template, e.g. `dxcluster/spots/{band}/{continent}`, see `render_topic` for the placeholders.
+/// The characters `/`, `+` and `#` within values are replaced by `_`. Lines other than spots are ignored.
+///
+/// The connection to the broker is kept up by a background task, reconnecting after failures.
+/// Spots are queued while the broker is unavailable, the delivery fails if the queue is full.
+#[derive(Debug)]
+pub struct MqttSink {
+    /// Template of the topic
+    pub topic: String,
+
+    /// Quality of service of the published messages
+    pub qos: QoS,
+
+    /// True if the broker shall retain the last message of each topic
+    pub retain: bool,
+
+    /// Client to publish the messages with
+    client: AsyncClient,
+
+    /// Task driving the connection to the broker
+    task: JoinHandle<()>,
+}
+
+impl MqttSink {
+    /// Create a new sink connecting to a broker without authentication.
+    ///
+    /// # Arguments
+    ///
+    /// * `host`: Host of the broker
+    /// * `port`: Port of the broker, usually 1883
+    /// * `topic`: Template of the topic
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `MqttSink`. Must be called within a tokio runtime.
+    pub fn new(host: &str, port: u16, topic: &str) -> Self {
+        let id = format!("dxcllistener-{}", std::process::id());
+        Self::with_options(MqttOptions::new(id, host, port), topic)
+    }
+
+    /// Create a new sink with the given options, e.g. to set credentials or the client id.
+    ///
+    /// # Arguments
+    ///
+    /// * `options`: Options of the connection to the broker
+    /// * `topic`: Template of the topic
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `MqttSink`. Must be called within a tokio runtime.
+    pub fn with_options(options: MqttOptions, topic: &str) -> Self {
+        let (client, mut eventloop) = AsyncClient::new(options, QUEUE_CAPACITY);
+
+        let task = tokio::spawn(async move {
+            loop {
+                if eventloop.poll().await.is_err() {
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        });
+
+        Self {
+            topic: topic.into(),
+            qos: QoS::AtMostOnce,
+            retain: false,
+            client,
+            task,
+        }
+    }
+}
+
+impl Drop for MqttSink {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[async_trait]
+impl SpotSink for MqttSink {
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        if line.parsed.as_ref().and_then(|msg| msg.calls()).is_none() {
+            return Ok(());
+        }
+
+        let topic = render_topic(&self.topic, &line, &RESERVED);
+        self.client
+            .try_publish(topic, self.qos, self.retain, line.to_json())
+            .map_err(|err| SinkError::Backend(err.to_string()))
+    }
+}
@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::line::{LineFormat, ReceivedLine};
+
+/// Received line together with its parsed representation
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsedLine {
+    /// Received line
+    pub line: ReceivedLine,
+
+    /// Result of parsing the received line with `dxclparser`.
+    /// Lines in CC11 format are not supported by the parser and reported as `ParseError::UnknownType`.
+    pub spot: Result<dxclparser::Spot, dxclparser::ParseError>,
+}
+
+impl ParsedLine {
+    /// Parse a received line.
+    ///
+    /// # Arguments
+    ///
+    /// * `line`: Received line
+    ///
+    /// # Result
+    ///
+    /// Returns the received line together with its parsed representation.
+    pub fn new(line: ReceivedLine) -> Self {
+        let spot = match line.format {
+            LineFormat::Classic => dxclparser::parse(&line.raw),
+            LineFormat::Cc11 => Err(dxclparser::ParseError::UnknownType),
+        };
+
+        Self { line, spot }
+    }
+}
@@ -0,0 +1,1105 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time;
+
+#[cfg(feature = "config")]
+use crate::config::{Config, ConfigError, ListenerConfig};
+use crate::event::Event;
+#[cfg(feature = "config")]
+use crate::event::EventKind;
+use crate::filter::Filter;
+use crate::line::ReceivedLine;
+use crate::listener::{ListenError, Listener, StatsHandle};
+use crate::sink::SinkRegistry;
+
+// Delay before the second round of connecting the listeners on demand, doubled for every further round
+const DIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+// Maximum delay between two rounds of connecting the listeners on demand
+const MAX_DIAL_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How the listeners of a `ListenerPool` deliver their lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PoolMode {
+    /// All listeners are connected and deliver their lines
+    #[default]
+    All,
+
+    /// All listeners are connected, but only the first connected listener in the order the listeners were added
+    /// delivers its lines. The backup listeners take over as soon as the listeners before lost their connection
+    /// and hand back as soon as they are connected again.
+    Standby,
+
+    /// Only the primary listener is connected. If it stops, the next listener is connected by `reap`,
+    /// going round all listeners in the order they were added, i.e. back to the primary after the last backup.
+    /// If no listener could be started, `reap` starts another round after a growing delay.
+    /// Transient connection losses are handled by the reconnect policy of the listener first.
+    OnDemand,
+}
+
+/// State of a listener of a `ListenerPool`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ListenerState {
+    /// Not started yet, or stopped on request
+    Stopped,
+
+    /// Listening for spots
+    Running,
+
+    /// Failed to start or stopped unexpectedly
+    Failed(ListenError),
+}
+
+/// Status of a listener of a `ListenerPool`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListenerStatus {
+    /// Listener as `callsign@host:port`, preceded by its label if any, e.g. `local (N0CALL@dxc.example:7300)`
+    pub name: String,
+
+    /// Label of the listener, if any
+    pub label: Option<String>,
+
+    /// State of the listener
+    pub state: ListenerState,
+
+    /// Callsign used for the login of the current or last session
+    pub session_callsign: Option<String>,
+}
+
+/// Aggregate status of a `ListenerPool`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PoolStatus {
+    /// Status of each listener, in the order the listeners were added
+    pub listeners: Vec<ListenerStatus>,
+}
+
+impl PoolStatus {
+    /// Number of running listeners
+    pub fn running(&self) -> usize {
+        self.count(|state| *state == ListenerState::Running)
+    }
+
+    /// Number of failed listeners
+    pub fn failed(&self) -> usize {
+        self.count(|state| matches!(state, ListenerState::Failed(_)))
+    }
+
+    /// Count the listeners in a state
+    fn count<F: Fn(&ListenerState) -> bool>(&self, pred: F) -> usize {
+        self.listeners
+            .iter()
+            .filter(|status| pred(&status.state))
+            .count()
+    }
+}
+
+/// Outcome of `ListenerPool::shutdown`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShutdownReport {
+    /// Final status of each listener, in the order the listeners were added.
+    /// Listeners which did not stop in time failed with `ListenError::Aborted`.
+    pub listeners: Vec<ListenerStatus>,
+
+    /// True if the sinks driven by the pool took all pending lines and were flushed in time,
+    /// also true if the pool drives no sinks
+    pub sinks_flushed: bool,
+}
+
+/// Health report of a listener of a `ListenerPool`, e.g. for health endpoints and dashboards
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListenerHealth {
+    /// Listener as `callsign@host:port`, preceded by its label if any, e.g. `local (N0CALL@dxc.example:7300)`
+    pub name: String,
+
+    /// Label of the listener, if any
+    pub label: Option<String>,
+
+    /// State of the listener
+    pub state: ListenerState,
+
+    /// Point in time the current session was established, `None` if not connected
+    pub connected_since: Option<DateTime<Utc>>,
+
+    /// Duration of the current session in seconds, `0` if not connected
+    pub uptime_secs: u64,
+
+    /// Number of live spots delivered within the last minute
+    pub spots_per_minute: u64,
+
+    /// Error which stopped the listener, or ended its last session or connection attempt, if any
+    pub last_error: Option<ListenError>,
+
+    /// Number of reconnects after a lost session or a failed connection attempt
+    pub reconnects: u64,
+}
+
+/// Contribution of a listener to the spots of a `ListenerPool`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeContribution {
+    /// Listener as `callsign@host:port`, preceded by its label if any, e.g. `local (N0CALL@dxc.example:7300)`
+    pub name: String,
+
+    /// Label of the listener, if any
+    pub label: Option<String>,
+
+    /// Number of received live spots, before the filters
+    pub received: u64,
+
+    /// Number of delivered live spots, i.e. not dropped by the filters, e.g. as duplicates
+    pub spots: u64,
+
+    /// Number of lines of any type dropped by the filters
+    pub dropped_lines: u64,
+
+    /// Share of the listener in the live spots delivered by the pool, between 0 and 1
+    pub share: f64,
+}
+
+/// Statistics merged across all listeners of a `ListenerPool`, kept across sessions.
+///
+/// Share the same `Deduplicator` between the filters of all listeners to drop spots already received
+/// by another listener. The delivered spots of each listener then show which nodes add unique spots.
+/// In a standby mode, the lines of inactive backup listeners are not counted.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PoolStats {
+    /// Number of live spots received by all listeners, before the filters
+    pub received: u64,
+
+    /// Number of live spots delivered by all listeners
+    pub spots: u64,
+
+    /// Number of lines of any type dropped by the filters of all listeners
+    pub dropped_lines: u64,
+
+    /// Share of the received live spots dropped by the filters, between 0 and 1.
+    /// This is the share of duplicates if a shared `Deduplicator` is the only filter.
+    pub dedup_ratio: f64,
+
+    /// Contribution of each listener, in the order the listeners were added
+    pub listeners: Vec<NodeContribution>,
+}
+
+/// Changes applied by `ListenerPool::reload`, each listener named like `ListenerStatus::name`
+#[cfg(feature = "config")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReloadSummary {
+    /// Listeners of new endpoints, created and started
+    pub added: Vec<String>,
+
+    /// Listeners of removed endpoints, stopped and removed
+    pub removed: Vec<String>,
+
+    /// Listeners with changed settings, restarted
+    pub restarted: Vec<String>,
+
+    /// Listeners with changed filters only, updated without reconnecting
+    pub filters_updated: Vec<String>,
+}
+
+/// Configuration a listener of a pool was created from
+#[cfg(feature = "config")]
+#[derive(PartialEq, Eq)]
+struct Origin {
+    /// Configuration of the listener, with the reconnect policy of all listeners applied if it has none
+    listener: ListenerConfig,
+
+    /// Filter expression of all listeners
+    filter: Option<String>,
+}
+
+#[cfg(feature = "config")]
+impl Origin {
+    /// Resolve the configuration of a listener.
+    fn new(listener: &ListenerConfig, config: &Config) -> Self {
+        let mut listener = listener.clone();
+        listener.reconnect = listener.reconnect.or(config.reconnect);
+        Self {
+            listener,
+            filter: config.filter.clone(),
+        }
+    }
+
+    /// Check if both configurations are of the same server and callsign.
+    fn same_endpoint(&self, other: &Origin) -> bool {
+        self.listener.endpoint == other.listener.endpoint
+            && self.listener.callsign == other.listener.callsign
+    }
+
+    /// Check if both configurations differ in their filters at most.
+    fn same_except_filters(&self, other: &Origin) -> bool {
+        let without_filter = |origin: &Origin| ListenerConfig {
+            filter: None,
+            ..origin.listener.clone()
+        };
+        without_filter(self) == without_filter(other)
+    }
+}
+
+/// Listeners of a pool in the order of precedence, used by the standby modes
+type Precedence = Arc<Mutex<Vec<StatsHandle>>>;
+
+/// Filter of a pool in standby mode, accepting the lines of a listener only while no listener before is connected
+struct StandbyGate {
+    /// Listener the filter is applied to
+    listener: StatsHandle,
+
+    /// Listeners of the pool in the order of precedence
+    precedence: Precedence,
+}
+
+impl StandbyGate {
+    /// Create the gate of a listener, if a standby mode is used.
+    /// The gate is applied before the filters, so stateful filters, e.g. a `Deduplicator` shared with the active listener,
+    /// never see the lines of inactive listeners and the statistics only count the lines of the active listener.
+    fn create(precedence: Option<&Precedence>, listener: &Listener) -> Option<Arc<dyn Filter>> {
+        precedence.map(|precedence| {
+            Arc::new(StandbyGate {
+                listener: listener.stats_handle(),
+                precedence: precedence.clone(),
+            }) as Arc<dyn Filter>
+        })
+    }
+}
+
+impl Filter for StandbyGate {
+    fn accept(&self, _line: &ReceivedLine) -> bool {
+        self.precedence
+            .lock()
+            .unwrap()
+            .iter()
+            .take_while(|other| !other.same_listener(&self.listener))
+            .all(|other| !other.is_connected())
+    }
+}
+
+/// Listener of a pool together with its last known state
+struct Member {
+    /// Listener
+    listener: Listener,
+
+    /// State of the listener as of the last check
+    state: ListenerState,
+
+    /// Configuration the listener was created from, `None` if added with `ListenerPool::add`
+    #[cfg(feature = "config")]
+    origin: Option<Origin>,
+}
+
+impl Member {
+    /// Create a member not started yet.
+    fn new(listener: Listener) -> Self {
+        Self {
+            listener,
+            state: ListenerState::Stopped,
+            #[cfg(feature = "config")]
+            origin: None,
+        }
+    }
+
+    /// Start the listener and update its state.
+    async fn start(
+        &mut self,
+        channel: mpsc::UnboundedSender<ReceivedLine>,
+        connection_timeout: Duration,
+        precedence: Option<&Precedence>,
+    ) -> Result<(), ListenError> {
+        self.listener.gate = StandbyGate::create(precedence, &self.listener);
+        let res = self.listener.listen(channel, connection_timeout).await;
+        self.state = match &res {
+            Ok(()) => ListenerState::Running,
+            Err(err) => ListenerState::Failed(err.clone()),
+        };
+        res
+    }
+
+    /// Get the current state of the listener.
+    /// A listener which stopped on its own since the last check failed with its last error, if any.
+    fn current_state(&self) -> ListenerState {
+        if self.state != ListenerState::Running || self.listener.is_running() {
+            return self.state.clone();
+        }
+
+        match self.listener.stats().last_error {
+            Some(err) => ListenerState::Failed(err),
+            None => ListenerState::Stopped,
+        }
+    }
+
+    /// Stop the listener, if running, and wait until it is stopped.
+    #[cfg(feature = "config")]
+    async fn stop(&mut self) {
+        if self.state == ListenerState::Running {
+            // The listener may have stopped already on its own
+            let _ = self.listener.request_stop();
+            self.state = match self.listener.join().await {
+                Ok(()) => ListenerState::Stopped,
+                Err(err) => ListenerState::Failed(err),
+            };
+        }
+    }
+}
+
+/// Pool of listeners started and stopped together, e.g. to listen to multiple cluster servers in parallel.
+///
+/// All listeners deliver their lines into the same channel, each line is tagged with its `Source`.
+/// Set `Listener::label` to tell the listeners apart by name instead of by host and port.
+/// Set `mode` before starting the pool to use the first listener as primary and the others as backups.
+/// In a standby mode, the backup listeners are muted by their `Listener::gate`, which is kept when replacing the filters.
+#[derive(Default)]
+pub struct ListenerPool {
+    /// How the listeners deliver their lines
+    pub mode: PoolMode,
+
+    /// Listeners of the pool
+    members: Vec<Member>,
+
+    /// Listeners in the order of precedence, used by the standby modes
+    precedence: Precedence,
+
+    /// Channel and connection timeout to connect backup listeners on demand, set while listening
+    standby: Option<(mpsc::UnboundedSender<ReceivedLine>, Duration)>,
+
+    /// Listener to try first in the next round of connecting on demand
+    next_dial: usize,
+
+    /// Point in time of the next round of connecting on demand after a failed round, if any
+    dial_at: Option<time::Instant>,
+
+    /// Delay after the next failed round of connecting on demand
+    dial_backoff: Duration,
+
+    /// Channel for the lifecycle events of all listeners, if requested
+    events: Option<mpsc::UnboundedSender<Event>>,
+
+    /// Task delivering the lines to the sinks, if started with `listen_sinks`
+    sinks: Option<JoinHandle<()>>,
+
+    /// Channel of the sinks, open as long as any listener sends to it
+    sink_channel: Option<mpsc::WeakUnboundedSender<ReceivedLine>>,
+}
+
+impl ListenerPool {
+    /// Create a new empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a listener to the pool.
+    /// The listener is started with the next call to `listen`.
+    ///
+    /// # Arguments
+    ///
+    /// * `listener`: Listener to add, not yet listening
+    pub fn add(&mut self, mut listener: Listener) {
+        if let Some(events) = self.events.as_ref() {
+            listener.events = Some(events.clone());
+        }
+        self.members.push(Member::new(listener));
+        self.arrange();
+    }
+
+    /// Get a single channel for the lifecycle events of all listeners of the pool,
+    /// including listeners added later. Replaces the channel of a previous call.
+    /// Listeners already running report their events to the new channel after their next start only.
+    ///
+    /// # Result
+    ///
+    /// Returns the receiving end of the channel, which stays open as long as the pool exists.
+    pub fn events(&mut self) -> mpsc::UnboundedReceiver<Event> {
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        for member in self.members.iter_mut() {
+            member.listener.events = Some(events_tx.clone());
+        }
+        self.events = Some(events_tx);
+        events_rx
+    }
+
+    /// Create a pool with the listeners of a configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config`: Configuration of the pool
+    ///
+    /// # Result
+    ///
+    /// Returns the pool, not listening yet, or an error if the settings of a listener are invalid.
+    #[cfg(feature = "config")]
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let mut pool = Self {
+            mode: config.mode,
+            ..Self::default()
+        };
+        for listener in config.listeners.iter() {
+            pool.members.push(Member {
+                origin: Some(Origin::new(listener, config)),
+                ..Member::new(listener.build(config)?)
+            });
+        }
+        pool.arrange();
+        Ok(pool)
+    }
+
+    /// Apply a changed configuration to a pool created with `from_config`, without interrupting unchanged listeners.
+    ///
+    /// Listeners are identified by endpoint and callsign.
+    /// Listeners of new endpoints are created and started, listeners of removed endpoints are stopped and removed.
+    /// If only the filters of a listener changed, they apply to the next received line without reconnecting.
+    /// Listeners with other changed settings are restarted. Listeners added with `add` are left untouched.
+    /// In mode `OnDemand`, a listener is only started if none is running anymore.
+    ///
+    /// # Arguments
+    ///
+    /// * `config`: Changed configuration of the pool
+    /// * `channel`: Communication channel where the started listeners send their received spots to
+    ///
+    /// # Result
+    ///
+    /// Returns the applied changes, or an error if the settings of a listener are invalid, leaving the pool unchanged.
+    /// Listeners which failed to start are reported by `status`.
+    #[cfg(feature = "config")]
+    pub async fn reload(
+        &mut self,
+        config: &Config,
+        channel: mpsc::UnboundedSender<ReceivedLine>,
+    ) -> Result<ReloadSummary, ConfigError> {
+        // Create all listeners first to leave the pool unchanged if a setting is invalid
+        let mut wanted = Vec::with_capacity(config.listeners.len());
+        for listener in config.listeners.iter() {
+            let mut built = listener.build(config)?;
+            built.events = self.events.clone();
+            wanted.push((Origin::new(listener, config), built));
+        }
+
+        let mut summary = ReloadSummary::default();
+        let precedence = self.precedence();
+        let (mut members, mut previous): (Vec<_>, Vec<_>) = std::mem::take(&mut self.members)
+            .into_iter()
+            .partition(|member| member.origin.is_none());
+        let mut start = Vec::new();
+
+        for (origin, listener) in wanted {
+            let pos = previous.iter().position(|member| {
+                member
+                    .origin
+                    .as_ref()
+                    .is_some_and(|o| o.same_endpoint(&origin))
+            });
+            let Some(mut member) = pos.map(|pos| previous.remove(pos)) else {
+                summary.added.push(listener.to_string());
+                start.push(members.len());
+                members.push(Member {
+                    origin: Some(origin),
+                    ..Member::new(listener)
+                });
+                continue;
+            };
+
+            if member.origin.as_ref() == Some(&origin) {
+                members.push(member);
+            } else if member
+                .origin
+                .as_ref()
+                .is_some_and(|o| o.same_except_filters(&origin))
+            {
+                // Kept for the next start if the listener is not running
+                let _ = member.listener.set_filters(listener.filters);
+                member.origin = Some(origin);
+                if let Some(events) = self.events.as_ref() {
+                    let _ = events.send(Event::new(
+                        member.listener.source(),
+                        EventKind::FiltersUpdated,
+                    ));
+                }
+                summary.filters_updated.push(member.listener.to_string());
+                members.push(member);
+            } else {
+                member.stop().await;
+                summary.restarted.push(listener.to_string());
+                start.push(members.len());
+                members.push(Member {
+                    origin: Some(origin),
+                    ..Member::new(listener)
+                });
+            }
+        }
+
+        for mut member in previous {
+            member.stop().await;
+            summary.removed.push(member.listener.to_string());
+        }
+
+        self.members = members;
+        self.arrange();
+
+        // Failed listeners are reported by status
+        if self.mode == PoolMode::OnDemand {
+            if self.standby.is_some() {
+                self.standby = Some((channel, config.connection_timeout()));
+                if !self.is_listening() {
+                    self.dial_primary();
+                    self.dial().await;
+                }
+            }
+        } else {
+            for idx in start {
+                let _ = self.members[idx]
+                    .start(
+                        channel.clone(),
+                        config.connection_timeout(),
+                        precedence.as_ref(),
+                    )
+                    .await;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Number of listeners in the pool
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Check if the pool contains no listeners
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Iterate over the listeners of the pool, e.g. to send commands.
+    pub fn iter(&self) -> impl Iterator<Item = &Listener> {
+        self.members.iter().map(|member| &member.listener)
+    }
+
+    /// Iterate mutably over the listeners of the pool, e.g. to update the server-side filters.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Listener> {
+        self.members.iter_mut().map(|member| &mut member.listener)
+    }
+
+    /// Start all listeners of the pool not running yet.
+    /// The listeners are connected one after another.
+    /// In mode `OnDemand`, only the first listener which can be started is started, beginning with the primary,
+    /// unless a listener is running already. If none can be started, `reap` retries after a delay until the pool is stopped.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel`: Communication channel where all listeners send their received spots to
+    /// * `connection_timeout`: Connection timeout to each server
+    ///
+    /// # Result
+    ///
+    /// Returns `Ok(())` if all listeners are listening, otherwise the error of the first listener which failed to start.
+    /// The remaining listeners are started nevertheless, see `status` for the state of each listener.
+    pub async fn listen(
+        &mut self,
+        channel: mpsc::UnboundedSender<ReceivedLine>,
+        connection_timeout: Duration,
+    ) -> Result<(), ListenError> {
+        if self.mode == PoolMode::OnDemand {
+            self.standby = Some((channel, connection_timeout));
+            if self.is_listening() {
+                return Ok(());
+            }
+
+            self.dial_primary();
+            let failed = self.dial().await;
+            return match failed.into_iter().next() {
+                Some((_, err)) if !self.is_listening() => Err(err),
+                _ => Ok(()),
+            };
+        }
+
+        let precedence = self.precedence();
+        let mut res = Ok(());
+
+        for member in self.members.iter_mut() {
+            if member.state == ListenerState::Running {
+                continue;
+            }
+
+            let started = member
+                .start(channel.clone(), connection_timeout, precedence.as_ref())
+                .await;
+            if res.is_ok() {
+                res = started;
+            }
+        }
+
+        res
+    }
+
+    /// Start all listeners of the pool not running yet and deliver their lines to sinks, see `SinkRegistry::spawn`.
+    /// Use `sink_channel` to start further listeners, e.g. with `reload`, and `shutdown` to flush the sinks when done.
+    ///
+    /// # Arguments
+    ///
+    /// * `sinks`: Sinks to deliver the lines of all listeners to
+    /// * `connection_timeout`: Connection timeout to each server
+    ///
+    /// # Result
+    ///
+    /// Same as `listen`.
+    pub async fn listen_sinks(
+        &mut self,
+        sinks: SinkRegistry,
+        connection_timeout: Duration,
+    ) -> Result<(), ListenError> {
+        let (lines_tx, lines_rx) = mpsc::unbounded_channel();
+        self.sink_channel = Some(lines_tx.downgrade());
+        self.sinks = Some(sinks.spawn(lines_rx));
+        self.listen(lines_tx, connection_timeout).await
+    }
+
+    /// Get the channel of the sinks started with `listen_sinks`, as long as any listener sends to it.
+    pub fn sink_channel(&self) -> Option<mpsc::UnboundedSender<ReceivedLine>> {
+        self.sink_channel
+            .as_ref()
+            .and_then(|channel| channel.upgrade())
+    }
+
+    /// Stop all listeners and flush the sinks started with `listen_sinks`, within a timeout.
+    /// Listeners and sinks which did not stop in time are aborted, the lines left in the queues of aborted sinks are lost.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout`: Time to wait for all listeners to stop and all sinks to be flushed
+    ///
+    /// # Result
+    ///
+    /// Returns the final status of each listener and whether the sinks were flushed.
+    pub async fn shutdown(&mut self, timeout: Duration) -> ShutdownReport {
+        let deadline = time::Instant::now() + timeout;
+        self.standby = None;
+
+        for member in self.members.iter_mut() {
+            if member.state == ListenerState::Running {
+                // The listener may have stopped already on its own
+                let _ = member.listener.request_stop();
+            }
+        }
+
+        for member in self.members.iter_mut() {
+            if member.state == ListenerState::Running {
+                let remaining = deadline.saturating_duration_since(time::Instant::now());
+                member.state = match member.listener.join_timeout(remaining).await {
+                    Ok(()) => ListenerState::Stopped,
+                    Err(err) => ListenerState::Failed(err),
+                };
+            }
+        }
+
+        // The sinks are flushed once all listeners dropped the channel
+        self.sink_channel = None;
+        let sinks_flushed = match self.sinks.take() {
+            Some(mut sinks) => match time::timeout_at(deadline, &mut sinks).await {
+                Ok(res) => res.is_ok(),
+                Err(_) => {
+                    sinks.abort();
+                    false
+                }
+            },
+            None => true,
+        };
+
+        ShutdownReport {
+            listeners: self.status().listeners,
+            sinks_flushed,
+        }
+    }
+
+    /// Stop all running listeners of the pool and wait until they are stopped.
+    pub async fn stop(&mut self) {
+        self.standby = None;
+
+        for member in self.members.iter_mut() {
+            if member.state == ListenerState::Running {
+                // The listener may have stopped already on its own
+                let _ = member.listener.request_stop();
+            }
+        }
+
+        for member in self.members.iter_mut() {
+            if member.state == ListenerState::Running {
+                member.state = match member.listener.join().await {
+                    Ok(()) => ListenerState::Stopped,
+                    Err(err) => ListenerState::Failed(err),
+                };
+            }
+        }
+    }
+
+    /// Collect the listeners which stopped unexpectedly since the last check.
+    /// In mode `OnDemand`, the next listener is started if no listener is running anymore, see `PoolMode::OnDemand`.
+    ///
+    /// # Result
+    ///
+    /// Returns the index and the error of each listener which stopped or failed to start as backup,
+    /// in the order the listeners were added.
+    pub async fn reap(&mut self) -> Vec<(usize, ListenError)> {
+        let mut stopped = Vec::new();
+        let mut last = None;
+
+        for (idx, member) in self.members.iter_mut().enumerate() {
+            if member.state != ListenerState::Running || member.listener.is_running() {
+                continue;
+            }
+            last = Some(idx);
+
+            member.state = match member.listener.join().await {
+                Ok(()) => ListenerState::Stopped,
+                Err(err) => {
+                    stopped.push((idx, err.clone()));
+                    ListenerState::Failed(err)
+                }
+            };
+        }
+
+        if self.mode == PoolMode::OnDemand && !self.is_listening() {
+            // Continue with the listener after the one which stopped, the next round is delayed after a failed round
+            if let Some(idx) = last {
+                self.next_dial = idx + 1;
+            }
+            if self
+                .dial_at
+                .filter(|at| *at > time::Instant::now())
+                .is_none()
+            {
+                stopped.extend(self.dial().await);
+            }
+        }
+
+        stopped
+    }
+
+    /// Start the first listener which can be started, in mode `OnDemand`.
+    /// The listeners are tried once in a ring, beginning with `next_dial`.
+    /// If none can be started, the next round is delayed by a growing backoff.
+    ///
+    /// # Result
+    ///
+    /// Returns the index and the error of each listener which failed to start.
+    async fn dial(&mut self) -> Vec<(usize, ListenError)> {
+        let mut failed = Vec::new();
+        let Some((channel, connection_timeout)) = self.standby.clone() else {
+            return failed;
+        };
+        let precedence = self.precedence();
+        let len = self.members.len();
+
+        for offset in 0..len {
+            let idx = (self.next_dial + offset) % len;
+            match self.members[idx]
+                .start(channel.clone(), connection_timeout, precedence.as_ref())
+                .await
+            {
+                Ok(()) => {
+                    self.next_dial = idx;
+                    self.dial_at = None;
+                    self.dial_backoff = DIAL_BACKOFF;
+                    return failed;
+                }
+                Err(err) => failed.push((idx, err)),
+            }
+        }
+
+        let backoff = self.dial_backoff.max(DIAL_BACKOFF);
+        self.dial_at = Some(time::Instant::now() + backoff);
+        self.dial_backoff = (backoff * 2).min(MAX_DIAL_BACKOFF);
+        failed
+    }
+
+    /// Start connecting on demand with the primary listener, without delay.
+    fn dial_primary(&mut self) {
+        self.next_dial = 0;
+        self.dial_at = None;
+        self.dial_backoff = DIAL_BACKOFF;
+    }
+
+    /// Check if any listener of the pool is running.
+    fn is_listening(&self) -> bool {
+        self.members
+            .iter()
+            .any(|member| member.state == ListenerState::Running)
+    }
+
+    /// Listeners in the order of precedence, if a standby mode is used.
+    fn precedence(&self) -> Option<Precedence> {
+        (self.mode != PoolMode::All).then(|| self.precedence.clone())
+    }
+
+    /// Update the order of precedence after the listeners changed.
+    fn arrange(&mut self) {
+        *self.precedence.lock().unwrap() = self
+            .members
+            .iter()
+            .map(|member| member.listener.stats_handle())
+            .collect();
+    }
+
+    /// Get the health report of all listeners of the pool.
+    /// The state of each listener is read from the listener itself, so listeners which stopped since the last `reap` are reported as well.
+    /// Collecting them and starting backups is still left to `reap`.
+    ///
+    /// # Result
+    ///
+    /// Returns the health report of each listener, in the order the listeners were added.
+    pub fn health(&self) -> Vec<ListenerHealth> {
+        let now = Utc::now();
+        self.members
+            .iter()
+            .map(|member| {
+                let stats = member.listener.stats();
+                let state = member.current_state();
+                let last_error = match &state {
+                    ListenerState::Failed(err) => Some(err.clone()),
+                    _ => stats.last_error,
+                };
+
+                ListenerHealth {
+                    name: member.listener.to_string(),
+                    label: member.listener.label.clone(),
+                    state,
+                    connected_since: stats.connected_since,
+                    uptime_secs: stats
+                        .connected_since
+                        .map_or(0, |since| (now - since).num_seconds().max(0) as u64),
+                    spots_per_minute: stats.spots_per_minute,
+                    last_error,
+                    reconnects: stats.reconnects,
+                }
+            })
+            .collect()
+    }
+
+    /// Get the statistics merged across all listeners of the pool.
+    /// Listeners removed from the pool no longer count.
+    pub fn stats(&self) -> PoolStats {
+        let stats: Vec<_> = self
+            .members
+            .iter()
+            .map(|member| (&member.listener, member.listener.stats()))
+            .collect();
+
+        let received = stats.iter().map(|(_, stats)| stats.received).sum();
+        let spots = stats.iter().map(|(_, stats)| stats.spots).sum();
+        let ratio = |part: u64, total: u64| {
+            if total == 0 {
+                0.0
+            } else {
+                part as f64 / total as f64
+            }
+        };
+
+        PoolStats {
+            received,
+            spots,
+            dropped_lines: stats.iter().map(|(_, stats)| stats.dropped_lines).sum(),
+            dedup_ratio: ratio(received.saturating_sub(spots), received),
+            listeners: stats
+                .iter()
+                .map(|(listener, stats)| NodeContribution {
+                    name: listener.to_string(),
+                    label: listener.label.clone(),
+                    received: stats.received,
+                    spots: stats.spots,
+                    dropped_lines: stats.dropped_lines,
+                    share: ratio(stats.spots, spots),
+                })
+                .collect(),
+        }
+    }
+
+    /// Get the status of all listeners of the pool.
+    /// The state of each listener is read from the listener itself, see `health`.
+    pub fn status(&self) -> PoolStatus {
+        PoolStatus {
+            listeners: self
+                .members
+                .iter()
+                .map(|member| ListenerStatus {
+                    name: member.listener.to_string(),
+                    label: member.listener.label.clone(),
+                    state: member.current_state(),
+                    session_callsign: member.listener.session_callsign(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream};
+
+    // Time to wait for the listeners to react to the scripted servers
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Create a labeled listener talking to a scripted server.
+    fn listener(label: &str) -> (Listener, BufReader<DuplexStream>) {
+        let (mut listener, server) = Listener::duplex("N0CALL".into(), 4096);
+        listener.label = Some(label.into());
+        listener.auth_timeout = Duration::from_millis(100);
+        (listener, BufReader::new(server))
+    }
+
+    /// Send the login prompt and wait for the callsign sent back.
+    async fn login(server: &mut BufReader<DuplexStream>) {
+        server.write_all(b"login: ").await.unwrap();
+        let mut line = String::new();
+        time::timeout(TIMEOUT, server.read_line(&mut line))
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    /// Send a spot of the given callsign.
+    async fn spot(server: &mut BufReader<DuplexStream>, call: &str) {
+        let line = format!(
+            "DX de W3LPL:     14025.0  {:<13}CW 599                      1200Z\r\n",
+            call
+        );
+        server.write_all(line.as_bytes()).await.unwrap();
+    }
+
+    /// Wait until each listener of the pool is connected or not.
+    async fn wait_connected<const N: usize>(pool: &ListenerPool, connected: [bool; N]) {
+        time::timeout(TIMEOUT, async {
+            while pool
+                .health()
+                .iter()
+                .map(|health| health.connected_since.is_some())
+                .ne(connected)
+            {
+                time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    /// Receive the next delivered line and get the label of its listener and the spotted callsign.
+    async fn receive(rx: &mut mpsc::UnboundedReceiver<ReceivedLine>) -> (String, String) {
+        let line = time::timeout(TIMEOUT, rx.recv()).await.unwrap().unwrap();
+        let call = line.raw.split_whitespace().nth(4).unwrap_or_default();
+        (line.source.label.clone().unwrap(), call.into())
+    }
+
+    #[tokio::test]
+    async fn standby_failover() {
+        let (primary, mut primary_server) = listener("primary");
+        let (backup, mut backup_server) = listener("backup");
+        let mut pool = ListenerPool::new();
+        pool.mode = PoolMode::Standby;
+        pool.add(primary);
+        pool.add(backup);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        pool.listen(tx, TIMEOUT).await.unwrap();
+        login(&mut primary_server).await;
+        login(&mut backup_server).await;
+        wait_connected(&pool, [true, true]).await;
+
+        // The backup is muted while the primary is connected
+        spot(&mut backup_server, "DL1ABC").await;
+        time::sleep(Duration::from_millis(50)).await;
+        spot(&mut primary_server, "OK1ABC").await;
+        assert_eq!(receive(&mut rx).await, ("primary".into(), "OK1ABC".into()));
+
+        // The backup takes over after the primary lost its connection
+        drop(primary_server);
+        wait_connected(&pool, [false, true]).await;
+        spot(&mut backup_server, "G4ABC").await;
+        assert_eq!(receive(&mut rx).await, ("backup".into(), "G4ABC".into()));
+
+        // Only the delivered lines are counted
+        let stats = pool.stats();
+        assert_eq!(stats.listeners[0].spots, 1);
+        assert_eq!(stats.listeners[1].spots, 1);
+
+        pool.stop().await;
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn on_demand_failover() {
+        // Nothing listens on the port of the primary
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut primary = Listener::new("127.0.0.1".into(), port, "N0CALL".into());
+        primary.label = Some("primary".into());
+        let (backup, mut backup_server) = listener("backup");
+        let mut pool = ListenerPool::new();
+        pool.mode = PoolMode::OnDemand;
+        pool.add(primary);
+        pool.add(backup);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        pool.listen(tx, TIMEOUT).await.unwrap();
+
+        let status = pool.status();
+        assert!(matches!(
+            status.listeners[0].state,
+            ListenerState::Failed(_)
+        ));
+        assert_eq!(status.listeners[1].state, ListenerState::Running);
+
+        login(&mut backup_server).await;
+        spot(&mut backup_server, "DL1ABC").await;
+        assert_eq!(receive(&mut rx).await, ("backup".into(), "DL1ABC".into()));
+
+        pool.stop().await;
+    }
+
+    #[tokio::test]
+    async fn live_health() {
+        let (listener, mut server) = listener("local");
+        let mut pool = ListenerPool::new();
+        pool.add(listener);
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        pool.listen(tx, TIMEOUT).await.unwrap();
+        login(&mut server).await;
+        wait_connected(&pool, [true]).await;
+        assert_eq!(pool.health()[0].state, ListenerState::Running);
+
+        // The lost connection is reported without collecting the listener first
+        drop(server);
+        time::timeout(TIMEOUT, async {
+            while pool.status().running() > 0 {
+                time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let health = &pool.health()[0];
+        assert_eq!(
+            health.state,
+            ListenerState::Failed(ListenError::ConnectionLost)
+        );
+        assert_eq!(health.connected_since, None);
+        assert_eq!(health.uptime_secs, 0);
+        assert_eq!(pool.status().failed(), 1);
+        assert_eq!(pool.reap().await, [(0, ListenError::ConnectionLost)]);
+    }
+}
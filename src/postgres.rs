@@ -0,0 +1,215 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_postgres::{Client, NoTls};
+
+use crate::line::ReceivedLine;
+use crate::record::SpotRecord;
+use crate::sink::{SinkError, SpotSink};
+
+// Statements to create the database schema
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS spots (
+    id BIGSERIAL PRIMARY KEY,
+    received TIMESTAMPTZ NOT NULL,
+    time TIMESTAMPTZ NOT NULL,
+    spotter TEXT NOT NULL,
+    dx TEXT NOT NULL,
+    freq BIGINT NOT NULL,
+    band TEXT,
+    mode TEXT,
+    comment TEXT,
+    snr SMALLINT,
+    speed INTEGER,
+    skimmer BOOLEAN NOT NULL,
+    spotter_grid TEXT,
+    dx_grid TEXT,
+    spotter_dxcc INTEGER,
+    dx_dxcc INTEGER,
+    source TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS spots_time ON spots (time);
+CREATE INDEX IF NOT EXISTS spots_dx ON spots (dx);
+";
+
+// Statement to insert a spot
+const INSERT: &str = "
+INSERT INTO spots (
+    received, time, spotter, dx, freq, band, mode, comment, snr, speed,
+    skimmer, spotter_grid, dx_grid, spotter_dxcc, dx_dxcc, source
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+";
+
+// Default number of spots inserted by a single transaction
+const DEFAULT_BATCH_SIZE: usize = 500;
+
+// Default maximum number of spots buffered while the database is unavailable
+const DEFAULT_MAX_PENDING: usize = 100_000;
+
+// Default delay before reconnecting after a failure
+const DEFAULT_RETRY: Duration = Duration::from_secs(10);
+
+/// Sink inserting spots into the table `spots` of a PostgreSQL database.
+///
+/// The connection to the database is opened with the first batch and reopened after failures,
+/// independently of the connection to the cluster server. The schema is created by the sink if missing.
+/// Lines other than spots are ignored.
+///
+/// Spots are buffered and inserted in a single transaction once the batch is full or the sink is flushed.
+/// While the database is unavailable spots are kept and inserted after reconnecting,
+/// the oldest spots are dropped if more than `max_pending` spots are buffered.
+#[derive(Debug)]
+pub struct PostgresSink {
+    /// Connection parameters, e.g. `host=localhost user=dx dbname=spots` or `postgresql://dx@localhost/spots`
+    pub config: String,
+
+    /// Number of spots inserted by a single transaction
+    pub batch_size: usize,
+
+    /// Maximum number of spots buffered while the database is unavailable
+    pub max_pending: usize,
+
+    /// Delay before reconnecting after a failure
+    pub retry: Duration,
+
+    /// Connected client, if any
+    client: Option<Client>,
+
+    /// Point in time of the last failure, if any
+    failed: Option<Instant>,
+
+    /// Spots not yet inserted
+    pending: VecDeque<SpotRecord>,
+}
+
+impl PostgresSink {
+    /// Create a new sink. The database is not connected until the first batch is inserted.
+    ///
+    /// # Arguments
+    ///
+    /// * `config`: Connection parameters as key-value pairs or URL
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `PostgresSink`.
+    pub fn new(config: &str) -> Self {
+        Self {
+            config: config.into(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            max_pending: DEFAULT_MAX_PENDING,
+            retry: DEFAULT_RETRY,
+            client: None,
+            failed: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Get the connected client, connecting to the database and creating the schema if required.
+    async fn connect(&mut self) -> Result<&mut Client, SinkError> {
+        if self
+            .client
+            .as_ref()
+            .is_some_and(|client| client.is_closed())
+        {
+            self.client = None;
+        }
+
+        if self.client.is_none() {
+            let (client, connection) = tokio_postgres::connect(&self.config, NoTls)
+                .await
+                .map_err(backend)?;
+            tokio::spawn(connection);
+            client.batch_execute(SCHEMA).await.map_err(backend)?;
+            self.client = Some(client);
+        }
+
+        Ok(self.client.as_mut().unwrap())
+    }
+
+    /// Insert all pending spots in batches.
+    async fn insert(&mut self) -> Result<(), SinkError> {
+        while !self.pending.is_empty() {
+            let count = self.pending.len().min(self.batch_size.max(1));
+            let records: Vec<SpotRecord> = self.pending.range(..count).cloned().collect();
+
+            let client = self.connect().await?;
+            let tx = client.transaction().await.map_err(backend)?;
+            let stmt = tx.prepare(INSERT).await.map_err(backend)?;
+            for record in records.iter() {
+                tx.execute(
+                    &stmt,
+                    &[
+                        &record.received,
+                        &record.time,
+                        &record.spotter,
+                        &record.dx,
+                        &(record.freq as i64),
+                        &record.band.map(|band| band.to_string()),
+                        &record.mode.map(|mode| mode.to_string()),
+                        &record.comment,
+                        &record.snr,
+                        &record.speed.map(i32::from),
+                        &record.skimmer,
+                        &record.spotter_grid,
+                        &record.dx_grid,
+                        &record.spotter_dxcc.map(i32::from),
+                        &record.dx_dxcc.map(i32::from),
+                        &record.source,
+                    ],
+                )
+                .await
+                .map_err(backend)?;
+            }
+            tx.commit().await.map_err(backend)?;
+
+            self.pending.drain(..count);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SpotSink for PostgresSink {
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        if let Some(record) = SpotRecord::from_line(&line) {
+            if self.pending.len() >= self.max_pending {
+                self.pending.pop_front();
+            }
+            self.pending.push_back(record);
+
+            let waiting = self
+                .failed
+                .is_some_and(|failed| failed.elapsed() < self.retry);
+            if self.pending.len() >= self.batch_size && !waiting {
+                self.flush().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), SinkError> {
+        match self.insert().await {
+            Ok(()) => {
+                self.failed = None;
+                Ok(())
+            }
+            Err(err) => {
+                self.client = None;
+                self.failed = Some(Instant::now());
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Convert a database error.
+fn backend(err: tokio_postgres::Error) -> SinkError {
+    SinkError::Backend(err.to_string())
+}
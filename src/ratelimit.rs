@@ -0,0 +1,175 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant};
+
+use crate::line::ReceivedLine;
+
+/// Lines to drop if the queue of a rate limiter is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Drop the oldest queued lines in favor of new ones
+    #[default]
+    DropOldest,
+
+    /// Drop new lines until the queue drains
+    DropNewest,
+}
+
+/// Rate limiting stage between two channels, e.g. for consumers not able to handle the spot rate of the RBN.
+///
+/// Lines are forwarded immediately as long as the burst allows, afterwards with the given rate.
+/// Lines exceeding the rate are queued, if the queue is full lines are dropped according to the overflow policy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimiter {
+    /// Maximum number of forwarded lines per second
+    pub rate: f64,
+
+    /// Number of lines forwarded at once before the rate applies
+    pub burst: u32,
+
+    /// Maximum number of queued lines
+    pub queue: usize,
+
+    /// Lines to drop if the queue is full
+    pub overflow: Overflow,
+
+    /// Forward lines tagged with priority, e.g. by a `Watchlist`, immediately.
+    /// Only used by `RateLimiter::spawn_lines`.
+    pub bypass_priority: bool,
+}
+
+impl RateLimiter {
+    /// Create a new rate limiter with a queue for one second of lines, dropping the oldest lines on overflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate`: Maximum number of forwarded lines per second
+    /// * `burst`: Number of lines forwarded at once before the rate applies
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `RateLimiter`.
+    pub fn new(rate: f64, burst: u32) -> Self {
+        Self {
+            rate,
+            burst,
+            queue: rate.ceil().max(1.0) as usize,
+            overflow: Overflow::default(),
+            bypass_priority: false,
+        }
+    }
+
+    /// Start forwarding lines from one channel to another.
+    /// The forwarding stops after the input channel is closed and all queued lines are forwarded,
+    /// or after the output channel is closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: Channel to receive lines from, e.g. from one or more listeners
+    /// * `output`: Channel to forward lines to
+    ///
+    /// # Result
+    ///
+    /// Returns the handle of the forwarding task.
+    pub fn spawn<T: Send + 'static>(
+        &self,
+        input: mpsc::UnboundedReceiver<T>,
+        output: mpsc::UnboundedSender<T>,
+    ) -> JoinHandle<()> {
+        self.forward(input, output, |_| false)
+    }
+
+    /// Start forwarding received lines from one channel to another.
+    /// Like `RateLimiter::spawn`, but lines tagged with priority bypass the rate limit if enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: Channel to receive lines from, e.g. from one or more listeners
+    /// * `output`: Channel to forward lines to
+    ///
+    /// # Result
+    ///
+    /// Returns the handle of the forwarding task.
+    pub fn spawn_lines(
+        &self,
+        input: mpsc::UnboundedReceiver<ReceivedLine>,
+        output: mpsc::UnboundedSender<ReceivedLine>,
+    ) -> JoinHandle<()> {
+        let bypass = self.bypass_priority;
+        self.forward(input, output, move |line| bypass && line.priority)
+    }
+
+    /// Spawn the forwarding task, where lines matching `priority` are forwarded immediately.
+    fn forward<T, P>(
+        &self,
+        mut input: mpsc::UnboundedReceiver<T>,
+        output: mpsc::UnboundedSender<T>,
+        priority: P,
+    ) -> JoinHandle<()>
+    where
+        T: Send + 'static,
+        P: Fn(&T) -> bool + Send + 'static,
+    {
+        let rate = self.rate.max(f64::MIN_POSITIVE);
+        let burst = f64::from(self.burst.max(1));
+        let capacity = self.queue;
+        let overflow = self.overflow;
+
+        tokio::spawn(async move {
+            let mut queue = VecDeque::new();
+            let mut tokens = burst;
+            let mut refilled = Instant::now();
+            let mut open = true;
+
+            loop {
+                let now = Instant::now();
+                tokens = (tokens + now.duration_since(refilled).as_secs_f64() * rate).min(burst);
+                refilled = now;
+
+                while tokens >= 1.0 {
+                    match queue.pop_front() {
+                        Some(line) => {
+                            if output.send(line).is_err() {
+                                return;
+                            }
+                            tokens -= 1.0;
+                        }
+                        None => break,
+                    }
+                }
+
+                while queue.len() > capacity {
+                    match overflow {
+                        Overflow::DropOldest => queue.pop_front(),
+                        Overflow::DropNewest => queue.pop_back(),
+                    };
+                }
+
+                if !open && queue.is_empty() {
+                    return;
+                }
+
+                let wait = Duration::from_secs_f64((1.0 - tokens).max(0.0) / rate);
+                tokio::select! {
+                    line = input.recv(), if open => match line {
+                        Some(line) if priority(&line) => {
+                            if output.send(line).is_err() {
+                                return;
+                            }
+                        }
+                        Some(line) => queue.push_back(line),
+                        None => open = false,
+                    },
+                    _ = time::sleep(wait), if !queue.is_empty() => (),
+                    _ = output.closed() => return,
+                }
+            }
+        })
+    }
+}
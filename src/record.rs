@@ -0,0 +1,106 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chrono::{DateTime, Utc};
+
+use crate::band::{Band, Mode};
+use crate::line::ReceivedLine;
+use crate::message::ClusterMessage;
+
+/// Flat record of a spot with all derived information, e.g. for a row of a database table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpotRecord {
+    /// Point in time the spot was received
+    pub received: DateTime<Utc>,
+
+    /// Point in time of the spot as sent by the server, or the time of reception if unknown
+    pub time: DateTime<Utc>,
+
+    /// Callsign of the spotter
+    pub spotter: String,
+
+    /// Callsign of the spotted station
+    pub dx: String,
+
+    /// Frequency in Hz
+    pub freq: u64,
+
+    /// Band, if known
+    pub band: Option<Band>,
+
+    /// Mode, if known
+    pub mode: Option<Mode>,
+
+    /// Comment of a spot or additional information of a skimmer spot, if any
+    pub comment: Option<String>,
+
+    /// Signal to noise ratio in dB, if known
+    pub snr: Option<i16>,
+
+    /// Speed in words per minute or baud, if sent by a skimmer
+    pub speed: Option<u16>,
+
+    /// True if the spot was sent by a skimmer
+    pub skimmer: bool,
+
+    /// Maidenhead locator of the spotter, if known
+    pub spotter_grid: Option<String>,
+
+    /// Maidenhead locator of the spotted station, if known
+    pub dx_grid: Option<String>,
+
+    /// DXCC entity number of the spotter, if added by an enricher
+    pub spotter_dxcc: Option<u16>,
+
+    /// DXCC entity number of the spotted station, if added by an enricher
+    pub dx_dxcc: Option<u16>,
+
+    /// Listener the spot was received by
+    pub source: String,
+}
+
+impl SpotRecord {
+    /// Create the record of a received spot.
+    ///
+    /// # Arguments
+    ///
+    /// * `line`: Received line
+    ///
+    /// # Result
+    ///
+    /// Returns the record or `None` if the line is not a parsed spot.
+    pub fn from_line(line: &ReceivedLine) -> Option<Self> {
+        let parsed = line.parsed.as_ref()?;
+        let (spotter, dx) = parsed.calls()?;
+        let (comment, speed) = match parsed {
+            ClusterMessage::DxSpot(spot) => (spot.comment.clone(), None),
+            ClusterMessage::SkimmerSpot(spot) => (spot.info.clone(), spot.speed),
+            _ => (None, None),
+        };
+        let snr = match parsed {
+            ClusterMessage::SkimmerSpot(spot) => Some(spot.snr),
+            _ => parsed.digital().and_then(|digital| digital.snr),
+        };
+
+        Some(Self {
+            received: line.received,
+            time: line.timestamp.unwrap_or(line.received),
+            spotter: spotter.to_string(),
+            dx: dx.to_string(),
+            freq: parsed.freq()?,
+            band: line.band,
+            mode: line.mode,
+            comment,
+            snr,
+            speed,
+            skimmer: matches!(parsed, ClusterMessage::SkimmerSpot(_)),
+            spotter_grid: line.spotter_grid.clone(),
+            dx_grid: line.dx_grid.clone(),
+            spotter_dxcc: line.spotter_entity.as_ref().map(|entity| entity.dxcc),
+            dx_dxcc: line.dx_entity.as_ref().map(|entity| entity.dxcc),
+            source: line.source.to_string(),
+        })
+    }
+}
@@ -0,0 +1,109 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::streams::StreamMaxlen;
+use redis::AsyncCommands;
+
+use crate::line::ReceivedLine;
+use crate::sink::{SinkError, SpotSink};
+
+// Field of stream entries holding the spot
+const STREAM_FIELD: &str = "spot";
+
+/// Delivery of spots to Redis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedisMode {
+    /// Publish each spot to a channel with `PUBLISH`, only received by connected subscribers
+    Publish,
+
+    /// Append each spot to a stream with `XADD`, allowing consumers to replay past spots.
+    /// The stream is trimmed to about the given number of entries, if any.
+    Stream(Option<usize>),
+}
+
+/// Sink delivering each spot as JSON to Redis, either to a pub/sub channel or to a stream.
+///
+/// Stream entries hold the spot in the field `spot`. Lines other than spots are ignored.
+/// The connection is reestablished automatically after failures, spots delivered meanwhile are lost.
+pub struct RedisSink {
+    /// Name of the channel or key of the stream
+    pub key: String,
+
+    /// Delivery of the spots
+    pub mode: RedisMode,
+
+    /// Connection to the server
+    conn: ConnectionManager,
+}
+
+impl RedisSink {
+    /// Connect to a Redis server.
+    ///
+    /// # Arguments
+    ///
+    /// * `url`: URL of the server, e.g. `redis://127.0.0.1/`
+    /// * `key`: Name of the channel or key of the stream
+    /// * `mode`: Delivery of the spots
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `RedisSink` or `SinkError::Backend` if the connection failed.
+    pub async fn connect(url: &str, key: &str, mode: RedisMode) -> Result<Self, SinkError> {
+        let client = redis::Client::open(url).map_err(backend)?;
+        let conn = client.get_connection_manager().await.map_err(backend)?;
+
+        Ok(Self {
+            key: key.into(),
+            mode,
+            conn,
+        })
+    }
+}
+
+impl std::fmt::Debug for RedisSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisSink")
+            .field("key", &self.key)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl SpotSink for RedisSink {
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        if line.parsed.as_ref().and_then(|msg| msg.calls()).is_none() {
+            return Ok(());
+        }
+
+        let json = line.to_json();
+        match self.mode {
+            RedisMode::Publish => self.conn.publish(&self.key, json).await,
+            RedisMode::Stream(Some(len)) => {
+                self.conn
+                    .xadd_maxlen(
+                        &self.key,
+                        StreamMaxlen::Approx(len),
+                        "*",
+                        &[(STREAM_FIELD, json)],
+                    )
+                    .await
+            }
+            RedisMode::Stream(None) => {
+                self.conn
+                    .xadd(&self.key, "*", &[(STREAM_FIELD, json)])
+                    .await
+            }
+        }
+        .map(|_: redis::Value| ())
+        .map_err(backend)
+    }
+}
+
+/// Convert a Redis error.
+fn backend(err: redis::RedisError) -> SinkError {
+    SinkError::Backend(err.to_string())
+}
@@ -0,0 +1,192 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tokio::time;
+
+use crate::line::ReceivedLine;
+
+// Prompt asking clients for their callsign, as expected by the listener
+const LOGIN_PROMPT: &str = "login: ";
+
+// Commands of clients to close the connection
+const QUIT_COMMANDS: [&str; 3] = ["BYE", "QUIT", "Q"];
+
+// Maximum length of a callsign sent by a client
+const MAX_CALLSIGN_LEN: usize = 15;
+
+/// Relay server re-serving received lines to telnet clients, acting as a minimal cluster node.
+///
+/// Clients are asked for their callsign with `login:`, just like a cluster server would do,
+/// and receive every line passed to the relay afterwards. Commands sent by clients are ignored,
+/// except for `bye` and `quit` to close the connection.
+/// Clients not keeping up with the stream miss lines instead of slowing down the relay.
+#[derive(Debug, Clone)]
+pub struct Relay {
+    /// Address to listen on, e.g. `0.0.0.0:7300`
+    pub addr: SocketAddr,
+
+    /// Text sent to clients before the login prompt
+    pub greeting: String,
+
+    /// Name of the node shown to clients after the login
+    pub node_call: String,
+
+    /// Maximum number of connected clients, further clients are refused
+    pub max_clients: usize,
+
+    /// Time clients have to send their callsign
+    pub login_timeout: Duration,
+
+    /// Number of lines buffered per client
+    pub queue: usize,
+}
+
+impl Relay {
+    /// Create a new relay with default settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr`: Address to listen on
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `Relay`.
+    pub fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            greeting: "Welcome to the dxcllistener relay".into(),
+            node_call: "RELAY".into(),
+            max_clients: 100,
+            login_timeout: Duration::from_secs(60),
+            queue: 1000,
+        }
+    }
+
+    /// Start listening for clients and relay the received lines to them.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: Channel of the received lines, e.g. the merged output of multiple listeners
+    ///
+    /// # Result
+    ///
+    /// Returns the handle of the relay task or an error if listening on the address failed.
+    /// The relay stops and disconnects all clients once the input channel is closed or the task is aborted.
+    pub async fn spawn(
+        self,
+        mut input: mpsc::UnboundedReceiver<ReceivedLine>,
+    ) -> Result<JoinHandle<()>, std::io::Error> {
+        let listener = TcpListener::bind(self.addr).await?;
+        let (lines, _) = broadcast::channel::<Arc<str>>(self.queue.max(1));
+        let settings = Arc::new(self);
+
+        Ok(tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    line = input.recv() => match line {
+                        Some(line) => {
+                            let _ = lines.send(line.raw.into());
+                        }
+                        None => break,
+                    },
+                    client = listener.accept() => {
+                        if let Ok((stream, _)) = client {
+                            let full = lines.receiver_count() >= settings.max_clients;
+                            tokio::spawn(serve(stream, lines.subscribe(), settings.clone(), full));
+                        }
+                    }
+                }
+            }
+        }))
+    }
+}
+
+/// Serve a single client until it disconnects or the relay stops.
+async fn serve(
+    stream: TcpStream,
+    mut lines: broadcast::Receiver<Arc<str>>,
+    settings: Arc<Relay>,
+    full: bool,
+) -> Result<(), std::io::Error> {
+    let (read, mut write) = stream.into_split();
+    let mut read = BufReader::new(read);
+    let mut buf = Vec::new();
+
+    if full {
+        write
+            .write_all(b"Connection refused, too many users\r\n")
+            .await?;
+        return Ok(());
+    }
+
+    write
+        .write_all(format!("{}\r\n\r\n{}", settings.greeting, LOGIN_PROMPT).as_bytes())
+        .await?;
+
+    let callsign =
+        match time::timeout(settings.login_timeout, read.read_until(b'\n', &mut buf)).await {
+            Ok(Ok(len)) if len > 0 => callsign(&buf),
+            _ => return Ok(()),
+        };
+    let callsign = match callsign {
+        Some(callsign) => callsign,
+        None => {
+            write.write_all(b"Invalid callsign\r\n").await?;
+            return Ok(());
+        }
+    };
+    write
+        .write_all(
+            format!(
+                "Hello {}, this is {}\r\n{} de {} >\r\n",
+                callsign, settings.node_call, callsign, settings.node_call
+            )
+            .as_bytes(),
+        )
+        .await?;
+
+    buf.clear();
+    loop {
+        tokio::select! {
+            line = lines.recv() => match line {
+                Ok(line) => write.write_all(format!("{}\r\n", line).as_bytes()).await?,
+                Err(broadcast::error::RecvError::Lagged(_)) => (),
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            len = read.read_until(b'\n', &mut buf) => {
+                let command = String::from_utf8_lossy(&buf).trim().to_ascii_uppercase();
+                if len? == 0 || QUIT_COMMANDS.contains(&command.as_str()) {
+                    break;
+                }
+                buf.clear();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Extract the callsign from the login line of a client, ignoring telnet negotiation and control characters.
+fn callsign(data: &[u8]) -> Option<String> {
+    let callsign: String = data
+        .iter()
+        .filter(|byte| byte.is_ascii_graphic())
+        .map(|byte| byte.to_ascii_uppercase() as char)
+        .collect();
+
+    let valid = !callsign.is_empty()
+        && callsign.len() <= MAX_CALLSIGN_LEN
+        && callsign
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '/' || c == '-' || c == '#');
+
+    valid.then_some(callsign)
+}
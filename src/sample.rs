@@ -0,0 +1,91 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::band::Band;
+use crate::filter::Filter;
+use crate::line::ReceivedLine;
+
+/// Deterministic sampling of spots, e.g. for statistics only requiring a representative subset of a high-volume feed.
+///
+/// Of every `n` spots on a band the first one is accepted and the others are dropped, spots outside of the known bands are counted together.
+/// The rate applies to all bands unless overridden per band, a rate of `0` drops all spots of a band.
+/// Lines which are not spots are always accepted.
+///
+/// Clones of a sampler share their counters.
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    /// Accept one of every `n` spots
+    every: u64,
+
+    /// Rates overriding the default rate per band
+    bands: HashMap<Band, u64>,
+
+    /// Number of spots seen per band
+    counters: Arc<Mutex<HashMap<Option<Band>, u64>>>,
+}
+
+impl Sampler {
+    /// Create a new sampler.
+    ///
+    /// # Arguments
+    ///
+    /// * `every`: Accept one of every `every` spots of a band
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `Sampler`.
+    pub fn new(every: u64) -> Self {
+        Self {
+            every,
+            bands: HashMap::new(),
+            counters: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Override the rate for a single band.
+    ///
+    /// # Arguments
+    ///
+    /// * `band`: Band
+    /// * `every`: Accept one of every `every` spots of the band
+    pub fn with_band(mut self, band: Band, every: u64) -> Self {
+        self.bands.insert(band, every);
+        self
+    }
+
+    /// Reset the counters of all bands.
+    pub fn reset(&self) {
+        self.counters.lock().unwrap().clear();
+    }
+}
+
+impl Filter for Sampler {
+    fn accept(&self, line: &ReceivedLine) -> bool {
+        if line.parsed.as_ref().and_then(|msg| msg.freq()).is_none() {
+            return true;
+        }
+
+        let every = line
+            .band
+            .and_then(|band| self.bands.get(&band).copied())
+            .unwrap_or(self.every);
+        if every == 0 {
+            return false;
+        }
+
+        let mut counters = self.counters.lock().unwrap();
+        let counter = counters.entry(line.band).or_default();
+        let accept = counter.is_multiple_of(every);
+        *counter = counter.wrapping_add(1);
+
+        accept
+    }
+
+    fn is_stateful(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,79 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_serial::{DataBits, FlowControl, Parity, SerialPortBuilderExt, SerialStream, StopBits};
+
+/// Transport reading the cluster feed from a serial port, e.g. of a packet TNC connected to a cluster node.
+///
+/// The port is configured for raw 8N1 communication with the given baud rate.
+/// Use `Listener::listen_on` to process the received lines like those of a TCP connection.
+#[derive(Debug)]
+pub struct SerialTransport {
+    /// Opened serial port
+    port: SerialStream,
+}
+
+impl SerialTransport {
+    /// Open and configure a serial port.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: Path of the serial port, e.g. `/dev/ttyUSB0` or `COM3`
+    /// * `baud_rate`: Baud rate, e.g. `9600`
+    /// * `flow_control`: Enable hardware flow control using RTS and CTS
+    ///
+    /// # Result
+    ///
+    /// Returns the transport, or an error if the port could not be opened or the baud rate is not supported.
+    /// Must be called within a tokio runtime.
+    pub fn open<P: AsRef<Path>>(path: P, baud_rate: u32, flow_control: bool) -> io::Result<Self> {
+        let flow_control = if flow_control {
+            FlowControl::Hardware
+        } else {
+            FlowControl::None
+        };
+
+        let port = tokio_serial::new(path.as_ref().to_string_lossy(), baud_rate)
+            .data_bits(DataBits::Eight)
+            .parity(Parity::None)
+            .stop_bits(StopBits::One)
+            .flow_control(flow_control)
+            .open_native_async()?;
+
+        Ok(Self { port })
+    }
+}
+
+impl AsyncRead for SerialTransport {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.port).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for SerialTransport {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.port).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.port).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.port).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,352 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::band::{Band, Mode};
+use crate::flavor::ClusterFlavor;
+use crate::listener::ListenError;
+
+// Number of filter slots per action supported by DXSpider
+const DXSPIDER_SLOTS: usize = 9;
+
+// Bands covered by the band-mode filter of AR-Cluster if no band is given
+const BANDS: [Band; 13] = [
+    Band::M160,
+    Band::M80,
+    Band::M60,
+    Band::M40,
+    Band::M30,
+    Band::M20,
+    Band::M17,
+    Band::M15,
+    Band::M12,
+    Band::M10,
+    Band::M6,
+    Band::M4,
+    Band::M2,
+];
+
+/// Action of a server-side filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterAction {
+    /// Only spots matching the filter are sent
+    Accept,
+
+    /// Spots matching the filter are not sent
+    Reject,
+}
+
+/// Server-side spot filter, i.e. a filter applied by the cluster node before sending spots.
+///
+/// A spot matches the filter if it matches all given criteria, where a criterion with multiple values matches any of them.
+/// The commands to set up the filter differ between the cluster server implementations, see `ServerFilter::commands`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerFilter {
+    /// Action of the filter
+    pub action: FilterAction,
+
+    /// Bands of the spot
+    pub bands: Vec<Band>,
+
+    /// Modes of the spot
+    pub modes: Vec<Mode>,
+
+    /// Callsign prefixes of the spotted station
+    pub dx_prefixes: Vec<String>,
+
+    /// DXCC entities of the spotted station
+    pub dx_dxcc: Vec<u16>,
+
+    /// CQ zones of the spotted station
+    pub dx_zones: Vec<u8>,
+
+    /// Callsign prefixes of the spotter
+    pub spotter_prefixes: Vec<String>,
+
+    /// DXCC entities of the spotter
+    pub spotter_dxcc: Vec<u16>,
+
+    /// CQ zones of the spotter
+    pub spotter_zones: Vec<u8>,
+}
+
+impl ServerFilter {
+    /// Create a filter with the given action matching all spots.
+    pub fn new(action: FilterAction) -> Self {
+        Self {
+            action,
+            bands: Vec::new(),
+            modes: Vec::new(),
+            dx_prefixes: Vec::new(),
+            dx_dxcc: Vec::new(),
+            dx_zones: Vec::new(),
+            spotter_prefixes: Vec::new(),
+            spotter_dxcc: Vec::new(),
+            spotter_zones: Vec::new(),
+        }
+    }
+
+    /// Create a filter accepting only spots matching it.
+    pub fn accept() -> Self {
+        Self::new(FilterAction::Accept)
+    }
+
+    /// Create a filter rejecting spots matching it.
+    pub fn reject() -> Self {
+        Self::new(FilterAction::Reject)
+    }
+
+    /// Match spots on a band.
+    pub fn band(mut self, band: Band) -> Self {
+        self.bands.push(band);
+        self
+    }
+
+    /// Match spots of a mode.
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.modes.push(mode);
+        self
+    }
+
+    /// Match spots of stations with a callsign starting with the prefix.
+    pub fn dx_prefix(mut self, prefix: &str) -> Self {
+        self.dx_prefixes.push(prefix.to_uppercase());
+        self
+    }
+
+    /// Match spots of stations within a DXCC entity.
+    pub fn dx_dxcc(mut self, dxcc: u16) -> Self {
+        self.dx_dxcc.push(dxcc);
+        self
+    }
+
+    /// Match spots of stations within a CQ zone.
+    pub fn dx_zone(mut self, zone: u8) -> Self {
+        self.dx_zones.push(zone);
+        self
+    }
+
+    /// Match spots of spotters with a callsign starting with the prefix.
+    pub fn spotter_prefix(mut self, prefix: &str) -> Self {
+        self.spotter_prefixes.push(prefix.to_uppercase());
+        self
+    }
+
+    /// Match spots of spotters within a DXCC entity.
+    pub fn spotter_dxcc(mut self, dxcc: u16) -> Self {
+        self.spotter_dxcc.push(dxcc);
+        self
+    }
+
+    /// Match spots of spotters within a CQ zone.
+    pub fn spotter_zone(mut self, zone: u8) -> Self {
+        self.spotter_zones.push(zone);
+        self
+    }
+
+    /// Create the commands to set up the filter.
+    ///
+    /// - DXSpider: A single `accept/spots` or `reject/spots` command in the given slot, e.g. `accept/spots 1 on 20m and by_zone 14`
+    /// - AR-Cluster: A `set/filter` command per criterion, e.g. `set/filter dxbm/pass 20-CW`.
+    ///   Zones and DXCC entity numbers are not supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `flavor`: Software of the cluster server
+    /// * `slot`: Slot of the filter, starting at 1, only used by DXSpider
+    ///
+    /// # Result
+    ///
+    /// Returns the commands or `ListenError::InvalidFilter` if the filter is not supported by the server software.
+    pub fn commands(&self, flavor: ClusterFlavor, slot: usize) -> Result<Vec<String>, ListenError> {
+        match flavor {
+            ClusterFlavor::DxSpider => self.dxspider(slot).map(|cmd| vec![cmd]),
+            ClusterFlavor::ArCluster => self.arcluster(),
+            ClusterFlavor::Generic | ClusterFlavor::Rbn => Err(unsupported(flavor)),
+        }
+    }
+
+    /// Create the DXSpider command of the filter.
+    fn dxspider(&self, slot: usize) -> Result<String, ListenError> {
+        if !(1..=DXSPIDER_SLOTS).contains(&slot) {
+            return Err(ListenError::InvalidFilter(format!(
+                "slot {} out of range 1 to {}",
+                slot, DXSPIDER_SLOTS
+            )));
+        }
+
+        let mut criteria = Vec::new();
+        if !self.bands.is_empty() {
+            criteria.push(format!("on {}", join(&self.bands)));
+        }
+        if !self.modes.is_empty() {
+            let modes = self
+                .modes
+                .iter()
+                .map(|mode| match mode {
+                    Mode::Cw => Ok("hf/cw"),
+                    Mode::Ssb => Ok("hf/ssb"),
+                    Mode::Digi => Ok("hf/data"),
+                    Mode::Am | Mode::Fm => Err(ListenError::InvalidFilter(format!(
+                        "mode {} not supported by DXSpider",
+                        mode
+                    ))),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            criteria.push(format!("on {}", modes.join(",")));
+        }
+        for (keyword, values) in [
+            ("call", join(&self.dx_prefixes)),
+            ("call_dxcc", join(&self.dx_dxcc)),
+            ("call_zone", join(&self.dx_zones)),
+            ("by", join(&self.spotter_prefixes)),
+            ("by_dxcc", join(&self.spotter_dxcc)),
+            ("by_zone", join(&self.spotter_zones)),
+        ] {
+            if !values.is_empty() {
+                criteria.push(format!("{} {}", keyword, values));
+            }
+        }
+
+        if criteria.is_empty() {
+            return Err(ListenError::InvalidFilter("filter without criteria".into()));
+        }
+
+        let action = match self.action {
+            FilterAction::Accept => "accept",
+            FilterAction::Reject => "reject",
+        };
+        Ok(format!(
+            "{}/spots {} {}",
+            action,
+            slot,
+            criteria.join(" and ")
+        ))
+    }
+
+    /// Create the AR-Cluster commands of the filter.
+    fn arcluster(&self) -> Result<Vec<String>, ListenError> {
+        if !self.dx_dxcc.is_empty()
+            || !self.dx_zones.is_empty()
+            || !self.spotter_dxcc.is_empty()
+            || !self.spotter_zones.is_empty()
+        {
+            return Err(ListenError::InvalidFilter(
+                "zones and DXCC entities not supported by AR-Cluster".into(),
+            ));
+        }
+
+        let action = match self.action {
+            FilterAction::Accept => "pass",
+            FilterAction::Reject => "reject",
+        };
+        let mut commands = Vec::new();
+
+        if !self.bands.is_empty() || !self.modes.is_empty() {
+            let bands: Vec<Band> = if self.bands.is_empty() {
+                BANDS.to_vec()
+            } else {
+                self.bands.clone()
+            };
+            let modes = if self.modes.is_empty() {
+                vec![Mode::Cw, Mode::Ssb, Mode::Digi]
+            } else {
+                self.modes.clone()
+            };
+
+            let mut values = Vec::new();
+            for band in bands.iter() {
+                let band = band.to_string().to_uppercase();
+                let band = band
+                    .strip_suffix("CM")
+                    .map_or_else(|| band.trim_end_matches('M').to_string(), |_| band.clone());
+                for mode in modes.iter() {
+                    let mode = match mode {
+                        Mode::Cw => "CW",
+                        Mode::Ssb => "SSB",
+                        Mode::Digi => "RTTY",
+                        Mode::Am | Mode::Fm => {
+                            return Err(ListenError::InvalidFilter(format!(
+                                "mode {} not supported by AR-Cluster",
+                                mode
+                            )))
+                        }
+                    };
+                    values.push(format!("{}-{}", band, mode));
+                }
+            }
+            commands.push(format!("set/filter dxbm/{} {}", action, values.join(",")));
+        }
+        if !self.dx_prefixes.is_empty() {
+            commands.push(format!(
+                "set/filter dxcty/{} {}",
+                action,
+                join(&self.dx_prefixes)
+            ));
+        }
+        if !self.spotter_prefixes.is_empty() {
+            commands.push(format!(
+                "set/filter doc/{} {}",
+                action,
+                join(&self.spotter_prefixes)
+            ));
+        }
+
+        if commands.is_empty() {
+            return Err(ListenError::InvalidFilter("filter without criteria".into()));
+        }
+
+        Ok(commands)
+    }
+}
+
+/// Create the commands to replace all server-side filters by the given ones.
+/// Existing filters are cleared first, filters of the same action are numbered consecutively.
+///
+/// # Arguments
+///
+/// * `filters`: Filters to set up, may be empty to only clear existing filters
+/// * `flavor`: Software of the cluster server
+///
+/// # Result
+///
+/// Returns the commands or `ListenError::InvalidFilter` if a filter is not supported by the server software.
+pub fn server_filter_commands(
+    filters: &[ServerFilter],
+    flavor: ClusterFlavor,
+) -> Result<Vec<String>, ListenError> {
+    let mut commands = match flavor {
+        ClusterFlavor::DxSpider => vec!["clear/spots all".to_string()],
+        ClusterFlavor::ArCluster => vec!["set/nofilter".to_string()],
+        ClusterFlavor::Generic | ClusterFlavor::Rbn => return Err(unsupported(flavor)),
+    };
+
+    let (mut accept, mut reject) = (0, 0);
+    for filter in filters {
+        let slot = match filter.action {
+            FilterAction::Accept => &mut accept,
+            FilterAction::Reject => &mut reject,
+        };
+        *slot += 1;
+        commands.extend(filter.commands(flavor, *slot)?);
+    }
+
+    Ok(commands)
+}
+
+/// Error for server software not supporting server-side filters.
+fn unsupported(flavor: ClusterFlavor) -> ListenError {
+    ListenError::InvalidFilter(format!(
+        "server-side filters not supported by flavor {:?}",
+        flavor
+    ))
+}
+
+/// Join values separated by commas.
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
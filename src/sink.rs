@@ -0,0 +1,501 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tokio::sync::{mpsc, Notify};
+use tokio::task::JoinHandle;
+
+use crate::line::ReceivedLine;
+use crate::listener::{ListenError, Listener};
+
+/// Possible errors while delivering lines to a sink
+#[derive(Error, Debug)]
+pub enum SinkError {
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("sink is closed")]
+    Closed,
+
+    #[error("failed to encode line: {0}")]
+    Encoding(String),
+
+    #[error("sink backend error: {0}")]
+    Backend(String),
+
+    #[error("queue of sink is full, line dropped")]
+    QueueFull,
+}
+
+/// Output target of received lines, e.g. a file, a message broker or a database.
+#[async_trait]
+pub trait SpotSink: Send {
+    /// Deliver a received line to the sink.
+    ///
+    /// # Arguments
+    ///
+    /// * `line`: Received line
+    ///
+    /// # Result
+    ///
+    /// Returns `Ok(())` if the line was accepted by the sink, which may buffer it until the next flush.
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError>;
+
+    /// Write any buffered lines, e.g. before shutting down.
+    async fn flush(&mut self) -> Result<(), SinkError> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SpotSink for mpsc::UnboundedSender<ReceivedLine> {
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        self.send(line).map_err(|_| SinkError::Closed)
+    }
+}
+
+/// Handler called with the name of the sink if the delivery to a sink failed
+pub type SinkErrorHook = Arc<dyn Fn(&str, &SinkError) + Send + Sync>;
+
+/// Behavior if the queue of a sink is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the oldest queued line in favor of the new line
+    #[default]
+    DropOldest,
+
+    /// Drop the new line
+    DropNewest,
+
+    /// Wait until the sink took a line from the queue, slowing down the delivery to all sinks
+    Block,
+}
+
+/// Queue of a sink spawned by a `SinkRegistry`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SinkQueue {
+    /// Maximum number of queued lines
+    pub capacity: usize,
+
+    /// Behavior if the queue is full
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for SinkQueue {
+    fn default() -> Self {
+        Self {
+            capacity: 1000,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+/// Registry of named sinks every received line is delivered to.
+/// A failing sink does not affect the other sinks, its errors are passed to the error handler.
+/// Once spawned, every sink runs in its own task fed by its own queue, so a slow sink does not stall the other sinks.
+#[derive(Default)]
+pub struct SinkRegistry {
+    /// Registered sinks with their name and queue
+    sinks: Vec<(String, Box<dyn SpotSink>, SinkQueue)>,
+
+    /// Called if the delivery to a sink failed, errors are ignored if not set
+    pub on_error: Option<SinkErrorHook>,
+}
+
+impl SinkRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a sink with the default queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: Name of the sink passed to the error handler
+    /// * `sink`: Sink to deliver the lines to
+    pub fn register<S: SpotSink + 'static>(&mut self, name: &str, sink: S) {
+        self.register_queued(name, sink, SinkQueue::default());
+    }
+
+    /// Register a sink with the given queue, used once the registry is spawned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: Name of the sink passed to the error handler
+    /// * `sink`: Sink to deliver the lines to
+    /// * `queue`: Capacity and overflow policy of the queue of the sink
+    pub fn register_queued<S: SpotSink + 'static>(
+        &mut self,
+        name: &str,
+        sink: S,
+        queue: SinkQueue,
+    ) {
+        self.sinks.push((name.into(), Box::new(sink), queue));
+    }
+
+    /// Register a sink with the default queue.
+    pub fn with<S: SpotSink + 'static>(mut self, name: &str, sink: S) -> Self {
+        self.register(name, sink);
+        self
+    }
+
+    /// Register a sink with the given queue.
+    pub fn with_queued<S: SpotSink + 'static>(
+        mut self,
+        name: &str,
+        sink: S,
+        queue: SinkQueue,
+    ) -> Self {
+        self.register_queued(name, sink, queue);
+        self
+    }
+
+    /// Get the names of the registered sinks.
+    pub fn names(&self) -> Vec<&str> {
+        self.sinks
+            .iter()
+            .map(|(name, _, _)| name.as_str())
+            .collect()
+    }
+
+    /// Deliver a received line to all sinks.
+    ///
+    /// # Arguments
+    ///
+    /// * `line`: Received line
+    ///
+    /// # Result
+    ///
+    /// Returns the number of sinks the delivery failed for.
+    pub async fn deliver(&mut self, line: ReceivedLine) -> usize {
+        let mut failed = 0;
+
+        for (name, sink, _) in self.sinks.iter_mut() {
+            if let Err(err) = sink.deliver(line.clone()).await {
+                failed += 1;
+                if let Some(hook) = &self.on_error {
+                    hook(name, &err);
+                }
+            }
+        }
+
+        failed
+    }
+
+    /// Flush all sinks.
+    ///
+    /// # Result
+    ///
+    /// Returns the number of sinks the flush failed for.
+    pub async fn flush(&mut self) -> usize {
+        let mut failed = 0;
+
+        for (name, sink, _) in self.sinks.iter_mut() {
+            if let Err(err) = sink.flush().await {
+                failed += 1;
+                if let Some(hook) = &self.on_error {
+                    hook(name, &err);
+                }
+            }
+        }
+
+        failed
+    }
+
+    /// Start delivering the lines received from a channel to all sinks.
+    /// Every sink runs in its own task and takes the lines from its own queue, lines are dropped
+    /// or the delivery waits according to the overflow policy if the queue of a sink is full.
+    /// The sinks are flushed and the task stops after the channel is closed, i.e. all listeners sending to it stopped,
+    /// and all sinks took the lines left in their queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: Channel to receive lines from, e.g. from one or more listeners
+    ///
+    /// # Result
+    ///
+    /// Returns the handle of the delivering task.
+    pub fn spawn(self, mut input: mpsc::UnboundedReceiver<ReceivedLine>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut queues = Vec::new();
+            let mut tasks = Vec::new();
+
+            for (name, mut sink, queue) in self.sinks {
+                let name: Arc<str> = name.into();
+                let lines = Arc::new(LineQueue::new(queue.capacity));
+                let on_error = self.on_error.clone();
+                queues.push((name.clone(), lines.clone(), queue.overflow));
+
+                tasks.push(tokio::spawn(async move {
+                    let report = |err: SinkError| {
+                        if let Some(hook) = &on_error {
+                            hook(&name, &err);
+                        }
+                    };
+                    while let Some(line) = lines.pop().await {
+                        if let Err(err) = sink.deliver(line).await {
+                            report(err);
+                        }
+                    }
+                    if let Err(err) = sink.flush().await {
+                        report(err);
+                    }
+                }));
+            }
+
+            while let Some(line) = input.recv().await {
+                for (name, lines, overflow) in queues.iter() {
+                    if !lines.push(line.clone(), *overflow).await {
+                        if let Some(hook) = &self.on_error {
+                            hook(name, &SinkError::QueueFull);
+                        }
+                    }
+                }
+            }
+
+            for (_, lines, _) in queues.iter() {
+                lines.close();
+            }
+            for task in tasks {
+                let _ = task.await;
+            }
+        })
+    }
+}
+
+/// Bounded queue of lines between a spawned registry and a single sink
+struct LineQueue {
+    /// Queued lines and whether the queue is closed
+    state: Mutex<(VecDeque<ReceivedLine>, bool)>,
+
+    /// Maximum number of queued lines
+    capacity: usize,
+
+    /// Notified if a line was added or the queue was closed
+    readable: Notify,
+
+    /// Notified if a line was removed
+    writable: Notify,
+}
+
+impl LineQueue {
+    /// Create an empty queue.
+    fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new((VecDeque::new(), false)),
+            capacity: capacity.max(1),
+            readable: Notify::new(),
+            writable: Notify::new(),
+        }
+    }
+
+    /// Add a line to the queue, applying the overflow policy if the queue is full.
+    /// Returns false if a line was dropped.
+    async fn push(&self, line: ReceivedLine, overflow: OverflowPolicy) -> bool {
+        loop {
+            {
+                let lines = &mut self.state.lock().unwrap().0;
+                if lines.len() < self.capacity {
+                    lines.push_back(line);
+                    self.readable.notify_one();
+                    return true;
+                }
+
+                match overflow {
+                    OverflowPolicy::DropOldest => {
+                        lines.pop_front();
+                        lines.push_back(line);
+                        return false;
+                    }
+                    OverflowPolicy::DropNewest => return false,
+                    OverflowPolicy::Block => (),
+                }
+            }
+
+            self.writable.notified().await;
+        }
+    }
+
+    /// Take the oldest line from the queue, waiting for a line if the queue is empty.
+    /// Returns `None` once the queue is closed and empty.
+    async fn pop(&self) -> Option<ReceivedLine> {
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                if let Some(line) = state.0.pop_front() {
+                    self.writable.notify_one();
+                    return Some(line);
+                } else if state.1 {
+                    return None;
+                }
+            }
+
+            self.readable.notified().await;
+        }
+    }
+
+    /// Close the queue, the lines left are still taken by the sink.
+    fn close(&self) {
+        self.state.lock().unwrap().1 = true;
+        self.readable.notify_one();
+    }
+}
+
+impl fmt::Debug for SinkRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SinkRegistry")
+            .field("sinks", &self.names())
+            .finish()
+    }
+}
+
+impl Listener {
+    /// Listen for data from dx cluster and deliver the received lines to sinks.
+    /// Works like `listen` but drives the given sinks directly instead of sending the lines to a channel.
+    /// Use `SinkRegistry::spawn` with `listen` to feed the sinks from multiple listeners.
+    ///
+    /// # Arguments
+    ///
+    /// * `sinks`: Sinks to deliver the lines to
+    /// * `conn_timeout`: Connection timeout to server
+    ///
+    /// # Result
+    ///
+    /// The result shall be `Ok(JoinHandle)` if the listener is connected and is waiting for spots.
+    /// The handle belongs to the task delivering the lines, which finishes after the listener stopped and the sinks were flushed.
+    /// An `Err(ListenError)` shall be returned in case something went wrong while connecting.
+    pub async fn listen_sinks(
+        &mut self,
+        sinks: SinkRegistry,
+        connection_timeout: std::time::Duration,
+    ) -> Result<JoinHandle<()>, ListenError> {
+        let (tx, rx) = mpsc::unbounded_channel::<ReceivedLine>();
+        self.listen(tx, connection_timeout).await?;
+
+        Ok(sinks.spawn(rx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time;
+
+    fn line(n: usize) -> ReceivedLine {
+        ReceivedLine::unparsed(format!("line {}", n))
+    }
+
+    async fn drain(queue: &LineQueue) -> Vec<String> {
+        queue.close();
+        let mut lines = Vec::new();
+        while let Some(line) = queue.pop().await {
+            lines.push(line.raw);
+        }
+        lines
+    }
+
+    /// Sink recording the delivered lines and the flushes
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<(Vec<String>, usize)>>);
+
+    #[async_trait]
+    impl SpotSink for Recorder {
+        async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+            self.0.lock().unwrap().0.push(line.raw);
+            Ok(())
+        }
+
+        async fn flush(&mut self) -> Result<(), SinkError> {
+            self.0.lock().unwrap().1 += 1;
+            Ok(())
+        }
+    }
+
+    /// Sink failing every delivery
+    struct Failing;
+
+    #[async_trait]
+    impl SpotSink for Failing {
+        async fn deliver(&mut self, _line: ReceivedLine) -> Result<(), SinkError> {
+            Err(SinkError::Closed)
+        }
+    }
+
+    #[tokio::test]
+    async fn drop_oldest() {
+        let queue = LineQueue::new(2);
+
+        assert!(queue.push(line(1), OverflowPolicy::DropOldest).await);
+        assert!(queue.push(line(2), OverflowPolicy::DropOldest).await);
+        assert!(!queue.push(line(3), OverflowPolicy::DropOldest).await);
+        assert_eq!(drain(&queue).await, ["line 2", "line 3"]);
+    }
+
+    #[tokio::test]
+    async fn drop_newest() {
+        let queue = LineQueue::new(2);
+
+        assert!(queue.push(line(1), OverflowPolicy::DropNewest).await);
+        assert!(queue.push(line(2), OverflowPolicy::DropNewest).await);
+        assert!(!queue.push(line(3), OverflowPolicy::DropNewest).await);
+        assert_eq!(drain(&queue).await, ["line 1", "line 2"]);
+    }
+
+    #[tokio::test]
+    async fn block() {
+        let queue = Arc::new(LineQueue::new(1));
+        assert!(queue.push(line(1), OverflowPolicy::Block).await);
+
+        let blocked = {
+            let queue = queue.clone();
+            tokio::spawn(async move { queue.push(line(2), OverflowPolicy::Block).await })
+        };
+        time::sleep(Duration::from_millis(50)).await;
+        assert!(!blocked.is_finished());
+
+        // Taking a line makes room for the waiting one
+        assert_eq!(queue.pop().await.unwrap().raw, "line 1");
+        assert!(time::timeout(Duration::from_secs(1), blocked)
+            .await
+            .unwrap()
+            .unwrap());
+        assert_eq!(drain(&queue).await, ["line 2"]);
+    }
+
+    #[tokio::test]
+    async fn spawn() {
+        let recorder = Recorder::default();
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = SinkRegistry::new()
+            .with("recorder", recorder.clone())
+            .with("failing", Failing);
+        registry.on_error = Some({
+            let errors = errors.clone();
+            Arc::new(move |name: &str, _: &SinkError| errors.lock().unwrap().push(name.to_string()))
+        });
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handle = registry.spawn(rx);
+        for n in 1..=3 {
+            tx.send(line(n)).unwrap();
+        }
+        drop(tx);
+        time::timeout(Duration::from_secs(1), handle)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // The failing sink neither stops nor affects the other sink
+        let (lines, flushes) = recorder.0.lock().unwrap().clone();
+        assert_eq!(lines, ["line 1", "line 2", "line 3"]);
+        assert_eq!(flushes, 1);
+        assert_eq!(*errors.lock().unwrap(), ["failing"; 3]);
+    }
+}
@@ -0,0 +1,320 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::net::IpAddr;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::listener::ListenError;
+
+// Version of the SOCKS protocol
+const SOCKS_VERSION: u8 = 0x05;
+
+// Version of the username/password authentication
+const USER_PASS_VERSION: u8 = 0x01;
+
+// Authentication methods
+const NO_AUTH: u8 = 0x00;
+const USER_PASS: u8 = 0x02;
+
+// Command to open a TCP connection
+const CONNECT: u8 = 0x01;
+
+// Address types
+const IPV4: u8 = 0x01;
+const DOMAIN: u8 = 0x03;
+const IPV6: u8 = 0x04;
+
+// Reply of a successful request
+const SUCCEEDED: u8 = 0x00;
+
+// SOCKS port of a local Tor daemon
+const TOR_ADDRESS: &str = "127.0.0.1:9050";
+
+// Top-level domain of Tor onion services
+const ONION_DOMAIN: &str = ".onion";
+
+/// SOCKS5 proxy to connect to the server through, e.g. the SOCKS port of a Tor daemon.
+///
+/// Host names are always resolved by the proxy, so no DNS requests are sent by the listener.
+/// This is required to reach onion services, which are reachable through Tor only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Socks5Proxy {
+    /// Address of the proxy, e.g. `127.0.0.1:1080`
+    pub address: String,
+
+    /// Username and password to authenticate at the proxy, if any.
+    /// Tor uses different circuits for different credentials.
+    pub credentials: Option<(String, String)>,
+}
+
+impl Socks5Proxy {
+    /// Create new settings for a proxy without authentication.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: Address of the proxy, e.g. `127.0.0.1:1080`
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `Socks5Proxy`.
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.into(),
+            credentials: None,
+        }
+    }
+
+    /// Create new settings for the SOCKS port of a local Tor daemon, i.e. `127.0.0.1:9050`.
+    pub fn tor() -> Self {
+        Self::new(TOR_ADDRESS)
+    }
+
+    /// Request a connection to a server on a stream connected to the proxy.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream`: Stream connected to the proxy
+    /// * `host`: Host of the server, resolved by the proxy if it is not an IP address
+    /// * `port`: Port of the server
+    ///
+    /// # Result
+    ///
+    /// Returns the stream connected through the proxy to the server,
+    /// `ListenError::ConnectionError` if the proxy could not reach the server,
+    /// or `ListenError::ProxyError` if the proxy refused the request.
+    pub async fn connect<S: AsyncRead + AsyncWrite + Unpin>(
+        &self,
+        mut stream: S,
+        host: &str,
+        port: u16,
+    ) -> Result<S, ListenError> {
+        let method = if self.credentials.is_some() {
+            USER_PASS
+        } else {
+            NO_AUTH
+        };
+        write(&mut stream, &[SOCKS_VERSION, 1, method]).await?;
+
+        let mut reply = [0u8; 2];
+        read(&mut stream, &mut reply).await?;
+        if reply[0] != SOCKS_VERSION {
+            return Err(ListenError::ProxyError("not a SOCKS5 proxy".into()));
+        }
+        if reply[1] != method {
+            return Err(ListenError::ProxyError(
+                "authentication method not accepted".into(),
+            ));
+        }
+
+        if let Some((username, password)) = self.credentials.as_ref() {
+            let mut request = vec![USER_PASS_VERSION];
+            for field in [username, password] {
+                let len = u8::try_from(field.len())
+                    .map_err(|_| ListenError::ProxyError("credentials too long".into()))?;
+                request.push(len);
+                request.extend_from_slice(field.as_bytes());
+            }
+            write(&mut stream, &request).await?;
+
+            read(&mut stream, &mut reply).await?;
+            if reply[1] != SUCCEEDED {
+                return Err(ListenError::ProxyError("authentication failed".into()));
+            }
+        }
+
+        let mut request = vec![SOCKS_VERSION, CONNECT, 0x00];
+        match host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => {
+                request.push(IPV4);
+                request.extend_from_slice(&ip.octets());
+            }
+            Ok(IpAddr::V6(ip)) => {
+                request.push(IPV6);
+                request.extend_from_slice(&ip.octets());
+            }
+            Err(_) => {
+                let len = u8::try_from(host.len())
+                    .map_err(|_| ListenError::ProxyError("host name too long".into()))?;
+                request.push(DOMAIN);
+                request.push(len);
+                request.extend_from_slice(host.as_bytes());
+            }
+        }
+        request.extend_from_slice(&port.to_be_bytes());
+        write(&mut stream, &request).await?;
+
+        // Version, reply, reserved byte and type of the bound address
+        let mut reply = [0u8; 4];
+        read(&mut stream, &mut reply).await?;
+        if reply[1] != SUCCEEDED {
+            return Err(reply_error(reply[1]));
+        }
+
+        // The bound address is of no interest, but has to be consumed
+        let len = match reply[3] {
+            IPV4 => 4,
+            IPV6 => 16,
+            DOMAIN => {
+                let mut len = [0u8; 1];
+                read(&mut stream, &mut len).await?;
+                len[0] as usize
+            }
+            _ => return Err(ListenError::ProxyError("invalid reply".into())),
+        };
+        let mut bound = vec![0u8; len + 2];
+        read(&mut stream, &mut bound).await?;
+
+        Ok(stream)
+    }
+}
+
+/// Check if a host is an onion service, reachable through Tor only.
+pub fn is_onion(host: &str) -> bool {
+    let host = host.trim_end_matches('.');
+    host.len() > ONION_DOMAIN.len()
+        && host[host.len() - ONION_DOMAIN.len()..].eq_ignore_ascii_case(ONION_DOMAIN)
+}
+
+/// Write a message to the proxy.
+async fn write<S: AsyncWrite + Unpin>(stream: &mut S, data: &[u8]) -> Result<(), ListenError> {
+    stream
+        .write_all(data)
+        .await
+        .map_err(|_| ListenError::ConnectionError)?;
+    stream
+        .flush()
+        .await
+        .map_err(|_| ListenError::ConnectionError)
+}
+
+/// Read a message of the proxy.
+async fn read<S: AsyncRead + Unpin>(stream: &mut S, buf: &mut [u8]) -> Result<(), ListenError> {
+    stream
+        .read_exact(buf)
+        .await
+        .map(|_| ())
+        .map_err(|_| ListenError::ConnectionError)
+}
+
+/// Convert a failure reply of the proxy.
+/// Failures to reach the server are reported like those of a direct connection, so they are retried.
+fn reply_error(reply: u8) -> ListenError {
+    let reason = match reply {
+        // Server not reachable or refused the connection
+        0x03..=0x06 => return ListenError::ConnectionError,
+        // Onion service temporarily not reachable, reported by Tor
+        0xF2 | 0xF3 | 0xF7 => return ListenError::ConnectionError,
+        0x01 => "general failure",
+        0x02 => "connection not allowed by ruleset",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        0xF0 => "onion service descriptor not found",
+        0xF1 => "invalid onion service descriptor",
+        0xF4 => "onion service requires client authorization",
+        0xF5 => "onion service client authorization failed",
+        0xF6 => "invalid onion service address",
+        _ => "unknown failure",
+    };
+    ListenError::ProxyError(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::DuplexStream;
+
+    /// Read a request sent to the scripted proxy.
+    async fn request(proxy: &mut DuplexStream, len: usize) -> Vec<u8> {
+        let mut buf = vec![0u8; len];
+        proxy.read_exact(&mut buf).await.unwrap();
+        buf
+    }
+
+    #[tokio::test]
+    async fn connect_domain() {
+        let (client, mut proxy) = tokio::io::duplex(1024);
+        let script = tokio::spawn(async move {
+            assert_eq!(request(&mut proxy, 3).await, [SOCKS_VERSION, 1, USER_PASS]);
+            proxy.write_all(&[SOCKS_VERSION, USER_PASS]).await.unwrap();
+            assert_eq!(request(&mut proxy, 7).await, b"\x01\x02dx\x02pw");
+            proxy
+                .write_all(&[USER_PASS_VERSION, SUCCEEDED])
+                .await
+                .unwrap();
+
+            // The host name is resolved by the proxy
+            let host = b"dxc.example.onion";
+            let mut expected = vec![SOCKS_VERSION, CONNECT, 0x00, DOMAIN, host.len() as u8];
+            expected.extend_from_slice(host);
+            expected.extend_from_slice(&7300u16.to_be_bytes());
+            assert_eq!(request(&mut proxy, expected.len()).await, expected);
+            proxy
+                .write_all(&[
+                    SOCKS_VERSION,
+                    SUCCEEDED,
+                    0x00,
+                    IPV4,
+                    10,
+                    0,
+                    0,
+                    1,
+                    0x1c,
+                    0x84,
+                ])
+                .await
+                .unwrap();
+            proxy.write_all(b"login: ").await.unwrap();
+        });
+
+        let mut proxy = Socks5Proxy::tor();
+        proxy.credentials = Some(("dx".into(), "pw".into()));
+        let mut stream = proxy
+            .connect(client, "dxc.example.onion", 7300)
+            .await
+            .unwrap();
+        script.await.unwrap();
+
+        // The bound address is consumed, the data of the server follows
+        let mut data = [0u8; 7];
+        stream.read_exact(&mut data).await.unwrap();
+        assert_eq!(&data, b"login: ");
+    }
+
+    #[tokio::test]
+    async fn refused() {
+        for (reply, err) in [
+            (0x05, ListenError::ConnectionError),
+            (
+                0x02,
+                ListenError::ProxyError("connection not allowed by ruleset".into()),
+            ),
+        ] {
+            let (client, mut proxy) = tokio::io::duplex(1024);
+            let script = tokio::spawn(async move {
+                request(&mut proxy, 3).await;
+                proxy.write_all(&[SOCKS_VERSION, NO_AUTH]).await.unwrap();
+                request(&mut proxy, 10).await;
+                proxy
+                    .write_all(&[SOCKS_VERSION, reply, 0x00, IPV4])
+                    .await
+                    .unwrap();
+                proxy
+            });
+
+            let res = Socks5Proxy::new("127.0.0.1:1080")
+                .connect(client, "10.0.0.1", 7300)
+                .await;
+            assert_eq!(res.err(), Some(err));
+            script.await.unwrap();
+        }
+    }
+
+    #[test]
+    fn onion() {
+        assert!(is_onion("dxcexample.onion"));
+        assert!(is_onion("dxcexample.ONION."));
+        assert!(!is_onion(".onion"));
+        assert!(!is_onion("dxc.example.org"));
+    }
+}
@@ -0,0 +1,292 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use std::io::{ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
+use tokio::time::Instant;
+
+use crate::line::ReceivedLine;
+use crate::sink::{SinkError, SpotSink};
+
+// Extension of the segment files
+const SEGMENT_EXTENSION: &str = "spool";
+
+// Name of the file holding the cursor
+const CURSOR_FILE: &str = "cursor";
+
+// Number of delivered lines after which the cursor is persisted
+const CURSOR_INTERVAL: usize = 100;
+
+/// Position in the spool given by segment number and byte offset
+type Position = (u64, u64);
+
+/// Disk-backed spool in front of a sink, so lines are not lost while the sink is unavailable.
+///
+/// Every line is appended to the current segment file in the spool directory before it is delivered
+/// to the sink. The cursor marks the first line not delivered yet and is persisted in the same directory.
+/// If the delivery fails, the following lines are only spooled and delivered once the sink recovers,
+/// which is tried again after `retry` with the next line or flush. Fully delivered segments are removed.
+///
+/// Lines are delivered at least once: lines delivered after the cursor was persisted the last time
+/// are delivered again after a restart, and a line is delivered again if the sink returned an error.
+/// Lines are stored as JSON, see `ReceivedLine::to_json`.
+#[derive(Debug)]
+pub struct SpoolSink<S> {
+    /// Maximum size of a segment file in bytes
+    pub segment_size: u64,
+
+    /// Time to wait before delivering again after the sink failed
+    pub retry: Duration,
+
+    /// Directory holding the segment files and the cursor
+    dir: PathBuf,
+
+    /// Sink to deliver the lines to
+    sink: S,
+
+    /// Currently written segment
+    segment: File,
+
+    /// End of the spooled lines
+    head: Position,
+
+    /// First line not delivered yet
+    cursor: Position,
+
+    /// Number of lines delivered since the cursor was persisted
+    unsaved: usize,
+
+    /// Point in time the sink failed, if not recovered yet
+    failed: Option<Instant>,
+}
+
+impl<S: SpotSink> SpoolSink<S> {
+    /// Open the spool in the given directory, created if missing, in front of a sink.
+    /// Lines spooled in a previous run are delivered with the next line or flush.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir`: Directory of the spool, must not be shared with other spools
+    /// * `sink`: Sink to deliver the lines to
+    ///
+    /// # Result
+    ///
+    /// Returns the spool or an error if the directory could not be read.
+    pub async fn open<P: Into<PathBuf>>(dir: P, sink: S) -> Result<Self, SinkError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).await?;
+
+        let mut segments = Vec::new();
+        let mut entries = fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == SEGMENT_EXTENSION) {
+                if let Some(number) = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str()?.parse().ok())
+                {
+                    segments.push(number);
+                }
+            }
+        }
+        segments.sort_unstable();
+        let first = segments.first().copied().unwrap_or_default();
+        let last = segments.last().copied().unwrap_or_default();
+
+        let mut segment = open_segment(&dir, last).await?;
+        let mut size = segment.metadata().await?.len();
+        if size > 0 {
+            // Complete a line partially written before a crash, it is skipped as invalid while delivering
+            let mut end = [0u8];
+            segment.seek(SeekFrom::Start(size - 1)).await?;
+            segment.read_exact(&mut end).await?;
+            if end[0] != b'\n' {
+                segment.write_all(b"\n").await?;
+                size += 1;
+            }
+        }
+
+        let cursor = match fs::read_to_string(dir.join(CURSOR_FILE)).await {
+            Ok(data) => parse_cursor(&data).unwrap_or((first, 0)),
+            Err(err) if err.kind() == ErrorKind::NotFound => (first, 0),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Self {
+            segment_size: 16 * 1024 * 1024,
+            retry: Duration::from_secs(5),
+            dir,
+            sink,
+            segment,
+            head: (last, size),
+            cursor: cursor.max((first, 0)).min((last, size)),
+            unsaved: 0,
+            failed: None,
+        })
+    }
+
+    /// Get the sink the lines are delivered to.
+    pub fn inner(&self) -> &S {
+        &self.sink
+    }
+
+    /// Append an encoded line to the spool, starting a new segment if the current one is full.
+    async fn append(&mut self, data: &str) -> Result<Position, SinkError> {
+        let (number, size) = self.head;
+        if size > 0 && size + data.len() as u64 > self.segment_size {
+            self.segment = open_segment(&self.dir, number + 1).await?;
+            self.head = (number + 1, 0);
+            if self.cursor == (number, size) {
+                self.advance_segment().await?;
+            }
+        }
+
+        let start = self.head;
+        self.segment.write_all(data.as_bytes()).await?;
+        self.segment.flush().await?;
+        self.head.1 += data.len() as u64;
+
+        Ok(start)
+    }
+
+    /// Deliver the spooled lines starting at the cursor until the sink fails or all lines are delivered.
+    async fn replay(&mut self) -> Result<(), SinkError> {
+        while self.cursor != self.head {
+            let (number, offset) = self.cursor;
+            let file = match File::open(segment_path(&self.dir, number)).await {
+                Ok(file) => Some(file),
+                Err(err) if err.kind() == ErrorKind::NotFound => None,
+                Err(err) => return Err(err.into()),
+            };
+
+            if let Some(mut file) = file {
+                file.seek(SeekFrom::Start(offset)).await?;
+                let mut reader = BufReader::new(file);
+                let mut buf = Vec::new();
+
+                loop {
+                    buf.clear();
+                    let len = reader.read_until(b'\n', &mut buf).await?;
+                    if len == 0 || !buf.ends_with(b"\n") {
+                        break;
+                    }
+
+                    // Invalid lines, e.g. written partially before a crash, are skipped
+                    if let Ok(line) = serde_json::from_slice::<ReceivedLine>(&buf) {
+                        self.deliver_one(line).await?;
+                    }
+                    self.cursor.1 += len as u64;
+                }
+            }
+
+            if number >= self.head.0 {
+                break;
+            }
+            self.advance_segment().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Deliver a single line to the sink, remembering the failure if it fails.
+    async fn deliver_one(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        match self.sink.deliver(line).await {
+            Ok(()) => {
+                self.unsaved += 1;
+                if self.unsaved >= CURSOR_INTERVAL {
+                    self.save_cursor().await?;
+                }
+                Ok(())
+            }
+            Err(err) => {
+                self.failed = Some(Instant::now());
+                self.save_cursor().await?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Move the cursor to the next segment and remove the delivered segment.
+    async fn advance_segment(&mut self) -> Result<(), SinkError> {
+        let delivered = self.cursor.0;
+        self.cursor = (delivered + 1, 0);
+        self.save_cursor().await?;
+
+        match fs::remove_file(segment_path(&self.dir, delivered)).await {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Persist the cursor, replacing the previous cursor atomically.
+    async fn save_cursor(&mut self) -> Result<(), SinkError> {
+        let path = self.dir.join(CURSOR_FILE);
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, format!("{} {}\n", self.cursor.0, self.cursor.1)).await?;
+        fs::rename(&tmp, &path).await?;
+        self.unsaved = 0;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S: SpotSink> SpotSink for SpoolSink<S> {
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        let mut data = line.to_json();
+        data.push('\n');
+        let start = self.append(&data).await?;
+
+        if self
+            .failed
+            .is_some_and(|failed| failed.elapsed() < self.retry)
+        {
+            return Ok(());
+        }
+        self.failed = None;
+
+        if self.cursor == start {
+            // Sink is up to date, deliver the line without reading it back from the spool
+            self.deliver_one(line).await?;
+            self.cursor = self.head;
+            Ok(())
+        } else {
+            self.replay().await
+        }
+    }
+
+    async fn flush(&mut self) -> Result<(), SinkError> {
+        self.failed = None;
+        let replayed = self.replay().await;
+        self.save_cursor().await?;
+        replayed?;
+
+        self.sink.flush().await
+    }
+}
+
+/// Get the path of a segment file.
+fn segment_path(dir: &Path, number: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", number, SEGMENT_EXTENSION))
+}
+
+/// Open a segment file for appending, created if missing.
+async fn open_segment(dir: &Path, number: u64) -> Result<File, SinkError> {
+    Ok(OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(segment_path(dir, number))
+        .await?)
+}
+
+/// Parse a persisted cursor.
+fn parse_cursor(data: &str) -> Option<Position> {
+    let (number, offset) = data.trim().split_once(' ')?;
+    Some((number.parse().ok()?, offset.parse().ok()?))
+}
@@ -0,0 +1,196 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use rusqlite::{params, Connection};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::line::ReceivedLine;
+use crate::record::SpotRecord;
+use crate::sink::{SinkError, SpotSink};
+
+// Version of the database schema, stored as user version of the database
+const SCHEMA_VERSION: i32 = 1;
+
+// Statements to create the database schema
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS spots (
+    id INTEGER PRIMARY KEY,
+    received TEXT NOT NULL,
+    time TEXT NOT NULL,
+    spotter TEXT NOT NULL,
+    dx TEXT NOT NULL,
+    freq INTEGER NOT NULL,
+    band TEXT,
+    mode TEXT,
+    comment TEXT,
+    snr INTEGER,
+    speed INTEGER,
+    skimmer INTEGER NOT NULL,
+    spotter_grid TEXT,
+    dx_grid TEXT,
+    spotter_dxcc INTEGER,
+    dx_dxcc INTEGER,
+    source TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS spots_time ON spots (time);
+CREATE INDEX IF NOT EXISTS spots_dx ON spots (dx);
+";
+
+// Statement to insert a spot
+const INSERT: &str = "
+INSERT INTO spots (
+    received, time, spotter, dx, freq, band, mode, comment, snr, speed,
+    skimmer, spotter_grid, dx_grid, spotter_dxcc, dx_dxcc, source
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+";
+
+// Format of points in time, understood by the date and time functions of SQLite
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// Default number of spots inserted by a single transaction
+const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Sink inserting spots into the table `spots` of an SQLite database.
+///
+/// The schema is created by the sink if missing. Points in time are stored as text in UTC, e.g. `2026-10-16 12:00:00`,
+/// frequencies in Hz. Lines other than spots are ignored.
+/// Spots are buffered and inserted in a single transaction once the batch is full or the sink is flushed.
+/// A failed batch is dropped.
+#[derive(Debug)]
+pub struct SqliteSink {
+    /// Number of spots inserted by a single transaction
+    pub batch_size: usize,
+
+    /// Connection to the database, shared with the blocking insert task
+    conn: Arc<Mutex<Connection>>,
+
+    /// Spots not yet inserted
+    pending: Vec<SpotRecord>,
+}
+
+impl SqliteSink {
+    /// Open the database and create the schema if missing.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: Path of the database file, created if missing
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `SqliteSink` or `SinkError::Backend` if the database could not be opened
+    /// or was created by an incompatible version of this crate.
+    pub async fn open<P: Into<PathBuf>>(path: P) -> Result<Self, SinkError> {
+        let path = path.into();
+        let conn = blocking(move || {
+            let conn = Connection::open(path).map_err(backend)?;
+            migrate(&conn)?;
+            Ok(conn)
+        })
+        .await?;
+
+        Ok(Self {
+            batch_size: DEFAULT_BATCH_SIZE,
+            conn: Arc::new(Mutex::new(conn)),
+            pending: Vec::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl SpotSink for SqliteSink {
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        if let Some(record) = SpotRecord::from_line(&line) {
+            self.pending.push(record);
+            if self.pending.len() >= self.batch_size {
+                self.flush().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), SinkError> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let records = std::mem::take(&mut self.pending);
+        let conn = self.conn.clone();
+        blocking(move || {
+            let mut conn = conn.lock().unwrap();
+            insert(&mut conn, &records).map_err(backend)
+        })
+        .await
+    }
+}
+
+/// Create the schema if missing and check its version.
+fn migrate(conn: &Connection) -> Result<(), SinkError> {
+    let version: i32 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(backend)?;
+
+    match version {
+        0 => conn
+            .execute_batch(&format!(
+                "BEGIN; {} PRAGMA user_version = {}; COMMIT;",
+                SCHEMA, SCHEMA_VERSION
+            ))
+            .map_err(backend),
+        SCHEMA_VERSION => Ok(()),
+        _ => Err(SinkError::Backend(format!(
+            "unsupported schema version {}",
+            version
+        ))),
+    }
+}
+
+/// Insert spots within a single transaction.
+fn insert(conn: &mut Connection, records: &[SpotRecord]) -> Result<(), rusqlite::Error> {
+    let tx = conn.transaction()?;
+
+    {
+        let mut stmt = tx.prepare_cached(INSERT)?;
+        for record in records {
+            stmt.execute(params![
+                record.received.format(TIME_FORMAT).to_string(),
+                record.time.format(TIME_FORMAT).to_string(),
+                record.spotter,
+                record.dx,
+                record.freq as i64,
+                record.band.map(|band| band.to_string()),
+                record.mode.map(|mode| mode.to_string()),
+                record.comment,
+                record.snr,
+                record.speed,
+                record.skimmer,
+                record.spotter_grid,
+                record.dx_grid,
+                record.spotter_dxcc,
+                record.dx_dxcc,
+                record.source,
+            ])?;
+        }
+    }
+
+    tx.commit()
+}
+
+/// Run a database operation on the blocking thread pool.
+async fn blocking<T, F>(op: F) -> Result<T, SinkError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, SinkError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(op)
+        .await
+        .map_err(|err| SinkError::Backend(err.to_string()))?
+}
+
+/// Convert a database error.
+fn backend(err: rusqlite::Error) -> SinkError {
+    SinkError::Backend(err.to_string())
+}
@@ -0,0 +1,326 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use sha2::{Digest, Sha256};
+use std::io;
+#[cfg(feature = "tls")]
+use std::sync::Arc;
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::crypto::{
+    ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider,
+};
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::{
+    self, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+
+use crate::connection::BoxedTransport;
+use crate::listener::ListenError;
+
+/// Library used for TLS connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsBackend {
+    /// rustls with the Mozilla root certificates, requires the feature `tls`
+    #[cfg(feature = "tls")]
+    Rustls,
+
+    /// TLS library of the platform with the root certificates of the system, requires the feature `native-tls`.
+    /// Uses OpenSSL on Linux, Secure Transport on macOS and SChannel on Windows.
+    #[cfg(feature = "native-tls")]
+    Native,
+}
+
+impl Default for TlsBackend {
+    /// rustls if the feature `tls` is enabled, otherwise the TLS library of the platform
+    fn default() -> Self {
+        #[cfg(feature = "tls")]
+        return TlsBackend::Rustls;
+
+        #[cfg(not(feature = "tls"))]
+        return TlsBackend::Native;
+    }
+}
+
+/// Settings of a TLS connection to a cluster server
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// Library used for the connection
+    pub backend: TlsBackend,
+
+    /// Name of the server sent via SNI and checked against the certificate.
+    /// The host of the listener is used if not set.
+    pub server_name: Option<String>,
+
+    /// SHA-256 fingerprints of the accepted server certificates.
+    /// If not empty, the certificate of the server is accepted if its fingerprint matches one of them,
+    /// without validating it against the trusted root certificates, e.g. for self-signed certificates.
+    pub pinned_certificates: Vec<[u8; 32]>,
+
+    /// Additional trusted root certificates in DER format, besides the root certificates of the backend
+    pub root_certificates: Vec<Vec<u8>>,
+}
+
+impl TlsConfig {
+    /// Create new settings using the default backend,
+    /// validating the certificate of the server against the root certificates of the backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pin a server certificate.
+    ///
+    /// # Arguments
+    ///
+    /// * `fingerprint`: SHA-256 fingerprint of the certificate as hex string, optionally separated by colons,
+    ///   e.g. as printed by `openssl x509 -noout -fingerprint -sha256`
+    ///
+    /// # Result
+    ///
+    /// Returns `Ok(())` or `ListenError::TlsError` if the fingerprint is invalid.
+    pub fn pin(&mut self, fingerprint: &str) -> Result<(), ListenError> {
+        let digits: Vec<u8> = fingerprint.bytes().filter(|c| *c != b':').collect();
+        let invalid = || ListenError::TlsError(format!("invalid fingerprint '{}'", fingerprint));
+
+        if digits.len() != 64 {
+            return Err(invalid());
+        }
+
+        let mut pin = [0u8; 32];
+        for (byte, pair) in pin.iter_mut().zip(digits.chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+        }
+        self.pinned_certificates.push(pin);
+
+        Ok(())
+    }
+}
+
+/// Calculate the SHA-256 fingerprint of a certificate.
+///
+/// # Arguments
+///
+/// * `der`: Certificate in DER format
+///
+/// # Result
+///
+/// Returns the fingerprint.
+pub fn certificate_fingerprint(der: &[u8]) -> [u8; 32] {
+    Sha256::digest(der).into()
+}
+
+/// Establish a TLS session on a connected stream.
+///
+/// # Arguments
+///
+/// * `stream`: Connected stream, e.g. a TCP stream or an SSH tunnel
+/// * `host`: Host of the server, used as server name if not set in the settings
+/// * `config`: Settings of the TLS connection
+///
+/// # Result
+///
+/// Returns the encrypted stream, `ListenError::TlsError` if the settings are invalid or the handshake failed,
+/// or `ListenError::ConnectionError` if the connection failed during the handshake.
+pub async fn connect_tls(
+    stream: BoxedTransport,
+    host: &str,
+    config: &TlsConfig,
+) -> Result<BoxedTransport, ListenError> {
+    let name = config.server_name.as_deref().unwrap_or(host);
+
+    match config.backend {
+        #[cfg(feature = "tls")]
+        TlsBackend::Rustls => connect_rustls(stream, name, config).await,
+        #[cfg(feature = "native-tls")]
+        TlsBackend::Native => connect_native(stream, name, config).await,
+    }
+}
+
+/// Establish a TLS session using rustls.
+#[cfg(feature = "tls")]
+async fn connect_rustls(
+    stream: BoxedTransport,
+    name: &str,
+    config: &TlsConfig,
+) -> Result<BoxedTransport, ListenError> {
+    let provider = Arc::new(ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(tls_error)?;
+
+    let client = if config.pinned_certificates.is_empty() {
+        let mut roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        for cert in config.root_certificates.iter() {
+            roots
+                .add(CertificateDer::from(cert.clone()))
+                .map_err(tls_error)?;
+        }
+        builder.with_root_certificates(roots).with_no_client_auth()
+    } else {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(PinnedVerifier {
+                pins: config.pinned_certificates.clone(),
+                provider,
+            }))
+            .with_no_client_auth()
+    };
+
+    let name = ServerName::try_from(name.to_string())
+        .map_err(|_| ListenError::TlsError(format!("invalid server name '{}'", name)))?;
+
+    let stream = tokio_rustls::TlsConnector::from(Arc::new(client))
+        .connect(name, stream)
+        .await
+        .map_err(handshake_error)?;
+
+    Ok(Box::new(stream))
+}
+
+/// Establish a TLS session using the TLS library of the platform.
+/// Pinned certificates are checked after the handshake, as the library offers no custom verification.
+#[cfg(feature = "native-tls")]
+async fn connect_native(
+    stream: BoxedTransport,
+    name: &str,
+    config: &TlsConfig,
+) -> Result<BoxedTransport, ListenError> {
+    let pinned = !config.pinned_certificates.is_empty();
+
+    let mut builder = native_tls::TlsConnector::builder();
+    builder
+        .danger_accept_invalid_certs(pinned)
+        .danger_accept_invalid_hostnames(pinned);
+    for cert in config.root_certificates.iter() {
+        builder.add_root_certificate(native_tls::Certificate::from_der(cert).map_err(tls_error)?);
+    }
+    let connector = builder.build().map_err(tls_error)?;
+
+    let stream = tokio_native_tls::TlsConnector::from(connector)
+        .connect(name, stream)
+        .await
+        .map_err(native_error)?;
+
+    if pinned {
+        let cert = stream
+            .get_ref()
+            .peer_certificate()
+            .map_err(tls_error)?
+            .ok_or(ListenError::TlsError("server sent no certificate".into()))?;
+        let fingerprint = certificate_fingerprint(&cert.to_der().map_err(tls_error)?);
+        if !config.pinned_certificates.contains(&fingerprint) {
+            return Err(ListenError::TlsError(
+                "certificate does not match any pinned fingerprint".into(),
+            ));
+        }
+    }
+
+    Ok(Box::new(stream))
+}
+
+/// Verifier accepting server certificates by their fingerprint
+#[cfg(feature = "tls")]
+#[derive(Debug)]
+struct PinnedVerifier {
+    /// Fingerprints of the accepted certificates
+    pins: Vec<[u8; 32]>,
+
+    /// Provider of the signature algorithms
+    provider: Arc<CryptoProvider>,
+}
+
+#[cfg(feature = "tls")]
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if self.pins.contains(&certificate_fingerprint(end_entity)) {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(
+                "certificate does not match any pinned fingerprint".into(),
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Convert an error of the TLS library.
+fn tls_error<E: std::fmt::Display>(err: E) -> ListenError {
+    ListenError::TlsError(err.to_string())
+}
+
+/// Convert an error of the handshake.
+/// Errors of the TLS protocol are wrapped into an IO error, other IO errors are caused by the connection.
+#[cfg(feature = "tls")]
+fn handshake_error(err: io::Error) -> ListenError {
+    match err
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+    {
+        Some(err) => tls_error(err),
+        None => ListenError::ConnectionError,
+    }
+}
+
+/// Convert an error of the handshake using the TLS library of the platform.
+/// Errors caused by the connection carry an IO error as source.
+#[cfg(feature = "native-tls")]
+fn native_error(err: native_tls::Error) -> ListenError {
+    let mut source = std::error::Error::source(&err);
+    while let Some(inner) = source {
+        if inner.is::<io::Error>() {
+            return ListenError::ConnectionError;
+        }
+        source = inner.source();
+    }
+
+    tls_error(err)
+}
@@ -0,0 +1,69 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::enrich::Entity;
+use crate::line::ReceivedLine;
+
+// Placeholder value for unknown information
+const UNKNOWN: &str = "unknown";
+
+/// Replace the placeholders of a topic or routing key template by the values of a spot.
+///
+/// The following placeholders are supported, e.g. `dxcluster/spots/{band}/{continent}`:
+///
+/// - `{band}`: Band of the spot, e.g. `20m`
+/// - `{mode}`: Mode of the spot, e.g. `CW`
+/// - `{continent}`: Continent of the spotted station, e.g. `EU`
+/// - `{spotter_continent}`: Continent of the spotter
+/// - `{dx}`: Callsign of the spotted station
+/// - `{spotter}`: Callsign of the spotter
+/// - `{source}`: Listener the spot was received by, i.e. its label if set
+///
+/// Unknown values are replaced by `unknown`. Continents are only known if added by an enricher.
+///
+/// # Arguments
+///
+/// * `template`: Template of the topic
+/// * `line`: Received spot
+/// * `reserved`: Characters with a special meaning in topics, replaced by `_` within values
+///
+/// # Result
+///
+/// Returns the topic.
+pub fn render_topic(template: &str, line: &ReceivedLine, reserved: &[char]) -> String {
+    let (spotter, dx) = line
+        .parsed
+        .as_ref()
+        .and_then(|msg| msg.calls())
+        .unwrap_or((UNKNOWN, UNKNOWN));
+    let continent = |entity: &Option<Entity>| {
+        entity
+            .as_ref()
+            .map_or(UNKNOWN.to_string(), |entity| entity.continent.to_string())
+    };
+
+    let values = [
+        (
+            "{band}",
+            line.band
+                .map_or(UNKNOWN.to_string(), |band| band.to_string()),
+        ),
+        (
+            "{mode}",
+            line.mode
+                .map_or(UNKNOWN.to_string(), |mode| mode.to_string()),
+        ),
+        ("{continent}", continent(&line.dx_entity)),
+        ("{spotter_continent}", continent(&line.spotter_entity)),
+        ("{dx}", dx.to_string()),
+        ("{spotter}", spotter.to_string()),
+        ("{source}", line.source.to_string()),
+    ];
+
+    values
+        .iter()
+        .fold(template.to_string(), |topic, (placeholder, value)| {
+            topic.replace(placeholder, &value.replace(reserved, "_"))
+        })
+}
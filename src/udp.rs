@@ -0,0 +1,123 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
+
+use crate::line::ReceivedLine;
+use crate::record::SpotRecord;
+use crate::sink::{SinkError, SpotSink};
+
+/// Format of the datagrams sent by a `UdpSink`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum UdpFormat {
+    /// Received line as is
+    #[default]
+    Raw,
+
+    /// Template with the following placeholders, e.g. `{dx} {freq} {mode} {comment}`:
+    ///
+    /// - `{raw}`: Received line
+    /// - `{spotter}`: Callsign of the spotter
+    /// - `{dx}`: Callsign of the spotted station
+    /// - `{freq}`: Frequency in kHz with one decimal place, e.g. `14025.0`
+    /// - `{band}`: Band, e.g. `20m`
+    /// - `{mode}`: Mode, e.g. `CW`
+    /// - `{comment}`: Comment of the spot
+    /// - `{time}`: UTC time of the spot as HHMM
+    /// - `{date}`: UTC date of the spot as YYYY-MM-DD
+    /// - `{source}`: Listener the spot was received by, i.e. its label if set
+    ///
+    /// Unknown values are replaced by an empty string.
+    Template(String),
+}
+
+/// Sink sending each spot as UDP datagram, e.g. to the broadcast address of the LAN
+/// for logging programs listening for spots.
+/// Lines other than spots are ignored.
+#[derive(Debug)]
+pub struct UdpSink {
+    /// Address the datagrams are sent to, e.g. `192.168.1.255:12060`
+    pub target: SocketAddr,
+
+    /// Format of the datagrams
+    pub format: UdpFormat,
+
+    /// Socket to send the datagrams from
+    socket: UdpSocket,
+}
+
+impl UdpSink {
+    /// Create a new sink sending raw lines, allowing broadcast addresses as target.
+    ///
+    /// # Arguments
+    ///
+    /// * `target`: Address the datagrams are sent to
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `UdpSink` or `SinkError::Io` if the socket could not be created.
+    pub async fn bind(target: SocketAddr) -> Result<Self, SinkError> {
+        let local: SocketAddr = if target.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(local).await?;
+        socket.set_broadcast(true)?;
+
+        Ok(Self {
+            target,
+            format: UdpFormat::default(),
+            socket,
+        })
+    }
+}
+
+#[async_trait]
+impl SpotSink for UdpSink {
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        let record = match SpotRecord::from_line(&line) {
+            Some(record) => record,
+            None => return Ok(()),
+        };
+
+        let data = match &self.format {
+            UdpFormat::Raw => line.raw,
+            UdpFormat::Template(template) => render(template, &line.raw, &record),
+        };
+        self.socket.send_to(data.as_bytes(), self.target).await?;
+
+        Ok(())
+    }
+}
+
+/// Replace the placeholders of a template by the values of a spot.
+fn render(template: &str, raw: &str, record: &SpotRecord) -> String {
+    let values = [
+        ("{raw}", raw.to_string()),
+        ("{spotter}", record.spotter.clone()),
+        ("{dx}", record.dx.clone()),
+        ("{freq}", format!("{:.1}", record.freq as f64 / 1000.0)),
+        (
+            "{band}",
+            record.band.map(|band| band.to_string()).unwrap_or_default(),
+        ),
+        (
+            "{mode}",
+            record.mode.map(|mode| mode.to_string()).unwrap_or_default(),
+        ),
+        ("{comment}", record.comment.clone().unwrap_or_default()),
+        ("{time}", record.time.format("%H%M").to_string()),
+        ("{date}", record.time.format("%Y-%m-%d").to_string()),
+        ("{source}", record.source.clone()),
+    ];
+
+    values
+        .iter()
+        .fold(template.to_string(), |text, (placeholder, value)| {
+            text.replace(placeholder, value)
+        })
+}
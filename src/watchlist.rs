@@ -0,0 +1,49 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::enrich::Enricher;
+use crate::filter::CallPattern;
+use crate::line::ReceivedLine;
+
+/// Watchlist of needed stations.
+///
+/// As enricher, spots of stations on the watchlist are tagged by setting `ReceivedLine::priority`.
+/// Tagged spots may bypass the deduplication and rate limiting, see `Deduplicator::with_priority_bypass`,
+/// `CallThrottle::with_priority_bypass` and `RateLimiter::bypass_priority`.
+#[derive(Debug, Clone, Default)]
+pub struct Watchlist {
+    /// Patterns of watched callsigns
+    patterns: Vec<CallPattern>,
+}
+
+impl Watchlist {
+    /// Create an empty watchlist.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch a callsign, which may contain wildcards, e.g. `3Y0J` or `VK9*`.
+    pub fn with_call(self, call: &str) -> Self {
+        self.with_pattern(CallPattern::Wildcard(call.into()))
+    }
+
+    /// Watch all callsigns matching the pattern.
+    pub fn with_pattern(mut self, pattern: CallPattern) -> Self {
+        self.patterns.push(pattern);
+        self
+    }
+
+    /// Check if a callsign is on the watchlist.
+    pub fn contains(&self, call: &str) -> bool {
+        self.patterns.iter().any(|pattern| pattern.matches(call))
+    }
+}
+
+impl Enricher for Watchlist {
+    fn enrich(&self, line: &mut ReceivedLine) {
+        if let Some((_, dx)) = line.parsed.as_ref().and_then(|msg| msg.calls()) {
+            line.priority |= self.contains(dx);
+        }
+    }
+}
@@ -0,0 +1,313 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::time::Instant;
+
+use crate::line::ReceivedLine;
+use crate::sink::{SinkError, SpotSink};
+
+// Maximum time to wait for the response to a request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body of the requests sent by a `WebhookSink`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// Spot as JSON object, see `ReceivedLine::to_json`, or a JSON array of spots if batched
+    #[default]
+    Json,
+
+    /// JSON object with the received lines as text in the given field, one line per spot,
+    /// e.g. `content` for Discord or `text` for Slack webhooks
+    Message(String),
+}
+
+/// Sink posting spots to an HTTP endpoint, e.g. a webhook of a chat service or a hosted API.
+///
+/// Spots are collected until a batch is complete and posted in a single request.
+/// Failed requests are retried with the next delivery after an exponentially growing delay,
+/// spots received in the meantime are queued. Requests refused by the endpoint with a client error,
+/// except for `429 Too Many Requests`, are not retried.
+/// If the queue is full, the queued spots are moved to the overflow file, if set, and sent
+/// after the endpoint is available again. Otherwise the oldest spots are dropped.
+/// Lines other than spots are ignored.
+#[derive(Debug)]
+pub struct WebhookSink {
+    /// URL to post the spots to
+    pub url: String,
+
+    /// Body of the requests
+    pub format: WebhookFormat,
+
+    /// Number of spots posted in a single request
+    pub batch_size: usize,
+
+    /// Maximum number of queued spots while the endpoint is unavailable
+    pub max_pending: usize,
+
+    /// Delay before the first retry of a failed request, doubled with every further failure
+    pub backoff: Duration,
+
+    /// Maximum delay between retries
+    pub max_backoff: Duration,
+
+    /// File to move queued spots to if the queue is full, one spot per line
+    pub overflow: Option<PathBuf>,
+
+    /// HTTP client
+    client: reqwest::Client,
+
+    /// Encoded spots not posted yet
+    pending: VecDeque<String>,
+
+    /// Point in time of the next retry if the last request failed
+    retry_at: Option<Instant>,
+
+    /// Current delay between retries
+    delay: Duration,
+}
+
+/// Outcome of a failed request
+enum Failure {
+    /// Request may succeed if retried later
+    Temporary(SinkError),
+
+    /// Request was refused and will not succeed if retried
+    Permanent(SinkError),
+}
+
+impl WebhookSink {
+    /// Create a new sink posting every spot as JSON object.
+    ///
+    /// # Arguments
+    ///
+    /// * `url`: URL to post the spots to
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `WebhookSink` or `SinkError::Backend` if the HTTP client could not be created.
+    pub fn new(url: &str) -> Result<Self, SinkError> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(backend)?;
+
+        Ok(Self {
+            url: url.into(),
+            format: WebhookFormat::default(),
+            batch_size: 1,
+            max_pending: 1000,
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(300),
+            overflow: None,
+            client,
+            pending: VecDeque::new(),
+            retry_at: None,
+            delay: Duration::ZERO,
+        })
+    }
+
+    /// Encode a spot as item of a request body.
+    fn encode(&self, line: &ReceivedLine) -> String {
+        match self.format {
+            WebhookFormat::Json => line.to_json(),
+            WebhookFormat::Message(_) => line.raw.clone(),
+        }
+    }
+
+    /// Create the body of a request from encoded spots.
+    fn body(&self, items: &[String]) -> String {
+        match &self.format {
+            WebhookFormat::Json if self.batch_size <= 1 && items.len() == 1 => items[0].clone(),
+            WebhookFormat::Json => format!("[{}]", items.join(",")),
+            WebhookFormat::Message(field) => {
+                serde_json::json!({ field.as_str(): items.join("\n") }).to_string()
+            }
+        }
+    }
+
+    /// Post encoded spots in a single request.
+    async fn post(&self, items: &[String]) -> Result<(), Failure> {
+        let resp = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(self.body(items))
+            .send()
+            .await
+            .map_err(|err| Failure::Temporary(backend(err)))?;
+
+        let status = resp.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            let err = SinkError::Backend(format!("endpoint responded with {}", status));
+            if status.is_client_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                Err(Failure::Permanent(err))
+            } else {
+                Err(Failure::Temporary(err))
+            }
+        }
+    }
+
+    /// Post the spots of the overflow file, followed by the queued spots, in batches.
+    async fn send(&mut self) -> Result<(), SinkError> {
+        self.replay().await?;
+
+        while !self.pending.is_empty() {
+            let len = self.pending.len().min(self.batch_size.max(1));
+            let batch: Vec<String> = self.pending.range(..len).cloned().collect();
+            match self.post(&batch).await {
+                Ok(()) => {
+                    self.pending.drain(..len);
+                    self.retry_at = None;
+                    self.delay = Duration::ZERO;
+                }
+                Err(Failure::Permanent(err)) => {
+                    self.pending.drain(..len);
+                    return Err(err);
+                }
+                Err(Failure::Temporary(err)) => {
+                    self.failed();
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Post the spots of the overflow file, keeping the spots not posted in the file.
+    async fn replay(&mut self) -> Result<(), SinkError> {
+        let path = match &self.overflow {
+            Some(path) => path.clone(),
+            None => return Ok(()),
+        };
+        let data = match fs::read_to_string(&path).await {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let items: Vec<String> = data.lines().map(String::from).collect();
+        let mut sent = 0;
+        let mut result = Ok(());
+        for batch in items.chunks(self.batch_size.max(1)) {
+            match self.post(batch).await {
+                Ok(()) => sent += batch.len(),
+                Err(Failure::Permanent(err)) => {
+                    sent += batch.len();
+                    result = Err(err);
+                }
+                Err(Failure::Temporary(err)) => {
+                    self.failed();
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+
+        if sent == items.len() {
+            fs::remove_file(&path).await?;
+        } else {
+            let rest: String = items[sent..]
+                .iter()
+                .map(|item| item.clone() + "\n")
+                .collect();
+            fs::write(&path, rest).await?;
+        }
+
+        result
+    }
+
+    /// Move the queued spots to the overflow file or drop the oldest spots if the queue is full.
+    async fn spill(&mut self) -> Result<(), SinkError> {
+        if self.pending.len() <= self.max_pending {
+            return Ok(());
+        }
+
+        match self.overflow.clone() {
+            Some(path) => self.save(&path).await,
+            None => {
+                let dropped = self.pending.len() - self.max_pending;
+                self.pending.drain(..dropped);
+                Err(SinkError::Backend(format!(
+                    "queue is full, dropped {} spots",
+                    dropped
+                )))
+            }
+        }
+    }
+
+    /// Append the queued spots to the overflow file.
+    async fn save(&mut self, path: &Path) -> Result<(), SinkError> {
+        let data: String = self
+            .pending
+            .iter()
+            .map(|item| item.clone() + "\n")
+            .collect();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(data.as_bytes()).await?;
+        file.flush().await?;
+        self.pending.clear();
+
+        Ok(())
+    }
+
+    /// Schedule the next retry after a failed request.
+    fn failed(&mut self) {
+        self.delay = (self.delay * 2).clamp(self.backoff, self.max_backoff);
+        self.retry_at = Some(Instant::now() + self.delay);
+    }
+}
+
+#[async_trait]
+impl SpotSink for WebhookSink {
+    async fn deliver(&mut self, line: ReceivedLine) -> Result<(), SinkError> {
+        if line.parsed.as_ref().and_then(|msg| msg.calls()).is_none() {
+            return Ok(());
+        }
+
+        let item = self.encode(&line);
+        self.pending.push_back(item);
+
+        let result = match self.retry_at {
+            Some(retry_at) if Instant::now() < retry_at => Ok(()),
+            Some(_) => self.send().await,
+            None if self.pending.len() >= self.batch_size => self.send().await,
+            None => Ok(()),
+        };
+
+        if self.retry_at.is_some() {
+            self.spill().await?;
+        }
+
+        result
+    }
+
+    async fn flush(&mut self) -> Result<(), SinkError> {
+        let result = self.send().await;
+        if let (Err(_), Some(path)) = (&result, self.overflow.clone()) {
+            if !self.pending.is_empty() {
+                self.save(&path).await?;
+            }
+        }
+
+        result
+    }
+}
+
+/// Convert an HTTP client error.
+fn backend(err: reqwest::Error) -> SinkError {
+    SinkError::Backend(err.to_string())
+}
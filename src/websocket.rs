@@ -0,0 +1,194 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use futures_util::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::expr::FilterExpr;
+use crate::filter::Filter;
+use crate::line::ReceivedLine;
+
+// Query parameter holding the filter expression of a client
+const FILTER_PARAM: &str = "filter";
+
+/// Spot with its JSON representation, serialized once for all clients
+type Frame = (Arc<ReceivedLine>, Arc<str>);
+
+/// WebSocket server streaming received spots as JSON text frames, e.g. to browser clients.
+///
+/// Each frame holds a single spot in the same format as `ReceivedLine::to_json`.
+/// Clients may pass a filter expression in the query string to only receive matching spots,
+/// e.g. `ws://localhost:7380/?filter=band%20%3D%2020m` for `band = 20m`, see `FilterExpr`.
+/// Connections with an invalid expression are refused with status 400.
+/// Clients not keeping up with the stream miss spots instead of slowing down the server.
+#[derive(Debug, Clone)]
+pub struct WebSocketServer {
+    /// Address to listen on, e.g. `0.0.0.0:7380`
+    pub addr: SocketAddr,
+
+    /// Maximum number of connected clients, further clients are refused
+    pub max_clients: usize,
+
+    /// Number of spots buffered per client
+    pub queue: usize,
+}
+
+impl WebSocketServer {
+    /// Create a new server with default settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr`: Address to listen on
+    ///
+    /// # Result
+    ///
+    /// Returns a new instance of a `WebSocketServer`.
+    pub fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            max_clients: 100,
+            queue: 1000,
+        }
+    }
+
+    /// Start listening for clients and stream the received spots to them.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: Channel of the received lines, e.g. the merged output of multiple listeners
+    ///
+    /// # Result
+    ///
+    /// Returns the handle of the server task or an error if listening on the address failed.
+    /// The server stops and disconnects all clients once the input channel is closed or the task is aborted.
+    pub async fn spawn(
+        self,
+        mut input: mpsc::UnboundedReceiver<ReceivedLine>,
+    ) -> Result<JoinHandle<()>, std::io::Error> {
+        let listener = TcpListener::bind(self.addr).await?;
+        let (frames, _) = broadcast::channel::<Frame>(self.queue.max(1));
+        let max_clients = self.max_clients;
+
+        Ok(tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    line = input.recv() => match line {
+                        Some(line) if line.parsed.as_ref().and_then(|msg| msg.calls()).is_some() => {
+                            let json = line.to_json().into();
+                            let _ = frames.send((Arc::new(line), json));
+                        }
+                        Some(_) => (),
+                        None => break,
+                    },
+                    client = listener.accept() => {
+                        if let Ok((stream, _)) = client {
+                            let full = frames.receiver_count() >= max_clients;
+                            tokio::spawn(serve(stream, frames.subscribe(), full));
+                        }
+                    }
+                }
+            }
+        }))
+    }
+}
+
+/// Serve a single client until it disconnects or the server stops.
+async fn serve(stream: TcpStream, mut frames: broadcast::Receiver<Frame>, full: bool) {
+    let mut filter = None;
+    // Signature of the callback is given by the handshake
+    #[allow(clippy::result_large_err)]
+    let callback = |req: &Request, resp: Response| -> Result<Response, ErrorResponse> {
+        if full {
+            return Err(error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "too many clients".into(),
+            ));
+        }
+
+        if let Some(expr) = query_param(req.uri().query().unwrap_or_default(), FILTER_PARAM) {
+            let expr = FilterExpr::parse(&expr)
+                .map_err(|err| error(StatusCode::BAD_REQUEST, err.to_string()))?;
+            filter = Some(expr);
+        }
+
+        Ok(resp)
+    };
+
+    let mut ws = match tokio_tungstenite::accept_hdr_async(stream, callback).await {
+        Ok(ws) => ws,
+        Err(_) => return,
+    };
+
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Ok((line, json)) => {
+                    let rejected = filter.as_ref().is_some_and(|filter| !filter.accept(&line));
+                    if !rejected && ws.send(Message::text(json.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => (),
+                Err(broadcast::error::RecvError::Closed) => {
+                    let _ = ws.close(None).await;
+                    break;
+                }
+            },
+            msg = ws.next() => match msg {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => (),
+            }
+        }
+    }
+}
+
+/// Create an HTTP error response refusing the connection.
+fn error(status: StatusCode, msg: String) -> ErrorResponse {
+    let mut resp = ErrorResponse::new(Some(msg));
+    *resp.status_mut() = status;
+    resp
+}
+
+/// Get the percent-decoded value of a parameter of a query string.
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| percent_decode(value))
+}
+
+/// Decode a percent-encoded value of a query string, `+` is decoded as space.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,170 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use futures_util::{SinkExt, StreamExt};
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::WebSocketStream;
+
+use crate::connection::BoxedTransport;
+use crate::listener::ListenError;
+
+/// Transport speaking the cluster protocol over a WebSocket connection, e.g. to a web-facing cluster gateway.
+///
+/// The payload of received text and binary messages is read as continuous stream,
+/// so the server is expected to send the line breaks of the cluster protocol.
+/// Written data is sent as text message, or as binary message if it is not valid UTF-8.
+/// Pings of the server are answered automatically, a close message ends the stream.
+pub struct WebSocketTransport {
+    /// WebSocket connection
+    ws: WebSocketStream<BoxedTransport>,
+
+    /// Payload of the last received message
+    buffer: Vec<u8>,
+
+    /// Number of bytes of the buffer already read
+    pos: usize,
+}
+
+impl WebSocketTransport {
+    /// Open a WebSocket connection on an established stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream`: Connected stream to the server, already encrypted for `wss` URLs
+    /// * `url`: URL of the WebSocket endpoint, e.g. `wss://gateway.example:443/cluster`
+    ///
+    /// # Result
+    ///
+    /// Returns the transport, `ListenError::WebSocketError` if the server refused the handshake,
+    /// or `ListenError::ConnectionError` if the connection failed during the handshake.
+    pub async fn connect(stream: BoxedTransport, url: &str) -> Result<Self, ListenError> {
+        let (ws, _) =
+            tokio_tungstenite::client_async(url, stream)
+                .await
+                .map_err(|err| match err {
+                    tungstenite::Error::Io(_) => ListenError::ConnectionError,
+                    err => ListenError::WebSocketError(err.to_string()),
+                })?;
+
+        Ok(Self {
+            ws,
+            buffer: Vec::new(),
+            pos: 0,
+        })
+    }
+}
+
+impl AsyncRead for WebSocketTransport {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+
+        while this.pos >= this.buffer.len() {
+            match ready!(this.ws.poll_next_unpin(cx)) {
+                Some(Ok(Message::Text(text))) => this.buffer = text.into_bytes(),
+                Some(Ok(Message::Binary(data))) => this.buffer = data,
+                Some(Ok(Message::Close(_))) | None => return Poll::Ready(Ok(())),
+                Some(Ok(_)) => continue,
+                Some(Err(err)) => return Poll::Ready(Err(io_error(err))),
+            }
+            this.pos = 0;
+        }
+
+        let len = buf.remaining().min(this.buffer.len() - this.pos);
+        buf.put_slice(&this.buffer[this.pos..this.pos + len]);
+        this.pos += len;
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for WebSocketTransport {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.ws.poll_ready_unpin(cx)).map_err(io_error)?;
+
+        let msg = match std::str::from_utf8(buf) {
+            Ok(text) => Message::Text(text.into()),
+            Err(_) => Message::Binary(buf.to_vec()),
+        };
+        self.ws.start_send_unpin(msg).map_err(io_error)?;
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.ws.poll_flush_unpin(cx).map_err(io_error)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.ws.poll_close_unpin(cx).map_err(io_error)
+    }
+}
+
+/// Convert a WebSocket error.
+/// Errors other than IO errors leave the connection unusable and are reported as reset connection.
+fn io_error(err: tungstenite::Error) -> io::Error {
+    match err {
+        tungstenite::Error::Io(err) => err,
+        err => io::Error::new(io::ErrorKind::ConnectionReset, err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn lines_over_messages() {
+        let (client, server) = tokio::io::duplex(4096);
+        let script = tokio::spawn(async move {
+            let mut ws = tokio_tungstenite::accept_async(server).await.unwrap();
+            ws.send(Message::Text("Welcome\r\nlog".into()))
+                .await
+                .unwrap();
+            ws.send(Message::Ping(vec![1])).await.unwrap();
+            ws.send(Message::Binary(b"in: ".to_vec())).await.unwrap();
+
+            // The pong is received before the answer
+            let mut received = Vec::new();
+            while let Some(msg) = ws.next().await {
+                match msg.unwrap() {
+                    Message::Close(_) => break,
+                    msg => received.push(msg),
+                }
+            }
+            received
+        });
+
+        let mut transport = WebSocketTransport::connect(Box::new(client), "ws://gateway.example/")
+            .await
+            .unwrap();
+        let mut data = [0u8; 14];
+        transport.read_exact(&mut data).await.unwrap();
+        assert_eq!(&data, b"Welcome\r\nlogin");
+        transport.write_all(b"N0CALL\r\n").await.unwrap();
+        transport.write_all(b"\xff\r\n").await.unwrap();
+        transport.shutdown().await.unwrap();
+
+        assert_eq!(
+            script.await.unwrap(),
+            [
+                Message::Pong(vec![1]),
+                Message::Text("N0CALL\r\n".into()),
+                Message::Binary(b"\xff\r\n".to_vec())
+            ]
+        );
+    }
+}
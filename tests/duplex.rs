@@ -0,0 +1,247 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream};
+use tokio::sync::mpsc;
+use tokio::time;
+
+use dxcllistener::{ClusterMessage, ListenError, Listener, ReceivedLine};
+
+// Time to wait for the listener to react to the scripted server
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// Spot sent by the scripted server
+const SPOT: &str = "DX de W3LPL:     14025.0  DL1ABC       CW 599                      1200Z";
+
+// Command prompt of the scripted server
+const PROMPT: &str = "N0CALL de DB0ABC 16-Oct-2026 1200Z dxspider >";
+
+/// Start a listener talking to a scripted server.
+async fn start() -> (
+    Listener,
+    BufReader<DuplexStream>,
+    mpsc::UnboundedReceiver<ReceivedLine>,
+) {
+    start_with(|_| {}).await
+}
+
+/// Start a listener talking to a scripted server after adjusting its settings.
+async fn start_with(
+    configure: impl FnOnce(&mut Listener),
+) -> (
+    Listener,
+    BufReader<DuplexStream>,
+    mpsc::UnboundedReceiver<ReceivedLine>,
+) {
+    let (mut listener, server) = Listener::duplex("N0CALL".into(), 4096);
+    listener.auth_timeout = Duration::from_millis(100);
+    configure(&mut listener);
+    let (tx, rx) = mpsc::unbounded_channel();
+    listener.listen(tx, TIMEOUT).await.unwrap();
+
+    (listener, BufReader::new(server), rx)
+}
+
+/// Read the next line sent by the listener.
+async fn read_line(server: &mut BufReader<DuplexStream>) -> String {
+    let mut line = String::new();
+    time::timeout(TIMEOUT, server.read_line(&mut line))
+        .await
+        .unwrap()
+        .unwrap();
+    line.trim_end().into()
+}
+
+/// Send the login prompt and check the callsign sent back.
+async fn login(server: &mut BufReader<DuplexStream>) {
+    server.write_all(b"Welcome\r\nlogin: ").await.unwrap();
+    assert_eq!(read_line(server).await, "N0CALL");
+}
+
+/// Receive the next delivered line.
+async fn receive(rx: &mut mpsc::UnboundedReceiver<ReceivedLine>) -> ReceivedLine {
+    time::timeout(TIMEOUT, rx.recv()).await.unwrap().unwrap()
+}
+
+/// Wait for the listener to stop and get its result.
+async fn join(listener: &mut Listener) -> Result<(), ListenError> {
+    time::timeout(TIMEOUT, listener.join()).await.unwrap()
+}
+
+#[tokio::test]
+async fn login_and_spots() {
+    let (mut listener, mut server, mut rx) = start().await;
+    login(&mut server).await;
+
+    // The greeting terminates the line of the login prompt
+    server
+        .write_all(
+            format!(
+                "Hello N0CALL\r\n{}\r\nWWV de W0MU <18>:   SFI=68, A=4, K=1, No Storms\r\n",
+                SPOT
+            )
+            .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+    let line = receive(&mut rx).await;
+    assert_eq!(line.raw, " Hello N0CALL");
+    assert!(matches!(line.parsed, Some(ClusterMessage::Unknown(_))));
+
+    let line = receive(&mut rx).await;
+    assert_eq!(line.raw, SPOT);
+    assert!(line.parsed.as_ref().is_some_and(ClusterMessage::is_spot));
+    assert_eq!(line.source.host, "duplex");
+
+    let line = receive(&mut rx).await;
+    assert!(matches!(line.parsed, Some(ClusterMessage::Wwv(_))));
+
+    assert_eq!(listener.session_callsign().as_deref(), Some("N0CALL"));
+    assert_eq!(listener.stats().spots, 1);
+
+    listener.request_stop().unwrap();
+    assert_eq!(join(&mut listener).await, Ok(()));
+}
+
+#[tokio::test]
+async fn prompt_and_command() {
+    let (mut listener, mut server, mut rx) = start().await;
+    login(&mut server).await;
+
+    // The prompt is not terminated by a line break
+    server.write_all(PROMPT.as_bytes()).await.unwrap();
+    time::timeout(TIMEOUT, async {
+        while listener.prompt().is_none() {
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(listener.prompt().as_deref(), Some(PROMPT));
+
+    let (response, _) = tokio::join!(listener.command("sh/wwv"), async {
+        assert_eq!(read_line(&mut server).await, "sh/wwv");
+        server
+            .write_all(
+                format!(" Date Hour SFI A K\r\n16-Oct-2026 18 68 4 1\r\n{}", PROMPT).as_bytes(),
+            )
+            .await
+            .unwrap();
+    });
+    assert_eq!(
+        response.unwrap(),
+        vec![" Date Hour SFI A K", "16-Oct-2026 18 68 4 1"]
+    );
+
+    // Neither the prompts nor the response are delivered as lines
+    server
+        .write_all(format!("{}\r\n", SPOT).as_bytes())
+        .await
+        .unwrap();
+    assert_eq!(receive(&mut rx).await.raw, SPOT);
+
+    listener.request_stop().unwrap();
+    assert_eq!(join(&mut listener).await, Ok(()));
+}
+
+#[tokio::test]
+async fn user_prompt_answered() {
+    let (mut listener, mut server, mut rx) =
+        start_with(|listener| listener.login_name = Some("Operator".into())).await;
+    login(&mut server).await;
+
+    server
+        .write_all(format!("Hello N0CALL\r\n{}\r\nPlease enter your name: ", SPOT).as_bytes())
+        .await
+        .unwrap();
+    assert_eq!(read_line(&mut server).await, "Operator");
+
+    // Lines received before the prompt are still delivered
+    assert_eq!(receive(&mut rx).await.raw, " Hello N0CALL");
+    assert_eq!(receive(&mut rx).await.raw, SPOT);
+
+    listener.request_stop().unwrap();
+    assert_eq!(join(&mut listener).await, Ok(()));
+}
+
+#[tokio::test]
+async fn login_rejected() {
+    let (mut listener, mut server, mut rx) = start().await;
+    login(&mut server).await;
+
+    server
+        .write_all(b"N0CALL\r\nSorry, N0CALL is not a valid callsign\r\n")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        join(&mut listener).await,
+        Err(ListenError::LoginRejected(
+            "Sorry, N0CALL is not a valid callsign".into()
+        ))
+    );
+    assert!(rx.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn rejection_in_motd_ignored() {
+    let (mut listener, mut server, mut rx) = start().await;
+    login(&mut server).await;
+
+    server
+        .write_all(
+            format!(
+                "Hello N0CALL\r\nSpots of pirate stations are not allowed\r\n{}\r\n",
+                SPOT
+            )
+            .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+    receive(&mut rx).await;
+    receive(&mut rx).await;
+    assert_eq!(receive(&mut rx).await.raw, SPOT);
+
+    listener.request_stop().unwrap();
+    assert_eq!(join(&mut listener).await, Ok(()));
+}
+
+#[tokio::test]
+async fn callsign_in_use() {
+    let (mut listener, mut server, _rx) = start().await;
+    login(&mut server).await;
+
+    server
+        .write_all(b"N0CALL is already connected\r\n")
+        .await
+        .unwrap();
+
+    assert_eq!(join(&mut listener).await, Err(ListenError::CallsignInUse));
+}
+
+#[tokio::test]
+async fn connection_closed_after_login() {
+    let (mut listener, mut server, _rx) = start().await;
+    login(&mut server).await;
+    drop(server);
+
+    assert!(matches!(
+        join(&mut listener).await,
+        Err(ListenError::LoginRejected(_))
+    ));
+}
+
+#[tokio::test]
+async fn authentication_timeout() {
+    let (mut listener, mut server, _rx) = start().await;
+    server.write_all(b"Welcome to the node\r\n").await.unwrap();
+
+    assert_eq!(
+        join(&mut listener).await,
+        Err(ListenError::AuthenticationTimeout)
+    );
+}